@@ -0,0 +1,213 @@
+use std::time::Duration;
+
+use flom_core::{FlomError, FlomResult};
+use flom_music::MusicConverter;
+use futures_util::{SinkExt, StreamExt};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::reply::reply_for;
+
+const GATEWAY_URL: &str = "wss://gateway.discord.gg/?v=10&encoding=json";
+const API_BASE: &str = "https://discord.com/api/v10";
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+// Gateway intents: GUILD_MESSAGES | DIRECT_MESSAGES | MESSAGE_CONTENT
+const INTENTS: u64 = (1 << 9) | (1 << 12) | (1 << 15);
+
+const OP_DISPATCH: u64 = 0;
+const OP_HEARTBEAT: u64 = 1;
+const OP_IDENTIFY: u64 = 2;
+const OP_RECONNECT: u64 = 7;
+const OP_INVALID_SESSION: u64 = 9;
+const OP_HELLO: u64 = 10;
+
+#[derive(Debug, Deserialize)]
+struct GatewayEvent {
+    op: u64,
+    #[serde(default)]
+    d: Value,
+    s: Option<u64>,
+    t: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageCreate {
+    id: String,
+    channel_id: String,
+    content: String,
+    author: Author,
+}
+
+#[derive(Debug, Deserialize)]
+struct Author {
+    #[serde(default)]
+    bot: bool,
+}
+
+/// Connects to the gateway and replies to messages containing music links,
+/// reconnecting whenever Discord drops or recycles the session.
+pub async fn run(token: &str, converter: &MusicConverter, target: &str) -> FlomResult<()> {
+    let client = Client::builder()
+        .user_agent("flom-bot/0.1")
+        .build()
+        .map_err(|err| FlomError::Network(format!("failed to build http client: {err}")))?;
+    loop {
+        match session(token, &client, converter, target).await {
+            Err(FlomError::Api(message)) => return Err(FlomError::Api(message)),
+            Err(err) => eprintln!("discord: {err}"),
+            Ok(()) => {}
+        }
+        tokio::time::sleep(RETRY_DELAY).await;
+    }
+}
+
+/// Runs one gateway session until it is closed.
+async fn session(
+    token: &str,
+    client: &Client,
+    converter: &MusicConverter,
+    target: &str,
+) -> FlomResult<()> {
+    let (socket, _) = connect_async(GATEWAY_URL)
+        .await
+        .map_err(|err| FlomError::Network(format!("gateway connect failed: {err}")))?;
+    let (mut sink, mut stream) = socket.split();
+    let mut heartbeat = heartbeat_interval(Duration::from_secs(45));
+    let mut sequence: Option<u64> = None;
+
+    loop {
+        tokio::select! {
+            _ = heartbeat.tick() => send_heartbeat(&mut sink, sequence).await?,
+            message = stream.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(frame))) => {
+                        return match frame {
+                            // 4004: authentication failed
+                            Some(frame) if u16::from(frame.code) == 4004 => Err(FlomError::Api(
+                                "discord rejected the bot token".to_string(),
+                            )),
+                            _ => Ok(()),
+                        };
+                    }
+                    Some(Ok(_)) => continue,
+                    Some(Err(err)) => {
+                        return Err(FlomError::Network(format!("gateway error: {err}")));
+                    }
+                    None => return Ok(()),
+                };
+                let event: GatewayEvent = serde_json::from_str(&text)
+                    .map_err(|err| FlomError::Parse(format!("gateway event parse failed: {err}")))?;
+                if event.s.is_some() {
+                    sequence = event.s;
+                }
+                match event.op {
+                    OP_HELLO => {
+                        let interval = event.d["heartbeat_interval"].as_u64().unwrap_or(45_000);
+                        heartbeat = heartbeat_interval(Duration::from_millis(interval));
+                        let identify = json!({
+                            "op": OP_IDENTIFY,
+                            "d": {
+                                "token": token,
+                                "intents": INTENTS,
+                                "properties": { "os": std::env::consts::OS, "browser": "flom-bot", "device": "flom-bot" },
+                            },
+                        });
+                        sink.send(Message::text(identify.to_string()))
+                            .await
+                            .map_err(|err| FlomError::Network(format!("identify failed: {err}")))?;
+                    }
+                    // The gateway asks for a heartbeat right away.
+                    OP_HEARTBEAT => send_heartbeat(&mut sink, sequence).await?,
+                    OP_RECONNECT | OP_INVALID_SESSION => return Ok(()),
+                    OP_DISPATCH if event.t.as_deref() == Some("MESSAGE_CREATE") => {
+                        let Ok(message) = serde_json::from_value::<MessageCreate>(event.d) else {
+                            continue;
+                        };
+                        if message.author.bot {
+                            continue;
+                        }
+                        // Converting can take a while; heartbeats must go on meanwhile.
+                        let (client, token) = (client.clone(), token.to_string());
+                        let (converter, target) = (converter.clone(), target.to_string());
+                        tokio::spawn(async move {
+                            if let Some(reply) = reply_for(&converter, &message.content, &target).await
+                                && let Err(err) = send_reply(&client, &token, &message, &reply).await
+                            {
+                                eprintln!("discord: {err}");
+                            }
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+async fn send_heartbeat<S>(sink: &mut S, sequence: Option<u64>) -> FlomResult<()>
+where
+    S: SinkExt<Message> + Unpin,
+    S::Error: std::fmt::Display,
+{
+    let payload = json!({ "op": OP_HEARTBEAT, "d": sequence });
+    sink.send(Message::text(payload.to_string()))
+        .await
+        .map_err(|err| FlomError::Network(format!("heartbeat failed: {err}")))
+}
+
+/// Heartbeats start one period from now; the gateway expects Hello first.
+fn heartbeat_interval(period: Duration) -> tokio::time::Interval {
+    tokio::time::interval_at(tokio::time::Instant::now() + period, period)
+}
+
+async fn send_reply(
+    client: &Client,
+    token: &str,
+    message: &MessageCreate,
+    text: &str,
+) -> FlomResult<()> {
+    let response = client
+        .post(format!(
+            "{API_BASE}/channels/{}/messages",
+            message.channel_id
+        ))
+        .header("Authorization", format!("Bot {token}"))
+        .json(&json!({
+            "content": text,
+            "message_reference": { "message_id": message.id },
+            "allowed_mentions": { "parse": [] },
+            "flags": 1 << 2, // SUPPRESS_EMBEDS
+        }))
+        .send()
+        .await
+        .map_err(|err| FlomError::Network(format!("create message failed: {err}")))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(FlomError::Api(format!(
+            "create message error: status={status} body={body}"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_message_create_dispatch() {
+        let payload = r#"{"op":0,"s":3,"t":"MESSAGE_CREATE","d":{"id":"11","channel_id":"22","content":"listen https://youtu.be/xyz","author":{"id":"33","username":"a"}}}"#;
+        let event: GatewayEvent = serde_json::from_str(payload).unwrap();
+        assert_eq!(event.op, OP_DISPATCH);
+        assert_eq!(event.s, Some(3));
+        let message: MessageCreate = serde_json::from_value(event.d).unwrap();
+        assert_eq!(message.channel_id, "22");
+        assert!(!message.author.bot);
+    }
+}
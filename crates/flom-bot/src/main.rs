@@ -0,0 +1,85 @@
+#[cfg(not(any(feature = "telegram", feature = "discord")))]
+compile_error!("flom-bot needs at least one of the `telegram` or `discord` features");
+
+#[cfg(feature = "discord")]
+mod discord;
+mod reply;
+#[cfg(feature = "telegram")]
+mod telegram;
+
+use clap::{Parser, Subcommand};
+use flom_config::{
+    flom_dir, load_config, load_env_files, resolve_default_target, resolve_entity_db_enabled,
+    resolve_odesli_key, resolve_user_country,
+};
+use flom_core::{FlomError, FlomResult, validate_country_code};
+use flom_music::MusicConverter;
+
+#[derive(Debug, Parser)]
+#[command(name = "flom-bot")]
+#[command(about = "Chat bot that replies to music links with converted links", long_about = None)]
+struct Cli {
+    /// Target platform for replies (defaults to `bot.target`, then `default.target`)
+    #[arg(long)]
+    to: Option<String>,
+    #[command(subcommand)]
+    platform: Platform,
+}
+
+#[derive(Debug, Subcommand)]
+enum Platform {
+    /// Run as a Telegram bot (token: `bot.telegram_token` or FLOM_TELEGRAM_TOKEN)
+    #[cfg(feature = "telegram")]
+    Telegram,
+    /// Run as a Discord bot (token: `bot.discord_token` or FLOM_DISCORD_TOKEN)
+    #[cfg(feature = "discord")]
+    Discord,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    // Sets environment variables, so it has to run before the runtime starts
+    // its worker threads.
+    let result = load_env_files().and_then(|()| {
+        let runtime = tokio::runtime::Runtime::new()
+            .map_err(|err| FlomError::Config(format!("failed to start the runtime: {err}")))?;
+        runtime.block_on(run(cli))
+    });
+    if let Err(err) = result {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> FlomResult<()> {
+    let config = load_config()?;
+    validate_country_code(&resolve_user_country(&config))?;
+    let mut converter = MusicConverter::new(resolve_odesli_key(&config), &config);
+    if resolve_entity_db_enabled(&config) {
+        converter = converter.with_entity_db(flom_dir()?.join("entities.jsonl"));
+    }
+    let target = cli
+        .to
+        .or_else(|| config.bot.target.clone())
+        .or_else(|| resolve_default_target(&config))
+        .unwrap_or_else(|| "songlink".to_string());
+
+    match cli.platform {
+        #[cfg(feature = "telegram")]
+        Platform::Telegram => {
+            let token = flom_config::resolve_telegram_token(&config)
+                .ok_or_else(|| missing_token("bot.telegram_token", "FLOM_TELEGRAM_TOKEN"))?;
+            telegram::run(&token, &converter, &target).await
+        }
+        #[cfg(feature = "discord")]
+        Platform::Discord => {
+            let token = flom_config::resolve_discord_token(&config)
+                .ok_or_else(|| missing_token("bot.discord_token", "FLOM_DISCORD_TOKEN"))?;
+            discord::run(&token, &converter, &target).await
+        }
+    }
+}
+
+fn missing_token(key: &str, env: &str) -> FlomError {
+    FlomError::Config(format!("no bot token configured; set `{key}` or {env}"))
+}
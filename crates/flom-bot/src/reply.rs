@@ -0,0 +1,43 @@
+use flom_core::{FlomError, FlomResult};
+use flom_music::MusicConverter;
+use flom_music::extract::extract_music_urls;
+
+/// Builds the reply for a chat message: one converted link per music link in
+/// `text`, or `None` when the message contains no music links.
+pub async fn reply_for(converter: &MusicConverter, text: &str, target: &str) -> Option<String> {
+    let urls = extract_music_urls(text);
+    if urls.is_empty() {
+        return None;
+    }
+    let mut lines = Vec::with_capacity(urls.len());
+    for url in &urls {
+        lines.push(match convert(converter, url, target).await {
+            Ok(line) => line,
+            Err(err) => format!("{url}: {err}"),
+        });
+    }
+    Some(lines.join("\n"))
+}
+
+async fn convert(converter: &MusicConverter, url: &str, target: &str) -> FlomResult<String> {
+    let response = converter.fetch_links(url).await?;
+    if target == "songlink" {
+        return Ok(response.page_url);
+    }
+    let key = MusicConverter::normalize_target(target)
+        .ok_or_else(|| FlomError::InvalidInput(format!("unknown target: {target}")))?;
+    let result = converter.convert(&response, url, &key)?;
+    let link = result
+        .target_url
+        .ok_or_else(|| FlomError::Api(format!("no {key} link available")))?;
+    let title = result
+        .source_info
+        .and_then(|info| match (info.title, info.artist) {
+            (Some(title), Some(artist)) => Some(format!("{title} – {artist}")),
+            (title, _) => title,
+        });
+    Ok(match title {
+        Some(title) => format!("{title}\n{link}"),
+        None => link,
+    })
+}
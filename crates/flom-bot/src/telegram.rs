@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use flom_core::{FlomError, FlomResult};
+use flom_music::MusicConverter;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::reply::reply_for;
+
+const API_BASE: &str = "https://api.telegram.org";
+/// Long-polling timeout for `getUpdates`, in seconds.
+const POLL_TIMEOUT: u64 = 50;
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse<T> {
+    ok: bool,
+    result: Option<T>,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Update {
+    update_id: i64,
+    message: Option<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    message_id: i64,
+    chat: Chat,
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+/// Polls the Bot API for messages and replies to those containing music links.
+pub async fn run(token: &str, converter: &MusicConverter, target: &str) -> FlomResult<()> {
+    let client = Client::builder()
+        .user_agent("flom-bot/0.1")
+        .timeout(Duration::from_secs(POLL_TIMEOUT + 10))
+        .build()
+        .map_err(|err| FlomError::Network(format!("failed to build http client: {err}")))?;
+    let mut offset = 0;
+    loop {
+        let updates = match get_updates(&client, token, offset).await {
+            Ok(updates) => updates,
+            // A bad token never recovers; anything else is retried.
+            Err(FlomError::Api(message)) => return Err(FlomError::Api(message)),
+            Err(err) => {
+                eprintln!("telegram: {err}");
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            }
+        };
+        for update in updates {
+            offset = offset.max(update.update_id + 1);
+            let Some(message) = update.message else {
+                continue;
+            };
+            let Some(text) = message.text.as_deref() else {
+                continue;
+            };
+            if let Some(reply) = reply_for(converter, text, target).await
+                && let Err(err) = send_reply(&client, token, &message, &reply).await
+            {
+                eprintln!("telegram: {err}");
+            }
+        }
+    }
+}
+
+async fn get_updates(client: &Client, token: &str, offset: i64) -> FlomResult<Vec<Update>> {
+    let response = client
+        .get(format!("{API_BASE}/bot{token}/getUpdates"))
+        .query(&[
+            ("offset", offset.to_string()),
+            ("timeout", POLL_TIMEOUT.to_string()),
+            ("allowed_updates", r#"["message"]"#.to_string()),
+        ])
+        .send()
+        .await
+        .map_err(|err| FlomError::Network(format!("getUpdates failed: {}", err.without_url())))?;
+    let status = response.status();
+    let payload = response
+        .json::<ApiResponse<Vec<Update>>>()
+        .await
+        .map_err(|err| {
+            FlomError::Parse(format!(
+                "getUpdates response parse failed: {}",
+                err.without_url()
+            ))
+        })?;
+    if status.as_u16() == 401 || status.as_u16() == 404 {
+        return Err(FlomError::Api(format!(
+            "telegram rejected the bot token: {}",
+            payload.description.unwrap_or_default()
+        )));
+    }
+    match payload {
+        ApiResponse {
+            ok: true,
+            result: Some(updates),
+            ..
+        } => Ok(updates),
+        ApiResponse { description, .. } => Err(FlomError::Network(format!(
+            "getUpdates failed: status={status} {}",
+            description.unwrap_or_default()
+        ))),
+    }
+}
+
+async fn send_reply(client: &Client, token: &str, message: &Message, text: &str) -> FlomResult<()> {
+    let response = client
+        .post(format!("{API_BASE}/bot{token}/sendMessage"))
+        .json(&json!({
+            "chat_id": message.chat.id,
+            "text": text,
+            "reply_to_message_id": message.message_id,
+            "disable_web_page_preview": true,
+        }))
+        .send()
+        .await
+        .map_err(|err| FlomError::Network(format!("sendMessage failed: {}", err.without_url())))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(FlomError::Api(format!(
+            "sendMessage error: status={status} body={body}"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_updates_payload() {
+        let payload = r#"{"ok":true,"result":[
+            {"update_id":10,"message":{"message_id":5,"chat":{"id":-42,"type":"group"},"text":"https://open.spotify.com/track/abc"}},
+            {"update_id":11,"edited_message":{"message_id":6,"chat":{"id":1}}}
+        ]}"#;
+        let response: ApiResponse<Vec<Update>> = serde_json::from_str(payload).unwrap();
+        let updates = response.result.unwrap();
+        assert_eq!(updates.len(), 2);
+        let message = updates[0].message.as_ref().unwrap();
+        assert_eq!(message.chat.id, -42);
+        assert_eq!(
+            message.text.as_deref(),
+            Some("https://open.spotify.com/track/abc")
+        );
+        assert!(updates[1].message.is_none());
+    }
+}
@@ -9,18 +9,105 @@ pub struct ApiConfig {
 pub struct DefaultConfig {
     pub target: Option<String>,
     pub user_country: Option<String>,
+    /// Preferred metadata language as a BCP 47 tag, e.g. `ja-JP`.
+    pub locale: Option<String>,
+    /// Offer to convert the link on the clipboard when no URL is given.
+    pub clipboard_fallback: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct OutputConfig {
     pub simple: Option<bool>,
+    /// Transliterate titles, artists and albums to ASCII.
+    pub romanize: Option<bool>,
+    /// Default for `--output`: `human`, `json`, `gha` or `csv`.
+    pub format: Option<String>,
+    /// Default for `--template`, e.g. `{title} — {artist}: {target_url}`.
+    pub template: Option<String>,
+    /// Clickable OSC 8 links in human output; detected from the terminal when unset.
+    pub hyperlinks: Option<bool>,
+    /// Emoji before platform names in human output.
+    pub icons: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OdesliConfig {
+    pub song_if_single: Option<bool>,
+    /// Remember resolved tracks across runs in `~/.flom/entities.jsonl`.
+    pub entity_db: Option<bool>,
+    /// How long responses stay in `~/.flom/cache`; 0 turns the cache off.
+    pub cache_ttl_secs: Option<u64>,
+}
+
+/// Thresholds for fuzzy track matching.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MatchingConfig {
+    /// Minimum similarity (0.0 to 1.0) for two tracks to count as the same.
+    pub min_score: Option<f64>,
+    /// Largest duration difference in seconds still considered the same track.
+    pub duration_tolerance_secs: Option<u64>,
+    /// Fail conversions that come with a warning instead of printing them.
+    pub strict: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HistoryConfig {
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LogConfig {
+    pub enabled: Option<bool>,
+    pub path: Option<String>,
+    /// Maximum size of the active log file in bytes before it is rotated.
+    pub max_size: Option<u64>,
+    /// Number of rotated files to keep.
+    pub keep: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PostConfig {
+    /// Template for `flom post`; `{link:<platform>}` inserts a platform's link.
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BotConfig {
+    pub telegram_token: Option<String>,
+    pub discord_token: Option<String>,
+    /// Target for bot replies; falls back to `default.target`.
+    pub target: Option<String>,
+}
+
+/// OAuth credentials for creating playlists on a Spotify account.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SpotifyConfig {
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    /// Refresh token with the `playlist-modify-private` scope.
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SigningConfig {
+    /// Base64 Ed25519 private key (PKCS#8 or a 32-byte seed) for signing JSON reports.
+    pub key: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct FlomConfig {
     pub api: ApiConfig,
     pub default: DefaultConfig,
     pub output: OutputConfig,
+    pub odesli: OdesliConfig,
+    pub matching: MatchingConfig,
+    pub history: HistoryConfig,
+    pub log: LogConfig,
+    pub post: PostConfig,
+    pub bot: BotConfig,
+    pub spotify: SpotifyConfig,
+    pub signing: SigningConfig,
 }
 
 #[cfg(test)]
@@ -0,0 +1,143 @@
+#[cfg(feature = "encryption")]
+use std::io::{Read, Write};
+#[cfg(feature = "encryption")]
+use std::iter;
+use std::path::PathBuf;
+
+#[cfg(feature = "encryption")]
+use age::armor::{ArmoredReader, ArmoredWriter, Format};
+#[cfg(feature = "encryption")]
+use age::secrecy::SecretString;
+#[cfg(feature = "encryption")]
+use age::{Decryptor, Encryptor, IdentityFile};
+use flom_core::{FlomError, FlomResult};
+
+const ARMOR_HEADER: &[u8] = b"-----BEGIN AGE ENCRYPTED FILE-----";
+const BINARY_HEADER: &[u8] = b"age-encryption.org/";
+
+/// Secret used to encrypt or decrypt the config file.
+#[derive(Debug, Clone)]
+pub enum ConfigKey {
+    Passphrase(String),
+    Identity(PathBuf),
+}
+
+pub fn is_encrypted(content: &[u8]) -> bool {
+    let trimmed = content.trim_ascii_start();
+    trimmed.starts_with(ARMOR_HEADER) || trimmed.starts_with(BINARY_HEADER)
+}
+
+#[cfg(feature = "encryption")]
+pub(crate) fn encrypt(plaintext: &str, key: &ConfigKey) -> FlomResult<String> {
+    let encryptor = match key {
+        ConfigKey::Passphrase(passphrase) => {
+            Encryptor::with_user_passphrase(SecretString::from(passphrase.clone()))
+        }
+        ConfigKey::Identity(path) => {
+            let recipients = read_identity_file(path)?
+                .to_recipients()
+                .map_err(|err| FlomError::Config(format!("invalid identity file: {err}")))?;
+            Encryptor::with_recipients(recipients.iter().map(|r| r.as_ref() as _))
+                .map_err(|err| FlomError::Config(format!("failed to encrypt config: {err}")))?
+        }
+    };
+
+    let mut ciphertext = Vec::with_capacity(plaintext.len());
+    let armored = ArmoredWriter::wrap_output(&mut ciphertext, Format::AsciiArmor)
+        .map_err(|err| FlomError::Config(format!("failed to encrypt config: {err}")))?;
+    let mut writer = encryptor
+        .wrap_output(armored)
+        .map_err(|err| FlomError::Config(format!("failed to encrypt config: {err}")))?;
+    writer
+        .write_all(plaintext.as_bytes())
+        .and_then(|_| writer.finish())
+        .and_then(|armored| armored.finish())
+        .map_err(|err| FlomError::Config(format!("failed to encrypt config: {err}")))?;
+
+    String::from_utf8(ciphertext)
+        .map_err(|err| FlomError::Config(format!("failed to encrypt config: {err}")))
+}
+
+#[cfg(feature = "encryption")]
+pub(crate) fn decrypt(ciphertext: &[u8], key: &ConfigKey) -> FlomResult<String> {
+    let decryptor = Decryptor::new_buffered(ArmoredReader::new(ciphertext))
+        .map_err(|err| FlomError::Config(format!("failed to read encrypted config: {err}")))?;
+
+    let mut reader = match key {
+        ConfigKey::Passphrase(passphrase) => {
+            let identity = age::scrypt::Identity::new(SecretString::from(passphrase.clone()));
+            decryptor.decrypt(iter::once(&identity as _))
+        }
+        ConfigKey::Identity(path) => {
+            let identities = read_identity_file(path)?
+                .into_identities()
+                .map_err(|err| FlomError::Config(format!("invalid identity file: {err}")))?;
+            decryptor.decrypt(identities.iter().map(|i| i.as_ref() as _))
+        }
+    }
+    .map_err(|err| FlomError::Config(format!("failed to decrypt config: {err}")))?;
+
+    let mut plaintext = String::new();
+    reader
+        .read_to_string(&mut plaintext)
+        .map_err(|err| FlomError::Config(format!("failed to decrypt config: {err}")))?;
+    Ok(plaintext)
+}
+
+#[cfg(feature = "encryption")]
+fn read_identity_file(path: &std::path::Path) -> FlomResult<IdentityFile<age::NoCallbacks>> {
+    IdentityFile::from_file(path.to_string_lossy().to_string()).map_err(|err| {
+        FlomError::Config(format!(
+            "failed to read identity file {}: {err}",
+            path.display()
+        ))
+    })
+}
+
+#[cfg(not(feature = "encryption"))]
+pub(crate) fn encrypt(_plaintext: &str, _key: &ConfigKey) -> FlomResult<String> {
+    Err(unsupported())
+}
+
+#[cfg(not(feature = "encryption"))]
+pub(crate) fn decrypt(_ciphertext: &[u8], _key: &ConfigKey) -> FlomResult<String> {
+    Err(unsupported())
+}
+
+#[cfg(not(feature = "encryption"))]
+fn unsupported() -> FlomError {
+    FlomError::Config("config encryption is not enabled in this build".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_passphrase_roundtrip() {
+        let key = ConfigKey::Passphrase("correct horse".to_string());
+        let ciphertext = encrypt("[api]\nodesli_key = \"secret\"\n", &key).unwrap();
+        assert!(is_encrypted(ciphertext.as_bytes()));
+        assert!(!ciphertext.contains("secret"));
+
+        let plaintext = decrypt(ciphertext.as_bytes(), &key).unwrap();
+        assert_eq!(plaintext, "[api]\nodesli_key = \"secret\"\n");
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_wrong_passphrase_fails() {
+        let ciphertext = encrypt("x = 1", &ConfigKey::Passphrase("right".to_string())).unwrap();
+        let result = decrypt(
+            ciphertext.as_bytes(),
+            &ConfigKey::Passphrase("wrong".to_string()),
+        );
+        assert!(matches!(result, Err(FlomError::Config(_))));
+    }
+
+    #[test]
+    fn test_is_encrypted_plain_toml() {
+        assert!(!is_encrypted(b"[api]\nodesli_key = \"key\"\n"));
+    }
+}
@@ -1,54 +1,548 @@
 mod config;
+mod crypto;
 
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
 
 use crate::config::FlomConfig;
-use flom_core::{FlomError, FlomResult};
+use flom_core::{FlomError, FlomResult, find_platform_key, validate_country_code};
 
-pub use config::{ApiConfig, DefaultConfig, FlomConfig as FlomConfigData, OutputConfig};
+pub use config::{
+    ApiConfig, BotConfig, DefaultConfig, FlomConfig as FlomConfigData, HistoryConfig, LogConfig,
+    MatchingConfig, OdesliConfig, OutputConfig, PostConfig, SigningConfig, SpotifyConfig,
+};
+pub use crypto::{ConfigKey, is_encrypted};
 
 #[cfg(test)]
 pub(crate) static TEST_ENV_MUTEX: std::sync::Mutex<()> = std::sync::Mutex::new(());
 
-pub fn config_path() -> FlomResult<PathBuf> {
+const PROJECT_CONFIG_FILE: &str = ".flom.toml";
+const PROJECT_ENV_FILE: &str = ".env";
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+static SESSION_KEY: Mutex<Option<ConfigKey>> = Mutex::new(None);
+static SESSION_OVERRIDES: Mutex<Vec<ConfigOverride>> = Mutex::new(Vec::new());
+/// Variables [`load_env_files`] took from the working directory's `.env`.
+static PROJECT_ENV_VARS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// A single `key.path=value` override applied on top of every config file.
+#[derive(Debug, Clone, PartialEq)]
+struct ConfigOverride {
+    path: Vec<String>,
+    value: toml::Value,
+}
+
+impl ConfigOverride {
+    fn parse(input: &str) -> FlomResult<Self> {
+        let (key_path, raw) = input.split_once('=').ok_or_else(|| {
+            FlomError::Config(format!("override must look like key.path=value: {input}"))
+        })?;
+        let path: Vec<String> = key_path.trim().split('.').map(str::to_string).collect();
+        if path.len() < 2 || path.iter().any(|part| part.is_empty()) {
+            return Err(FlomError::Config(format!(
+                "key path must have at least 2 parts (e.g., 'output.simple'): {key_path}"
+            )));
+        }
+        // Values are read as TOML (`true`, `3`, `"x"`); anything else is a plain string.
+        let raw = raw.trim();
+        let value = toml::from_str::<toml::Table>(&format!("value = {raw}"))
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(raw.to_string()));
+        Ok(Self { path, value })
+    }
+
+    fn to_layer(&self) -> toml::Value {
+        self.path
+            .iter()
+            .rev()
+            .fold(self.value.clone(), |value, key| {
+                toml::Value::Table(toml::Table::from_iter([(key.clone(), value)]))
+            })
+    }
+}
+
+/// Directory holding the user config and flom's local data (`~/.flom`).
+pub fn flom_dir() -> FlomResult<PathBuf> {
     let home = dirs::home_dir()
         .ok_or_else(|| FlomError::Config("home directory not found".to_string()))?;
-    Ok(home.join(".flom").join("config.toml"))
+    Ok(home.join(".flom"))
+}
+
+pub fn config_path() -> FlomResult<PathBuf> {
+    Ok(flom_dir()?.join("config.toml"))
+}
+
+pub fn system_config_path() -> PathBuf {
+    PathBuf::from("/etc/flom/config.toml")
+}
+
+pub fn project_config_path() -> FlomResult<PathBuf> {
+    let cwd = env::current_dir()
+        .map_err(|err| FlomError::Config(format!("failed to resolve working directory: {err}")))?;
+    Ok(cwd.join(PROJECT_CONFIG_FILE))
+}
+
+/// Config files in merge order: system, user, then project-local.
+/// Later files override keys set by earlier ones.
+pub fn config_layers() -> FlomResult<Vec<PathBuf>> {
+    Ok(vec![
+        system_config_path(),
+        config_path()?,
+        project_config_path()?,
+    ])
+}
+
+/// `.env` files read into the environment, in priority order: the working
+/// directory's, then `~/.flom/env`.
+pub fn env_files() -> FlomResult<Vec<PathBuf>> {
+    Ok(vec![
+        project_config_path()?.with_file_name(PROJECT_ENV_FILE),
+        flom_dir()?.join("env"),
+    ])
+}
+
+/// Reads the existing [`env_files`] into the environment, so `FLOM_*`
+/// variables can live in a file instead of the shell profile.
+///
+/// Sets process environment variables, so call it before any other thread
+/// (such as an async runtime's workers) starts.
+pub fn load_env_files() -> FlomResult<()> {
+    let files = env_files()?;
+    let (project, user) = files.split_at(1);
+    let from_project = load_env_from(project)?;
+    load_env_from(user)?;
+    *PROJECT_ENV_VARS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner()) = from_project;
+    Ok(())
+}
+
+/// Loads the `FLOM_*` variables of the existing files among `paths`. A
+/// project's `.env` also holds its own settings (`HTTPS_PROXY`, `RUST_LOG`,
+/// ...), which must not leak into flom, so every other key is skipped.
+/// Variables that are already set are kept, and an earlier file wins over a
+/// later one. Returns the variables it set.
+fn load_env_from(paths: &[PathBuf]) -> FlomResult<Vec<String>> {
+    let mut loaded = Vec::new();
+    for path in paths {
+        if !path.is_file() {
+            continue;
+        }
+        let failed = |err: dotenvy::Error| {
+            FlomError::Config(format!("failed to load {}: {err}", path.display()))
+        };
+        for item in dotenvy::from_path_iter(path).map_err(failed)? {
+            let (key, value) = item.map_err(failed)?;
+            if key.starts_with("FLOM_") && env::var_os(&key).is_none() {
+                // SAFETY: documented to run before other threads start.
+                unsafe { env::set_var(&key, value) };
+                loaded.push(key);
+            }
+        }
+    }
+    Ok(loaded)
 }
 
 pub fn load_config() -> FlomResult<FlomConfig> {
-    let path = config_path()?;
-    if !path.exists() {
-        return Ok(FlomConfig::default());
+    let overrides = SESSION_OVERRIDES
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+    load_layered(&config_layers()?, &overrides)
+}
+
+/// Sets `key.path=value` overrides (from `--set`) that apply on top of all config
+/// files for the rest of the process, without touching any file.
+pub fn set_config_overrides(overrides: &[String]) -> FlomResult<()> {
+    let parsed = overrides
+        .iter()
+        .map(|input| ConfigOverride::parse(input))
+        .collect::<FlomResult<Vec<_>>>()?;
+    *SESSION_OVERRIDES
+        .lock()
+        .unwrap_or_else(|err| err.into_inner()) = parsed;
+    Ok(())
+}
+
+fn load_layered(paths: &[PathBuf], overrides: &[ConfigOverride]) -> FlomResult<FlomConfig> {
+    let mut merged = toml::Value::Table(Default::default());
+    for path in paths {
+        if !path.exists() {
+            continue;
+        }
+        merge_values(&mut merged, read_layer(path)?);
     }
-    let content = fs::read_to_string(&path)
-        .map_err(|err| FlomError::Config(format!("failed to read config: {err}")))?;
-    let config = toml::from_str(&content)
-        .map_err(|err| FlomError::Config(format!("failed to parse config: {err}")))?;
-    Ok(config)
+    for config_override in overrides {
+        merge_values(&mut merged, config_override.to_layer());
+    }
+    merged
+        .try_into()
+        .map_err(|err| FlomError::Config(format!("failed to parse config: {err}")))
 }
 
-pub fn save_config(config: &FlomConfig) -> FlomResult<()> {
-    let path = config_path()?;
+/// Reads the config file at `path` as one layer. A project's `.flom.toml`
+/// comes with the checkout rather than from the user, so the keys in
+/// [`SECRET_KEYS`] and [`USER_ONLY_KEYS`] are dropped from it.
+fn read_layer(path: &Path) -> FlomResult<toml::Value> {
+    let content = read_config_text(path)?;
+    let mut layer = toml::from_str::<toml::Value>(&content).map_err(|err| {
+        FlomError::Config(format!("failed to parse config {}: {err}", path.display()))
+    })?;
+    if path.file_name() == Some(PROJECT_CONFIG_FILE.as_ref()) {
+        for key in SECRET_KEYS.iter().chain(USER_ONLY_KEYS) {
+            let Some((section, name)) = key.split_once('.') else {
+                continue;
+            };
+            if let Some(table) = layer.get_mut(section).and_then(toml::Value::as_table_mut) {
+                table.remove(name);
+            }
+        }
+    }
+    Ok(layer)
+}
+
+/// Environment variables that take precedence over a config key.
+pub const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("api.odesli_key", "FLOM_ODESLI_KEY"),
+    ("default.target", "FLOM_DEFAULT_TARGET"),
+    ("default.user_country", "FLOM_USER_COUNTRY"),
+    ("default.locale", "FLOM_LOCALE"),
+    ("default.clipboard_fallback", "FLOM_CLIPBOARD_FALLBACK"),
+    ("output.simple", "FLOM_OUTPUT_SIMPLE"),
+    ("output.romanize", "FLOM_ROMANIZE"),
+    ("output.format", "FLOM_OUTPUT_FORMAT"),
+    ("output.template", "FLOM_OUTPUT_TEMPLATE"),
+    ("output.hyperlinks", "FLOM_HYPERLINKS"),
+    ("output.icons", "FLOM_ICONS"),
+    ("odesli.song_if_single", "FLOM_SONG_IF_SINGLE"),
+    ("odesli.entity_db", "FLOM_ENTITY_DB"),
+    ("odesli.cache_ttl_secs", "FLOM_CACHE_TTL"),
+    ("matching.min_score", "FLOM_MATCH_MIN_SCORE"),
+    (
+        "matching.duration_tolerance_secs",
+        "FLOM_MATCH_DURATION_TOLERANCE",
+    ),
+    ("matching.strict", "FLOM_STRICT"),
+    ("history.enabled", "FLOM_HISTORY"),
+    ("bot.telegram_token", "FLOM_TELEGRAM_TOKEN"),
+    ("bot.discord_token", "FLOM_DISCORD_TOKEN"),
+    ("spotify.client_id", "FLOM_SPOTIFY_CLIENT_ID"),
+    ("spotify.client_secret", "FLOM_SPOTIFY_CLIENT_SECRET"),
+    ("spotify.refresh_token", "FLOM_SPOTIFY_REFRESH_TOKEN"),
+    ("signing.key", "FLOM_SIGNING_KEY"),
+];
+
+/// Keys whose values are credentials and must not be printed.
+pub const SECRET_KEYS: &[&str] = &[
+    "api.odesli_key",
+    "bot.telegram_token",
+    "bot.discord_token",
+    "spotify.client_secret",
+    "spotify.refresh_token",
+    "signing.key",
+];
+
+/// Keys besides [`SECRET_KEYS`] that only the user's own config can set: the
+/// rest of the Spotify app credentials, and the file the log is appended to.
+pub const USER_ONLY_KEYS: &[&str] = &["spotify.client_id", "log.path"];
+
+/// What a config key holds, for checking values before they are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Text,
+    Bool,
+    Integer,
+    Number,
+    /// A platform name, a comma-separated list of them, `all` or `songlink`.
+    Platform,
+    /// An ISO 3166-1 alpha-2 country code.
+    Country,
+    Choice(&'static [&'static str]),
+}
+
+/// Every platform a `default.target` or `--to` can name, as its canonical key.
+pub const TARGET_KEYS: [&str; 8] = [
+    "spotify",
+    "appleMusic",
+    "itunes",
+    "youtube",
+    "youtubeMusic",
+    "tidal",
+    "deezer",
+    "amazonMusic",
+];
+
+/// Every key `flom config set` knows, in the order `flom config list` shows them.
+pub const CONFIG_KEYS: &[(&str, ValueKind)] = &[
+    ("api.odesli_key", ValueKind::Text),
+    ("default.target", ValueKind::Platform),
+    ("default.user_country", ValueKind::Country),
+    ("default.locale", ValueKind::Text),
+    ("default.clipboard_fallback", ValueKind::Bool),
+    ("output.simple", ValueKind::Bool),
+    ("output.romanize", ValueKind::Bool),
+    (
+        "output.format",
+        ValueKind::Choice(&["human", "json", "gha", "csv", "markdown"]),
+    ),
+    ("output.template", ValueKind::Text),
+    ("output.hyperlinks", ValueKind::Bool),
+    ("output.icons", ValueKind::Bool),
+    ("odesli.song_if_single", ValueKind::Bool),
+    ("odesli.entity_db", ValueKind::Bool),
+    ("odesli.cache_ttl_secs", ValueKind::Integer),
+    ("matching.min_score", ValueKind::Number),
+    ("matching.duration_tolerance_secs", ValueKind::Integer),
+    ("matching.strict", ValueKind::Bool),
+    ("history.enabled", ValueKind::Bool),
+    ("log.enabled", ValueKind::Bool),
+    ("log.path", ValueKind::Text),
+    ("log.max_size", ValueKind::Integer),
+    ("log.keep", ValueKind::Integer),
+    ("post.template", ValueKind::Text),
+    ("bot.telegram_token", ValueKind::Text),
+    ("bot.discord_token", ValueKind::Text),
+    ("bot.target", ValueKind::Platform),
+    ("spotify.client_id", ValueKind::Text),
+    ("spotify.client_secret", ValueKind::Text),
+    ("spotify.refresh_token", ValueKind::Text),
+    ("signing.key", ValueKind::Text),
+];
+
+pub fn config_value_kind(key_path: &str) -> Option<ValueKind> {
+    CONFIG_KEYS
+        .iter()
+        .find(|(key, _)| *key == key_path)
+        .map(|(_, kind)| *kind)
+}
+
+/// Where the effective value of a config key comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    File(PathBuf),
+    /// A `--set` override.
+    Override,
+    Env(&'static str),
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::File(path) => write!(f, "{}", path.display()),
+            ConfigSource::Override => write!(f, "--set"),
+            ConfigSource::Env(var) => write!(f, "${var}"),
+        }
+    }
+}
+
+/// One effective config value and where it was set last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: String,
+    pub source: ConfigSource,
+}
+
+/// Every key set by a config layer, `--set` or an environment variable, sorted
+/// by key, with the value and source that win.
+pub fn config_sources() -> FlomResult<Vec<ConfigEntry>> {
+    let overrides = SESSION_OVERRIDES
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+    sources_of(&config_layers()?, &overrides, |var| env::var(var).ok())
+}
+
+fn sources_of(
+    paths: &[PathBuf],
+    overrides: &[ConfigOverride],
+    env_var: impl Fn(&str) -> Option<String>,
+) -> FlomResult<Vec<ConfigEntry>> {
+    let mut entries = std::collections::BTreeMap::new();
+    let mut record = |layer: &toml::Value, source: &ConfigSource| {
+        let mut flat = Vec::new();
+        flatten(layer, String::new(), &mut flat);
+        for (key, value) in flat {
+            entries.insert(key, (value, source.clone()));
+        }
+    };
+    for path in paths {
+        if !path.exists() {
+            continue;
+        }
+        record(&read_layer(path)?, &ConfigSource::File(path.clone()));
+    }
+    for config_override in overrides {
+        record(&config_override.to_layer(), &ConfigSource::Override);
+    }
+    for (key, var) in ENV_OVERRIDES {
+        if let Some(value) = env_var(var) {
+            entries.insert(key.to_string(), (value, ConfigSource::Env(var)));
+        }
+    }
+    Ok(entries
+        .into_iter()
+        .map(|(key, (value, source))| ConfigEntry { key, value, source })
+        .collect())
+}
+
+/// Collects the leaves of `value` as `section.key` paths with their TOML text.
+fn flatten(value: &toml::Value, prefix: String, out: &mut Vec<(String, String)>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(value, path, out);
+            }
+        }
+        toml::Value::String(text) => out.push((prefix, text.clone())),
+        other => out.push((prefix, other.to_string())),
+    }
+}
+
+fn merge_values(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Sets the key used to unlock an encrypted config for the rest of the process.
+/// Takes precedence over `FLOM_CONFIG_IDENTITY` and `FLOM_CONFIG_PASSPHRASE`.
+pub fn set_config_key(key: ConfigKey) {
+    *SESSION_KEY.lock().unwrap_or_else(|err| err.into_inner()) = Some(key);
+}
+
+pub fn resolve_config_key() -> Option<ConfigKey> {
+    if let Some(key) = SESSION_KEY
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+    {
+        return Some(key);
+    }
+    if let Ok(value) = env::var("FLOM_CONFIG_IDENTITY")
+        && !value.trim().is_empty()
+    {
+        return Some(ConfigKey::Identity(PathBuf::from(value)));
+    }
+    if let Ok(value) = env::var("FLOM_CONFIG_PASSPHRASE")
+        && !value.is_empty()
+    {
+        return Some(ConfigKey::Passphrase(value));
+    }
+    None
+}
+
+fn read_config_text(path: &Path) -> FlomResult<String> {
+    let bytes = fs::read(path).map_err(|err| {
+        FlomError::Config(format!("failed to read config {}: {err}", path.display()))
+    })?;
+    if crypto::is_encrypted(&bytes) {
+        let key = resolve_config_key().ok_or_else(|| {
+            FlomError::Config(format!(
+                "config {} is encrypted; set FLOM_CONFIG_PASSPHRASE or FLOM_CONFIG_IDENTITY",
+                path.display()
+            ))
+        })?;
+        return crypto::decrypt(&bytes, &key);
+    }
+    String::from_utf8(bytes).map_err(|err| {
+        FlomError::Config(format!("failed to read config {}: {err}", path.display()))
+    })
+}
+
+fn write_config_text(path: &Path, content: &str, encrypted: bool) -> FlomResult<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
             .map_err(|err| FlomError::Config(format!("failed to create config dir: {err}")))?;
     }
-    let content = toml::to_string_pretty(config)
-        .map_err(|err| FlomError::Config(format!("failed to serialize config: {err}")))?;
-    fs::write(&path, content)
+    let content = if encrypted {
+        let key = resolve_config_key().ok_or_else(|| {
+            FlomError::Config("config is encrypted but no key is available".to_string())
+        })?;
+        crypto::encrypt(content, &key)?
+    } else {
+        content.to_string()
+    };
+    fs::write(path, content)
         .map_err(|err| FlomError::Config(format!("failed to write config: {err}")))?;
     Ok(())
 }
 
+pub fn save_config(config: &FlomConfig) -> FlomResult<()> {
+    let path = config_path()?;
+    let content = toml::to_string_pretty(config)
+        .map_err(|err| FlomError::Config(format!("failed to serialize config: {err}")))?;
+    write_config_text(&path, &content, is_config_encrypted()?)
+}
+
 pub fn config_exists() -> FlomResult<bool> {
     let path = config_path()?;
     Ok(path.exists())
 }
 
+pub fn is_config_encrypted() -> FlomResult<bool> {
+    let path = config_path()?;
+    if !path.exists() {
+        return Ok(false);
+    }
+    let bytes = fs::read(&path)
+        .map_err(|err| FlomError::Config(format!("failed to read config: {err}")))?;
+    Ok(crypto::is_encrypted(&bytes))
+}
+
+/// Encrypts the user config file in place with `key`.
+pub fn encrypt_config(key: &ConfigKey) -> FlomResult<()> {
+    if is_config_encrypted()? {
+        return Err(FlomError::Config("config is already encrypted".to_string()));
+    }
+    let path = config_path()?;
+    let content = if path.exists() {
+        read_config_text(&path)?
+    } else {
+        toml::to_string_pretty(&FlomConfig::default())
+            .map_err(|err| FlomError::Config(format!("failed to serialize config: {err}")))?
+    };
+    let ciphertext = crypto::encrypt(&content, key)?;
+    fs::write(&path, ciphertext)
+        .map_err(|err| FlomError::Config(format!("failed to write config: {err}")))?;
+    set_config_key(key.clone());
+    Ok(())
+}
+
+/// Decrypts the user config file in place with `key`.
+pub fn decrypt_config(key: &ConfigKey) -> FlomResult<()> {
+    if !is_config_encrypted()? {
+        return Err(FlomError::Config("config is not encrypted".to_string()));
+    }
+    let path = config_path()?;
+    let bytes = fs::read(&path)
+        .map_err(|err| FlomError::Config(format!("failed to read config: {err}")))?;
+    let plaintext = crypto::decrypt(&bytes, key)?;
+    fs::write(&path, plaintext)
+        .map_err(|err| FlomError::Config(format!("failed to write config: {err}")))?;
+    Ok(())
+}
+
 pub fn resolve_odesli_key(config: &FlomConfig) -> Option<String> {
     if let Ok(value) = env::var("FLOM_ODESLI_KEY")
         && !value.trim().is_empty() {
@@ -79,6 +573,122 @@ pub fn resolve_user_country(config: &FlomConfig) -> String {
         .unwrap_or_else(|| "US".to_string())
 }
 
+pub fn resolve_telegram_token(config: &FlomConfig) -> Option<String> {
+    if let Ok(value) = env::var("FLOM_TELEGRAM_TOKEN") {
+        let normalized = value.trim();
+        if !normalized.is_empty() {
+            return Some(normalized.to_string());
+        }
+    }
+    config.bot.telegram_token.clone()
+}
+
+pub fn resolve_discord_token(config: &FlomConfig) -> Option<String> {
+    if let Ok(value) = env::var("FLOM_DISCORD_TOKEN") {
+        let normalized = value.trim();
+        if !normalized.is_empty() {
+            return Some(normalized.to_string());
+        }
+    }
+    config.bot.discord_token.clone()
+}
+
+pub fn resolve_spotify_client_id(config: &FlomConfig) -> Option<String> {
+    resolve_secret("FLOM_SPOTIFY_CLIENT_ID", &config.spotify.client_id)
+}
+
+pub fn resolve_spotify_client_secret(config: &FlomConfig) -> Option<String> {
+    resolve_secret("FLOM_SPOTIFY_CLIENT_SECRET", &config.spotify.client_secret)
+}
+
+pub fn resolve_spotify_refresh_token(config: &FlomConfig) -> Option<String> {
+    resolve_secret("FLOM_SPOTIFY_REFRESH_TOKEN", &config.spotify.refresh_token)
+}
+
+pub fn resolve_signing_key(config: &FlomConfig) -> Option<String> {
+    resolve_secret("FLOM_SIGNING_KEY", &config.signing.key)
+}
+
+/// The signing key from the user's own settings only: `FLOM_SIGNING_KEY`
+/// unless the working directory's `.env` set it, `--set`, then the system and
+/// user config files. What a checkout ships can't vouch for a report.
+pub fn trusted_signing_key() -> FlomResult<Option<String>> {
+    let from_project = PROJECT_ENV_VARS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .iter()
+        .any(|var| var == "FLOM_SIGNING_KEY");
+    let overrides = SESSION_OVERRIDES
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+    let config = load_layered(&[system_config_path(), config_path()?], &overrides)?;
+    if from_project {
+        return Ok(config
+            .signing
+            .key
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty()));
+    }
+    Ok(resolve_signing_key(&config))
+}
+
+fn resolve_secret(var: &str, configured: &Option<String>) -> Option<String> {
+    if let Ok(value) = env::var(var) {
+        let normalized = value.trim();
+        if !normalized.is_empty() {
+            return Some(normalized.to_string());
+        }
+    }
+    configured
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+pub fn resolve_locale(config: &FlomConfig) -> Option<String> {
+    if let Ok(value) = env::var("FLOM_LOCALE") {
+        let normalized = value.trim();
+        if !normalized.is_empty() {
+            return Some(normalized.to_string());
+        }
+    }
+    config.default.locale.clone()
+}
+
+pub fn resolve_clipboard_fallback(config: &FlomConfig) -> Option<bool> {
+    if let Ok(value) = env::var("FLOM_CLIPBOARD_FALLBACK") {
+        let normalized = value.to_lowercase();
+        return Some(normalized == "1" || normalized == "true" || normalized == "yes");
+    }
+    config.default.clipboard_fallback
+}
+
+pub fn resolve_romanize(config: &FlomConfig) -> Option<bool> {
+    if let Ok(value) = env::var("FLOM_ROMANIZE") {
+        let normalized = value.to_lowercase();
+        return Some(normalized == "1" || normalized == "true" || normalized == "yes");
+    }
+    config.output.romanize
+}
+
+pub fn resolve_hyperlinks(config: &FlomConfig) -> Option<bool> {
+    if let Ok(value) = env::var("FLOM_HYPERLINKS") {
+        let normalized = value.to_lowercase();
+        return Some(normalized == "1" || normalized == "true" || normalized == "yes");
+    }
+    config.output.hyperlinks
+}
+
+pub fn resolve_icons(config: &FlomConfig) -> Option<bool> {
+    if let Ok(value) = env::var("FLOM_ICONS") {
+        let normalized = value.to_lowercase();
+        return Some(normalized == "1" || normalized == "true" || normalized == "yes");
+    }
+    config.output.icons
+}
+
 pub fn resolve_simple_output(config: &FlomConfig) -> Option<bool> {
     if let Ok(value) = env::var("FLOM_OUTPUT_SIMPLE") {
         let normalized = value.to_lowercase();
@@ -87,11 +697,95 @@ pub fn resolve_simple_output(config: &FlomConfig) -> Option<bool> {
     config.output.simple
 }
 
+pub fn resolve_output_format(config: &FlomConfig) -> Option<String> {
+    if let Ok(value) = env::var("FLOM_OUTPUT_FORMAT")
+        && !value.trim().is_empty()
+    {
+        return Some(value.trim().to_string());
+    }
+    config.output.format.clone()
+}
+
+pub fn resolve_output_template(config: &FlomConfig) -> Option<String> {
+    if let Ok(value) = env::var("FLOM_OUTPUT_TEMPLATE")
+        && !value.trim().is_empty()
+    {
+        return Some(value);
+    }
+    config.output.template.clone()
+}
+
+pub fn resolve_song_if_single(config: &FlomConfig) -> Option<bool> {
+    if let Ok(value) = env::var("FLOM_SONG_IF_SINGLE") {
+        let normalized = value.to_lowercase();
+        return Some(normalized == "1" || normalized == "true" || normalized == "yes");
+    }
+    config.odesli.song_if_single
+}
+
+pub fn resolve_entity_db_enabled(config: &FlomConfig) -> bool {
+    if let Ok(value) = env::var("FLOM_ENTITY_DB") {
+        let normalized = value.to_lowercase();
+        return normalized == "1" || normalized == "true" || normalized == "yes";
+    }
+    config.odesli.entity_db.unwrap_or(true)
+}
+
+/// Lifetime of cached Odesli responses, a day unless configured.
+pub fn resolve_cache_ttl(config: &FlomConfig) -> u64 {
+    if let Ok(value) = env::var("FLOM_CACHE_TTL")
+        && let Ok(secs) = value.trim().parse()
+    {
+        return secs;
+    }
+    config.odesli.cache_ttl_secs.unwrap_or(86_400)
+}
+
+pub fn resolve_match_min_score(config: &FlomConfig) -> Option<f64> {
+    if let Ok(value) = env::var("FLOM_MATCH_MIN_SCORE")
+        && let Ok(score) = value.trim().parse()
+    {
+        return Some(score);
+    }
+    config.matching.min_score
+}
+
+pub fn resolve_match_duration_tolerance(config: &FlomConfig) -> Option<u64> {
+    if let Ok(value) = env::var("FLOM_MATCH_DURATION_TOLERANCE")
+        && let Ok(secs) = value.trim().parse()
+    {
+        return Some(secs);
+    }
+    config.matching.duration_tolerance_secs
+}
+
+pub fn resolve_strict(config: &FlomConfig) -> bool {
+    if let Ok(value) = env::var("FLOM_STRICT") {
+        let normalized = value.to_lowercase();
+        return normalized == "1" || normalized == "true" || normalized == "yes";
+    }
+    config.matching.strict.unwrap_or(false)
+}
+
+pub fn resolve_history_enabled(config: &FlomConfig) -> bool {
+    if let Ok(value) = env::var("FLOM_HISTORY") {
+        let normalized = value.to_lowercase();
+        return normalized == "1" || normalized == "true" || normalized == "yes";
+    }
+    config.history.enabled.unwrap_or(true)
+}
+
 pub fn set_config_value(key_path: &str, value: &str) -> FlomResult<()> {
+    update_config_values(&[(key_path, Some(value))])
+}
+
+/// Sets (`Some`) or removes (`None`) several keys with a single write of the
+/// user config file.
+pub fn update_config_values(changes: &[(&str, Option<&str>)]) -> FlomResult<()> {
     let path = config_path()?;
+    let encrypted = is_config_encrypted()?;
     let content = if path.exists() {
-        fs::read_to_string(&path)
-            .map_err(|err| FlomError::Config(format!("failed to read config: {err}")))?
+        read_config_text(&path)?
     } else {
         String::new()
     };
@@ -100,37 +794,97 @@ pub fn set_config_value(key_path: &str, value: &str) -> FlomResult<()> {
         .parse::<toml_edit::DocumentMut>()
         .unwrap_or_default();
 
-    let parts: Vec<&str> = key_path.split('.').collect();
-    if parts.len() < 2 {
-        return Err(FlomError::Config(
-            "key path must have at least 2 parts (e.g., 'api.odesli_key')".to_string(),
-        ));
-    }
+    for (key_path, value) in changes {
+        let parts: Vec<&str> = key_path.split('.').collect();
+        if parts.len() < 2 {
+            return Err(FlomError::Config(
+                "key path must have at least 2 parts (e.g., 'api.odesli_key')".to_string(),
+            ));
+        }
 
-    let table = doc.as_table_mut();
-    let mut current = table;
-    for part in &parts[..parts.len() - 1] {
-        current = current
-            .entry(part)
-            .or_insert(toml_edit::Item::Table(Default::default()))
-            .as_table_mut()
-            .ok_or_else(|| {
-                FlomError::Config(format!("cannot set nested value in '{}'", key_path))
-            })?;
+        let table = doc.as_table_mut();
+        let mut current = table;
+        for part in &parts[..parts.len() - 1] {
+            current = current
+                .entry(part)
+                .or_insert(toml_edit::Item::Table(Default::default()))
+                .as_table_mut()
+                .ok_or_else(|| {
+                    FlomError::Config(format!("cannot set nested value in '{}'", key_path))
+                })?;
+        }
+
+        let last_part = parts.last().unwrap();
+        match value {
+            Some(value) => current[last_part] = toml_edit::value(typed_value(key_path, value)?),
+            None => {
+                current.remove(last_part);
+            }
+        }
     }
 
-    let last_part = parts.last().unwrap();
-    current[last_part] = toml_edit::value(value);
+    write_config_text(&path, &doc.to_string(), encrypted)
+}
 
-    let content = doc.to_string();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|err| FlomError::Config(format!("failed to create config dir: {err}")))?;
-    }
-    fs::write(&path, content)
-        .map_err(|err| FlomError::Config(format!("failed to write config: {err}")))?;
+/// Checks `value` the way [`set_config_value`] would before writing it.
+pub fn check_config_value(key_path: &str, value: &str) -> FlomResult<()> {
+    typed_value(key_path, value).map(|_| ())
+}
 
-    Ok(())
+/// `value` as the TOML type `key_path` holds. Unknown keys are stored as strings.
+fn typed_value(key_path: &str, value: &str) -> FlomResult<toml_edit::Value> {
+    let value = value.trim();
+    let invalid =
+        |expected: &str| FlomError::Config(format!("{key_path} expects {expected}, got '{value}'"));
+    let typed = match config_value_kind(key_path) {
+        Some(ValueKind::Bool) => match value.to_lowercase().as_str() {
+            "true" | "1" | "yes" => true.into(),
+            "false" | "0" | "no" => false.into(),
+            _ => return Err(invalid("true or false")),
+        },
+        Some(ValueKind::Integer) => value
+            .parse::<i64>()
+            .ok()
+            .filter(|number| *number >= 0)
+            .ok_or_else(|| invalid("a whole number"))?
+            .into(),
+        Some(ValueKind::Number) => value
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .ok_or_else(|| invalid("a number"))?
+            .into(),
+        Some(ValueKind::Country) => validate_country_code(value)?.into(),
+        Some(ValueKind::Choice(choices)) => {
+            if !choices.contains(&value) {
+                return Err(invalid(&format!("one of {}", choices.join(", "))));
+            }
+            value.into()
+        }
+        Some(ValueKind::Platform) => {
+            if let Some(unknown) = value
+                .split(',')
+                .map(str::trim)
+                .find(|part| !is_target(part))
+            {
+                return Err(FlomError::Config(format!(
+                    "{key_path} expects platforms such as spotify,tidal, all or songlink; \
+                     unknown: '{unknown}'"
+                )));
+            }
+            value.into()
+        }
+        Some(ValueKind::Text) | None => value.into(),
+    };
+    Ok(typed)
+}
+
+/// Whether `name` is one of the [`TARGET_KEYS`], `all` or `songlink`.
+fn is_target(name: &str) -> bool {
+    let name = name.trim();
+    name.eq_ignore_ascii_case("all")
+        || name.eq_ignore_ascii_case("songlink")
+        || find_platform_key(&TARGET_KEYS, name).is_some()
 }
 
 pub fn open_in_editor() -> FlomResult<()> {
@@ -138,6 +892,11 @@ pub fn open_in_editor() -> FlomResult<()> {
     if !path.exists() {
         save_config(&FlomConfig::default())?;
     }
+    if is_config_encrypted()? {
+        return Err(FlomError::Config(
+            "config is encrypted; run `flom config decrypt` before editing".to_string(),
+        ));
+    }
 
     let editor = env::var("EDITOR").unwrap_or_else(|_| {
         if cfg!(target_os = "macos") {
@@ -211,6 +970,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolve_hyperlinks() {
+        let _lock = TEST_ENV_MUTEX.lock().unwrap();
+        let mut config = FlomConfig::default();
+        assert_eq!(resolve_hyperlinks(&config), None);
+        config.output.hyperlinks = Some(true);
+        unsafe {
+            env::set_var("FLOM_HYPERLINKS", "0");
+        }
+        assert_eq!(resolve_hyperlinks(&config), Some(false));
+        unsafe {
+            env::remove_var("FLOM_HYPERLINKS");
+        }
+        assert_eq!(resolve_hyperlinks(&config), Some(true));
+    }
+
+    #[test]
+    fn test_resolve_spotify_credentials() {
+        let _lock = TEST_ENV_MUTEX.lock().unwrap();
+        let mut config = FlomConfig::default();
+        config.spotify.client_id = Some("id".to_string());
+        config.spotify.refresh_token = Some("  ".to_string());
+        unsafe {
+            env::set_var("FLOM_SPOTIFY_CLIENT_SECRET", "secret");
+        }
+        assert_eq!(resolve_spotify_client_id(&config), Some("id".to_string()));
+        assert_eq!(
+            resolve_spotify_client_secret(&config),
+            Some("secret".to_string())
+        );
+        assert_eq!(resolve_spotify_refresh_token(&config), None);
+        unsafe {
+            env::remove_var("FLOM_SPOTIFY_CLIENT_SECRET");
+        }
+    }
+
+    #[test]
+    fn test_resolve_output_format_and_template() {
+        let _lock = TEST_ENV_MUTEX.lock().unwrap();
+        let mut config = FlomConfig::default();
+        config.output.format = Some("json".to_string());
+        assert_eq!(resolve_output_format(&config), Some("json".to_string()));
+        unsafe {
+            env::set_var("FLOM_OUTPUT_FORMAT", " gha ");
+        }
+        assert_eq!(resolve_output_format(&config), Some("gha".to_string()));
+        unsafe {
+            env::remove_var("FLOM_OUTPUT_FORMAT");
+        }
+
+        config.output.template = Some("{title}".to_string());
+        unsafe {
+            env::set_var("FLOM_OUTPUT_TEMPLATE", "{artist} ");
+        }
+        assert_eq!(
+            resolve_output_template(&config),
+            Some("{artist} ".to_string())
+        );
+        unsafe {
+            env::remove_var("FLOM_OUTPUT_TEMPLATE");
+        }
+        assert_eq!(
+            resolve_output_template(&config),
+            Some("{title}".to_string())
+        );
+    }
+
     #[test]
     fn test_resolve_user_country_env() {
         let _lock = TEST_ENV_MUTEX.lock().unwrap();
@@ -225,10 +1051,265 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolve_clipboard_fallback() {
+        let _lock = TEST_ENV_MUTEX.lock().unwrap();
+        let mut config = FlomConfig::default();
+        assert_eq!(resolve_clipboard_fallback(&config), None);
+        config.default.clipboard_fallback = Some(true);
+        assert_eq!(resolve_clipboard_fallback(&config), Some(true));
+        unsafe {
+            env::set_var("FLOM_CLIPBOARD_FALLBACK", "no");
+        }
+        assert_eq!(resolve_clipboard_fallback(&config), Some(false));
+        unsafe {
+            env::remove_var("FLOM_CLIPBOARD_FALLBACK");
+        }
+    }
+
+    #[test]
+    fn test_resolve_match_thresholds() {
+        let _lock = TEST_ENV_MUTEX.lock().unwrap();
+        let mut config = FlomConfig::default();
+        config.matching.min_score = Some(0.9);
+        assert_eq!(resolve_match_min_score(&config), Some(0.9));
+        assert_eq!(resolve_match_duration_tolerance(&config), None);
+        unsafe {
+            env::set_var("FLOM_MATCH_MIN_SCORE", "0.7");
+            env::set_var("FLOM_MATCH_DURATION_TOLERANCE", "5");
+        }
+        assert_eq!(resolve_match_min_score(&config), Some(0.7));
+        assert_eq!(resolve_match_duration_tolerance(&config), Some(5));
+        unsafe {
+            env::remove_var("FLOM_MATCH_MIN_SCORE");
+            env::remove_var("FLOM_MATCH_DURATION_TOLERANCE");
+        }
+    }
+
+    #[test]
+    fn test_resolve_strict() {
+        let _lock = TEST_ENV_MUTEX.lock().unwrap();
+        let mut config = FlomConfig::default();
+        assert!(!resolve_strict(&config));
+        config.matching.strict = Some(true);
+        assert!(resolve_strict(&config));
+        unsafe {
+            env::set_var("FLOM_STRICT", "0");
+        }
+        assert!(!resolve_strict(&config));
+        unsafe {
+            env::remove_var("FLOM_STRICT");
+        }
+    }
+
     #[test]
     fn test_resolve_user_country_default() {
         let config = FlomConfig::default();
         let result = resolve_user_country(&config);
         assert_eq!(result, "US");
     }
+
+    #[test]
+    fn test_load_layered_overrides_in_order() {
+        let dir = env::temp_dir().join("flom-test-layers");
+        fs::create_dir_all(&dir).unwrap();
+        let system = dir.join("system.toml");
+        let user = dir.join("user.toml");
+        let project = dir.join("project.toml");
+        fs::write(
+            &system,
+            "[default]\ntarget = \"tidal\"\nuser_country = \"DE\"\n",
+        )
+        .unwrap();
+        fs::write(&user, "[api]\nodesli_key = \"user-key\"\n").unwrap();
+        fs::write(&project, "[default]\ntarget = \"spotify\"\n").unwrap();
+
+        let config = load_layered(&[system, user, project, dir.join("missing.toml")], &[]).unwrap();
+        assert_eq!(config.api.odesli_key, Some("user-key".to_string()));
+        assert_eq!(config.default.target, Some("spotify".to_string()));
+        assert_eq!(config.default.user_country, Some("DE".to_string()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_project_config_cannot_set_user_only_keys() {
+        let dir = env::temp_dir().join("flom-test-project-keys");
+        fs::create_dir_all(&dir).unwrap();
+        let user = dir.join("config.toml");
+        let project = dir.join(PROJECT_CONFIG_FILE);
+        fs::write(&user, "[api]\nodesli_key = \"user-key\"\n").unwrap();
+        fs::write(
+            &project,
+            "[api]\nodesli_key = \"project-key\"\n[default]\ntarget = \"spotify\"\n\
+             [log]\npath = \"/tmp/elsewhere\"\n[signing]\nkey = \"forged\"\n",
+        )
+        .unwrap();
+
+        let config = load_layered(&[user.clone(), project.clone()], &[]).unwrap();
+        assert_eq!(config.api.odesli_key, Some("user-key".to_string()));
+        assert_eq!(config.default.target, Some("spotify".to_string()));
+        assert_eq!(config.log.path, None);
+        assert_eq!(config.signing.key, None);
+
+        let entries = sources_of(&[user, project], &[], |_| None).unwrap();
+        assert!(entries.iter().all(|entry| entry.key != "signing.key"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_layered_applies_overrides_last() {
+        let dir = env::temp_dir().join("flom-test-overrides");
+        fs::create_dir_all(&dir).unwrap();
+        let user = dir.join("user.toml");
+        fs::write(
+            &user,
+            "[default]\ntarget = \"tidal\"\n[output]\nsimple = false\n",
+        )
+        .unwrap();
+
+        let overrides = [
+            ConfigOverride::parse("output.simple=true").unwrap(),
+            ConfigOverride::parse("default.target = spotify").unwrap(),
+            ConfigOverride::parse("log.max_size=2048").unwrap(),
+        ];
+        let config = load_layered(&[user], &overrides).unwrap();
+        assert_eq!(config.output.simple, Some(true));
+        assert_eq!(config.default.target, Some("spotify".to_string()));
+        assert_eq!(config.log.max_size, Some(2048));
+
+        let bad = ConfigOverride::parse("output.simple=maybe").unwrap();
+        assert!(load_layered(&[], &[bad]).is_err());
+        assert!(ConfigOverride::parse("simple=true").is_err());
+        assert!(ConfigOverride::parse("output.simple").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_config_sources_report_the_winning_layer() {
+        let dir = env::temp_dir().join("flom-test-sources");
+        fs::create_dir_all(&dir).unwrap();
+        let system = dir.join("system.toml");
+        let user = dir.join("user.toml");
+        fs::write(
+            &system,
+            "[default]\ntarget = \"tidal\"\nuser_country = \"DE\"\n",
+        )
+        .unwrap();
+        fs::write(&user, "[default]\ntarget = \"spotify\"\n").unwrap();
+        let overrides = [ConfigOverride::parse("output.simple=true").unwrap()];
+        let env_var = |var: &str| (var == "FLOM_USER_COUNTRY").then(|| "JP".to_string());
+
+        let entries = sources_of(&[system.clone(), user.clone()], &overrides, env_var).unwrap();
+        let found = |key: &str| entries.iter().find(|entry| entry.key == key).unwrap();
+        assert_eq!(found("default.target").value, "spotify");
+        assert_eq!(found("default.target").source, ConfigSource::File(user));
+        assert_eq!(found("default.user_country").value, "JP");
+        assert_eq!(
+            found("default.user_country").source,
+            ConfigSource::Env("FLOM_USER_COUNTRY")
+        );
+        assert_eq!(found("output.simple").value, "true");
+        assert_eq!(found("output.simple").source, ConfigSource::Override);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_config_values_are_written_as_their_type() {
+        assert_eq!(
+            typed_value("output.simple", "yes").unwrap().as_bool(),
+            Some(true)
+        );
+        assert_eq!(typed_value("log.keep", "5").unwrap().as_integer(), Some(5));
+        assert_eq!(
+            typed_value("matching.min_score", "0.8").unwrap().as_float(),
+            Some(0.8)
+        );
+        assert_eq!(
+            typed_value("output.format", "json").unwrap().as_str(),
+            Some("json")
+        );
+        assert!(check_config_value("output.simple", "maybe").is_err());
+        assert!(check_config_value("log.keep", "-1").is_err());
+        assert!(check_config_value("output.format", "xml").is_err());
+        assert!(check_config_value("default.user_country", "Japan").is_err());
+        assert!(check_config_value("default.target", "anything").is_err());
+        assert!(check_config_value("default.target", "spotify,tidal").is_ok());
+        assert!(check_config_value("default.target", "Apple-Music, songlink").is_ok());
+        assert!(check_config_value("bot.target", "all").is_ok());
+        assert!(check_config_value("bot.target", "spotify,").is_err());
+    }
+
+    #[test]
+    fn test_env_files_load_only_unset_flom_variables() {
+        let _lock = TEST_ENV_MUTEX.lock().unwrap();
+        let dir = env::temp_dir().join("flom-test-dotenv");
+        fs::create_dir_all(&dir).unwrap();
+        let project = dir.join(".env");
+        let user = dir.join("env");
+        fs::write(
+            &project,
+            "FLOM_TEST_DOTENV_NEW=from-project\nFLOM_TEST_DOTENV_SET=from-project\n\
+             FLOM_TEST_DOTENV_OTHER_PROJECT=1\nTEST_DOTENV_PROXY=http://proxy\n",
+        )
+        .unwrap();
+        fs::write(
+            &user,
+            "FLOM_TEST_DOTENV_NEW=from-user\nFLOM_TEST_DOTENV_USER=from-user\n",
+        )
+        .unwrap();
+        unsafe {
+            env::remove_var("FLOM_TEST_DOTENV_NEW");
+            env::remove_var("FLOM_TEST_DOTENV_USER");
+            env::remove_var("TEST_DOTENV_PROXY");
+            env::set_var("FLOM_TEST_DOTENV_SET", "from-shell");
+        }
+
+        let loaded = load_env_from(&[project, dir.join("missing"), user]).unwrap();
+        assert_eq!(
+            loaded,
+            [
+                "FLOM_TEST_DOTENV_NEW",
+                "FLOM_TEST_DOTENV_OTHER_PROJECT",
+                "FLOM_TEST_DOTENV_USER"
+            ]
+        );
+        assert_eq!(env::var("FLOM_TEST_DOTENV_NEW").unwrap(), "from-project");
+        assert_eq!(env::var("FLOM_TEST_DOTENV_USER").unwrap(), "from-user");
+        assert_eq!(env::var("FLOM_TEST_DOTENV_SET").unwrap(), "from-shell");
+        assert!(env::var_os("TEST_DOTENV_PROXY").is_none());
+
+        unsafe {
+            for key in [
+                "FLOM_TEST_DOTENV_NEW",
+                "FLOM_TEST_DOTENV_SET",
+                "FLOM_TEST_DOTENV_USER",
+                "FLOM_TEST_DOTENV_OTHER_PROJECT",
+            ] {
+                env::remove_var(key);
+            }
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_trusted_signing_key_ignores_the_project_env() {
+        let _lock = TEST_ENV_MUTEX.lock().unwrap();
+        unsafe { env::set_var("FLOM_SIGNING_KEY", "from-project") };
+        *PROJECT_ENV_VARS.lock().unwrap() = vec!["FLOM_SIGNING_KEY".to_string()];
+        assert_ne!(
+            trusted_signing_key().unwrap().as_deref(),
+            Some("from-project")
+        );
+
+        PROJECT_ENV_VARS.lock().unwrap().clear();
+        assert_eq!(
+            trusted_signing_key().unwrap().as_deref(),
+            Some("from-project")
+        );
+        unsafe { env::remove_var("FLOM_SIGNING_KEY") };
+    }
 }
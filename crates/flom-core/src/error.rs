@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum FlomError {
     #[error("unsupported input: {0}")]
     UnsupportedInput(String),
@@ -12,8 +12,22 @@ pub enum FlomError {
     Network(String),
     #[error("api error: {0}")]
     Api(String),
+    /// An API answered with an error status, kept apart so retries can tell
+    /// rate limits and server errors from permanent failures.
+    #[error("api error: {message}")]
+    ApiStatus { status: u16, message: String },
     #[error("parse error: {0}")]
     Parse(String),
 }
 
+impl FlomError {
+    /// The HTTP status of a failed API call, when it is known.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            FlomError::ApiStatus { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
 pub type FlomResult<T> = Result<T, FlomError>;
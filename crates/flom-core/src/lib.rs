@@ -1,19 +1,156 @@
+mod country;
 mod error;
 mod result;
 
+pub use country::{COUNTRIES, validate_country_code};
 pub use error::{FlomError, FlomResult};
-pub use result::{ConversionResult, MediaInfo};
+pub use result::{ConversionResult, MediaInfo, MediaType};
 
+use unicode_normalization::UnicodeNormalization;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Longest URL accepted as input; well above any real share link.
+pub const MAX_URL_LENGTH: usize = 2048;
+
+/// Hosts (and their subdomains) of the services supported by the music converter.
+pub const MUSIC_HOSTS: &[&str] = &[
+    "spotify.com",
+    "spotify.link",
+    "music.apple.com",
+    "itunes.apple.com",
+    "youtube.com",
+    "youtu.be",
+    "tidal.com",
+    "deezer.com",
+    "deezer.page.link",
+    "music.amazon.com",
+    "music.amazon.co.jp",
+    "music.amazon.co.uk",
+    "music.amazon.de",
+    "music.amazon.fr",
+    "amazon.com",
+    "soundcloud.com",
+    "pandora.com",
+    "napster.com",
+    "music.yandex.ru",
+    "music.yandex.com",
+    "anghami.com",
+    "boomplay.com",
+    "audiomack.com",
+    "song.link",
+    "album.link",
+    "odesli.co",
+];
+
+/// Finds the key among `keys` that `name` spells, case-insensitively and
+/// ignoring `-`/`_` separators (e.g. `apple-music` for `appleMusic`).
+pub fn find_platform_key<'a>(keys: &[&'a str], name: &str) -> Option<&'a str> {
+    let wanted: String = name
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '-' | '_'))
+        .collect();
+    keys.iter()
+        .copied()
+        .find(|key| key.eq_ignore_ascii_case(&wanted))
+}
+
+/// Parses `url` and accepts only http(s) URLs up to [`MAX_URL_LENGTH`], so inputs
+/// such as `file:`, `javascript:` or `data:` never reach a backend.
 pub fn validate_url(url: &str) -> FlomResult<()> {
-    url::Url::parse(url).map_err(|err| FlomError::InvalidInput(format!("invalid url: {err}")))?;
+    parse_http_url(url)?;
     Ok(())
 }
 
+/// Like [`validate_url`], but additionally requires one of the [`MUSIC_HOSTS`].
+pub fn validate_music_url(url: &str) -> FlomResult<()> {
+    let parsed = parse_http_url(url)?;
+    let host = parsed.host_str().unwrap_or_default().to_ascii_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let known = MUSIC_HOSTS
+        .iter()
+        .any(|known| host == *known || host.ends_with(&format!(".{known}")));
+    if !known {
+        return Err(FlomError::UnsupportedInput(format!(
+            "not a supported music service: {host}"
+        )));
+    }
+    Ok(())
+}
+
+/// Canonical form of `url` for caching and comparison: NFC-normalized, with the host
+/// lowercased and converted to punycode, non-ASCII path characters percent-encoded,
+/// and the fragment dropped. A homoglyph domain therefore never equals the real one.
+pub fn normalize_url(url: &str) -> FlomResult<String> {
+    let composed: String = url.trim().nfc().collect();
+    let mut parsed = parse_http_url(&composed)?;
+    parsed.set_fragment(None);
+    Ok(parsed.into())
+}
+
+/// Canonical form of an ISRC such as `US-S1Z-99-00001`: uppercase, without
+/// separators, and checked to be a two-letter country code, a three-character
+/// registrant code and seven digits.
+pub fn normalize_isrc(isrc: &str) -> FlomResult<String> {
+    let code: String = isrc
+        .chars()
+        .filter(|ch| !matches!(ch, '-' | ' '))
+        .map(|ch| ch.to_ascii_uppercase())
+        .collect();
+    let bytes = code.as_bytes();
+    let valid = bytes.len() == 12
+        && bytes[..2].iter().all(u8::is_ascii_alphabetic)
+        && bytes[2..5].iter().all(u8::is_ascii_alphanumeric)
+        && bytes[5..].iter().all(u8::is_ascii_digit);
+    if !valid {
+        return Err(FlomError::InvalidInput(format!(
+            "invalid ISRC: {} (expected e.g. USS1Z9900001)",
+            isrc.trim()
+        )));
+    }
+    Ok(code)
+}
+
+fn parse_http_url(url: &str) -> FlomResult<url::Url> {
+    if url.len() > MAX_URL_LENGTH {
+        return Err(FlomError::InvalidInput(format!(
+            "invalid url: longer than {MAX_URL_LENGTH} characters"
+        )));
+    }
+    let parsed = url::Url::parse(url)
+        .map_err(|err| FlomError::InvalidInput(format!("invalid url: {err}")))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(parsed),
+        scheme => Err(FlomError::InvalidInput(format!(
+            "invalid url: unsupported scheme `{scheme}`"
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::validate_url;
+    use super::{
+        MAX_URL_LENGTH, find_platform_key, normalize_isrc, normalize_url, validate_music_url,
+        validate_url,
+    };
     use crate::FlomError;
 
+    #[test]
+    fn platform_keys_match_loosely_spelled_names() {
+        let keys = ["spotify", "appleMusic", "youtubeMusic"];
+        assert_eq!(
+            find_platform_key(&keys, " Apple-Music "),
+            Some("appleMusic")
+        );
+        assert_eq!(
+            find_platform_key(&keys, "youtube_music"),
+            Some("youtubeMusic")
+        );
+        assert_eq!(find_platform_key(&keys, "SPOTIFY"), Some("spotify"));
+        assert_eq!(find_platform_key(&keys, "tidal"), None);
+    }
+
     #[test]
     fn test_validate_url_valid_https() {
         assert!(validate_url("https://example.com").is_ok());
@@ -40,4 +177,85 @@ mod tests {
             _ => panic!("Expected InvalidInput error"),
         }
     }
+
+    #[test]
+    fn test_validate_url_rejects_other_schemes() {
+        for url in [
+            "file:///etc/passwd",
+            "javascript:alert(1)",
+            "data:text/plain,hi",
+            "ftp://example.com/a",
+        ] {
+            match validate_url(url) {
+                Err(FlomError::InvalidInput(msg)) => assert!(msg.contains("unsupported scheme")),
+                other => panic!("Expected InvalidInput for {url}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_url_max_length() {
+        let url = format!("https://example.com/{}", "a".repeat(MAX_URL_LENGTH));
+        assert!(matches!(
+            validate_url(&url),
+            Err(FlomError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_music_url_hosts() {
+        assert!(validate_music_url("https://open.spotify.com/track/abc").is_ok());
+        assert!(validate_music_url("https://www.youtube.com/watch?v=abc").is_ok());
+        assert!(validate_music_url("https://MUSIC.apple.com/us/album/x/1").is_ok());
+        assert!(matches!(
+            validate_music_url("https://example.com/track/1"),
+            Err(FlomError::UnsupportedInput(_))
+        ));
+        assert!(matches!(
+            validate_music_url("https://evilspotify.com/track/1"),
+            Err(FlomError::UnsupportedInput(_))
+        ));
+        assert!(matches!(
+            validate_music_url("javascript:alert(1)"),
+            Err(FlomError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_normalize_url_idn_and_unicode_path() {
+        assert_eq!(
+            normalize_url("https://MÜNCHEN.example/Caf\u{0065}\u{0301}#top").unwrap(),
+            normalize_url("https://xn--mnchen-3ya.example/Caf\u{00e9}").unwrap()
+        );
+        assert_eq!(
+            normalize_url("https://xn--mnchen-3ya.example/Caf\u{00e9}").unwrap(),
+            "https://xn--mnchen-3ya.example/Caf%C3%A9"
+        );
+    }
+
+    #[test]
+    fn test_normalize_url_homoglyph_host_is_not_trusted() {
+        // Cyrillic "о" in place of the Latin "o"
+        let spoofed = normalize_url("https://sp\u{043e}tify.com/track/abc").unwrap();
+        assert!(spoofed.starts_with("https://xn--"));
+        assert!(validate_music_url(&spoofed).is_err());
+    }
+
+    #[test]
+    fn test_normalize_isrc() {
+        assert_eq!(normalize_isrc("US-S1Z-99-00001").unwrap(), "USS1Z9900001");
+        assert_eq!(normalize_isrc(" gbaye0601498 ").unwrap(), "GBAYE0601498");
+        for invalid in [
+            "",
+            "USS1Z99",
+            "1SS1Z9900001",
+            "USS1Z99000A1",
+            "USS1Z99000011",
+        ] {
+            assert!(matches!(
+                normalize_isrc(invalid),
+                Err(FlomError::InvalidInput(_))
+            ));
+        }
+    }
 }
@@ -1,19 +1,153 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct MediaInfo {
     pub title: Option<String>,
     pub artist: Option<String>,
     pub album: Option<String>,
+    /// Entity type as reported by the provider, e.g. `song` or `album`.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artwork_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isrc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_year: Option<u16>,
+    /// Record label of the release.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl MediaInfo {
+    /// Sets `release_date`, and the year from it, unless a date is known.
+    pub fn complete_release_date(&mut self, date: Option<&str>) {
+        if self.release_date.is_none() {
+            self.release_date = date.map(str::to_string);
+        }
+        if self.release_year.is_none() {
+            self.release_year = self
+                .release_date
+                .as_deref()
+                .and_then(|date| date.get(..4)?.parse().ok());
+        }
+    }
+
+    /// `duration_ms` as `m:ss`, or `h:mm:ss` past an hour.
+    pub fn duration_label(&self) -> Option<String> {
+        let total = self.duration_ms? / 1000;
+        let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+        Some(if hours > 0 {
+            format!("{hours}:{minutes:02}:{seconds:02}")
+        } else {
+            format!("{minutes}:{seconds:02}")
+        })
+    }
+}
+
+/// What a link points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaType {
+    Song,
+    Album,
+    Podcast,
+}
+
+impl MediaType {
+    /// Parses an Odesli entity type (`song`, `album`) or `podcast`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "song" | "track" => Some(MediaType::Song),
+            "album" => Some(MediaType::Album),
+            "podcast" | "episode" => Some(MediaType::Podcast),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MediaType::Song => "song",
+            MediaType::Album => "album",
+            MediaType::Podcast => "podcast",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ConversionResult {
     pub source_url: String,
     pub target_url: Option<String>,
     pub source_platform: Option<String>,
     pub target_platform: Option<String>,
+    /// Platform-native ID of the source (e.g. Spotify track ID, YouTube video ID).
+    pub source_id: Option<String>,
+    /// Platform-native ID of the target.
+    pub target_id: Option<String>,
     pub source_info: Option<MediaInfo>,
     pub target_info: Option<MediaInfo>,
+    /// Songlink page with links to every platform, whatever the target.
+    pub page_url: Option<String>,
+    /// Kind of the source entity.
+    #[serde(default)]
+    pub media_type: Option<MediaType>,
     pub warning: Option<String>,
 }
+
+impl ConversionResult {
+    /// Whether the target is a different kind of entity than the source, such
+    /// as the album a platform returned for a song link.
+    pub fn is_type_mismatch(&self) -> bool {
+        let target = self
+            .target_info
+            .as_ref()
+            .and_then(|info| info.kind.as_deref())
+            .and_then(MediaType::parse);
+        matches!((self.media_type, target), (Some(source), Some(target)) if source != target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_mismatch_needs_both_types() {
+        let album = |kind: &str| MediaInfo {
+            kind: Some(kind.to_string()),
+            ..Default::default()
+        };
+        let mut result = ConversionResult {
+            media_type: Some(MediaType::Song),
+            target_info: Some(album("album")),
+            ..Default::default()
+        };
+        assert!(result.is_type_mismatch());
+        result.target_info = Some(album("song"));
+        assert!(!result.is_type_mismatch());
+        result.target_info = None;
+        assert!(!result.is_type_mismatch());
+        result.media_type = None;
+        result.target_info = Some(album("album"));
+        assert!(!result.is_type_mismatch());
+    }
+
+    #[test]
+    fn release_year_follows_the_date() {
+        let mut info = MediaInfo::default();
+        info.complete_release_date(Some("2011-05-09T07:00:00Z"));
+        assert_eq!(info.release_date.as_deref(), Some("2011-05-09T07:00:00Z"));
+        assert_eq!(info.release_year, Some(2011));
+        // A known date stays.
+        info.complete_release_date(Some("1999-01-01"));
+        assert_eq!(info.release_year, Some(2011));
+
+        let mut undated = MediaInfo::default();
+        undated.complete_release_date(Some("soon"));
+        assert_eq!(undated.release_year, None);
+    }
+}
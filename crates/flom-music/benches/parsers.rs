@@ -0,0 +1,43 @@
+//! Link parsing over a mixed batch, as when converting a large `--input` file.
+//! Run with `cargo bench -p flom-music`.
+
+#![feature(test)]
+
+extern crate test;
+
+use flom_music::extract::extract_music_urls;
+use flom_music::parsers::{detect_platform, parse_platform_id};
+use test::{Bencher, black_box};
+
+const LINKS: [&str; 6] = [
+    "https://open.spotify.com/intl-ja/track/4Km5HrUvYTaSUfiSGPJeQR",
+    "https://music.apple.com/us/album/blinding-lights/1496794033?i=1496794038",
+    "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+    "https://music.youtube.com/watch?v=dQw4w9WgXcQ",
+    "https://www.deezer.com/track/3135556",
+    "https://example.com/not-music",
+];
+
+#[bench]
+fn detect_platform_batch(b: &mut Bencher) {
+    b.iter(|| {
+        for link in LINKS {
+            black_box(detect_platform(black_box(link)));
+        }
+    });
+}
+
+#[bench]
+fn parse_platform_id_batch(b: &mut Bencher) {
+    b.iter(|| {
+        for link in LINKS {
+            black_box(parse_platform_id(black_box(link)));
+        }
+    });
+}
+
+#[bench]
+fn extract_music_urls_from_text(b: &mut Bencher) {
+    let text = LINKS.map(|link| format!("- [track]({link})")).join("\n");
+    b.iter(|| black_box(extract_music_urls(black_box(&text))));
+}
@@ -0,0 +1,120 @@
+use flom_core::{FlomError, FlomResult};
+use reqwest::Client;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+const API_BASE: &str = "https://api.deezer.com";
+
+/// Deezer's public API, which needs no account.
+#[derive(Debug, Clone)]
+pub struct DeezerClient {
+    client: Client,
+}
+
+/// Deezer reports errors with status 200 and an `error` object.
+#[derive(Debug, Deserialize)]
+struct Reply<T> {
+    error: Option<ApiError>,
+    #[serde(flatten)]
+    body: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArtistBody {
+    name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeezerTrack {
+    pub release_date: Option<String>,
+    pub album: Option<DeezerAlbumRef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeezerAlbumRef {
+    pub id: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeezerAlbum {
+    pub release_date: Option<String>,
+    pub label: Option<String>,
+}
+
+impl DeezerClient {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    pub async fn artist_name(&self, artist_id: &str) -> FlomResult<String> {
+        let body: ArtistBody = self.get(&format!("artist/{artist_id}")).await?;
+        body.name
+            .ok_or_else(|| FlomError::Parse("deezer artist has no name".to_string()))
+    }
+
+    pub async fn track(&self, track_id: &str) -> FlomResult<DeezerTrack> {
+        self.get(&format!("track/{track_id}")).await
+    }
+
+    pub async fn album(&self, album_id: &str) -> FlomResult<DeezerAlbum> {
+        self.get(&format!("album/{album_id}")).await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> FlomResult<T> {
+        let response = self
+            .client
+            .get(format!("{API_BASE}/{path}"))
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .map_err(|err| FlomError::Network(format!("deezer request failed: {err}")))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(FlomError::ApiStatus {
+                status: status.as_u16(),
+                message: format!("deezer error: status={status} body={body}"),
+            });
+        }
+
+        let reply = response
+            .json::<Reply<T>>()
+            .await
+            .map_err(|err| FlomError::Parse(format!("deezer response parse failed: {err}")))?;
+        match reply.error {
+            Some(error) => Err(FlomError::Api(format!("deezer error: {}", error.message))),
+            None => Ok(reply.body),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replies_carry_either_a_body_or_an_error() {
+        let track: Reply<DeezerTrack> = serde_json::from_str(
+            r#"{"id":3135556,"release_date":"2005-01-24","album":{"id":302127,"title":"Discovery"}}"#,
+        )
+        .unwrap();
+        assert!(track.error.is_none());
+        assert_eq!(track.body.release_date.as_deref(), Some("2005-01-24"));
+        assert_eq!(track.body.album.map(|album| album.id), Some(302127));
+
+        let missing: Reply<DeezerAlbum> = serde_json::from_str(
+            r#"{"error":{"type":"DataException","message":"no data","code":800}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            missing.error.map(|error| error.message).as_deref(),
+            Some("no data")
+        );
+    }
+}
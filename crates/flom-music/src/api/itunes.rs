@@ -0,0 +1,201 @@
+use flom_core::{FlomError, FlomResult};
+use reqwest::Client;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+use crate::metadata::artist_key;
+
+const SEARCH_BASE: &str = "https://itunes.apple.com/search";
+const LOOKUP_BASE: &str = "https://itunes.apple.com/lookup";
+
+/// Free-text song search, used to find a link for tracks known only by name,
+/// and album, artist and podcast listings.
+#[derive(Debug, Clone)]
+pub struct ItunesClient {
+    client: Client,
+    country: String,
+}
+
+impl ItunesClient {
+    pub fn new(client: Client, country: impl Into<String>) -> Self {
+        Self {
+            client,
+            country: country.into(),
+        }
+    }
+
+    pub fn http_client(&self) -> &Client {
+        &self.client
+    }
+
+    pub async fn search_songs(&self, term: &str, limit: u32) -> FlomResult<Vec<ItunesTrack>> {
+        let params = [
+            ("term", term.to_string()),
+            ("entity", "song".to_string()),
+            ("limit", limit.to_string()),
+            ("country", self.country.clone()),
+        ];
+        self.get(SEARCH_BASE, &params, "itunes search").await
+    }
+
+    /// A song or album by its track or collection ID.
+    pub async fn lookup(&self, id: &str) -> FlomResult<Option<ItunesTrack>> {
+        let params = [("id", id.to_string()), ("country", self.country.clone())];
+        let results: Vec<ItunesTrack> = self.get(LOOKUP_BASE, &params, "itunes lookup").await?;
+        Ok(results.into_iter().next())
+    }
+
+    /// The songs of an album by collection ID, in album order.
+    pub async fn album_tracks(&self, collection_id: &str) -> FlomResult<Vec<ItunesTrack>> {
+        let params = [
+            ("id", collection_id.to_string()),
+            ("entity", "song".to_string()),
+            ("country", self.country.clone()),
+        ];
+        let results: Vec<ItunesTrack> = self.get(LOOKUP_BASE, &params, "itunes lookup").await?;
+        // The album itself comes first and has no track link.
+        Ok(results
+            .into_iter()
+            .filter(|track| track.track_view_url.is_some())
+            .collect())
+    }
+
+    /// The artist whose name matches `name` best, if any is found.
+    pub async fn find_artist(&self, name: &str) -> FlomResult<Option<ItunesArtist>> {
+        let params = [
+            ("term", name.to_string()),
+            ("entity", "musicArtist".to_string()),
+            ("limit", "5".to_string()),
+            ("country", self.country.clone()),
+        ];
+        let artists: Vec<ItunesArtist> = self.get(SEARCH_BASE, &params, "itunes search").await?;
+        let wanted = artist_key(name);
+        Ok(artists
+            .iter()
+            .find(|artist| artist_key(&artist.artist_name) == wanted)
+            .or(artists.first())
+            .cloned())
+    }
+
+    /// Up to `limit` albums of an artist, in the order the store ranks them.
+    pub async fn artist_albums(&self, artist_id: u64, limit: u32) -> FlomResult<Vec<ItunesAlbum>> {
+        let params = [
+            ("id", artist_id.to_string()),
+            ("entity", "album".to_string()),
+            ("limit", limit.to_string()),
+            ("country", self.country.clone()),
+        ];
+        let results: Vec<ItunesAlbum> = self.get(LOOKUP_BASE, &params, "itunes lookup").await?;
+        // The artist itself comes first and has no album link.
+        Ok(results
+            .into_iter()
+            .filter(|album| album.collection_view_url.is_some())
+            .collect())
+    }
+
+    /// Podcasts (shows) matching `term`.
+    pub async fn search_podcasts(&self, term: &str, limit: u32) -> FlomResult<Vec<ItunesPodcast>> {
+        let params = [
+            ("term", term.to_string()),
+            ("entity", "podcast".to_string()),
+            ("limit", limit.to_string()),
+            ("country", self.country.clone()),
+        ];
+        self.get(SEARCH_BASE, &params, "itunes search").await
+    }
+
+    /// The latest episodes of a podcast, newest first. The store lists at most 200.
+    pub async fn podcast_episodes(&self, podcast_id: u64) -> FlomResult<Vec<ItunesEpisode>> {
+        let params = [
+            ("id", podcast_id.to_string()),
+            ("entity", "podcastEpisode".to_string()),
+            ("limit", "200".to_string()),
+            ("country", self.country.clone()),
+        ];
+        let results: Vec<ItunesEpisode> = self.get(LOOKUP_BASE, &params, "itunes lookup").await?;
+        // The podcast itself comes first.
+        Ok(results
+            .into_iter()
+            .filter(|episode| episode.wrapper_type.as_deref() == Some("podcastEpisode"))
+            .collect())
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        params: &[(&str, String)],
+        what: &str,
+    ) -> FlomResult<Vec<T>> {
+        let response = self
+            .client
+            .get(url)
+            .query(params)
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .map_err(|err| FlomError::Network(format!("{what} failed: {err}")))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(FlomError::ApiStatus {
+                status: status.as_u16(),
+                message: format!("itunes error: status={status} body={body}"),
+            });
+        }
+
+        let body = response
+            .json::<SearchResponse<T>>()
+            .await
+            .map_err(|err| FlomError::Parse(format!("itunes response parse failed: {err}")))?;
+        Ok(body.results)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse<T> {
+    #[serde(default = "Vec::new")]
+    results: Vec<T>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItunesTrack {
+    pub track_name: Option<String>,
+    pub artist_name: Option<String>,
+    pub track_view_url: Option<String>,
+    pub track_time_millis: Option<u64>,
+    pub release_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItunesArtist {
+    pub artist_id: u64,
+    pub artist_name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItunesAlbum {
+    pub collection_name: Option<String>,
+    pub collection_view_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItunesPodcast {
+    pub collection_id: u64,
+    pub collection_name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItunesEpisode {
+    pub wrapper_type: Option<String>,
+    pub track_id: Option<u64>,
+    pub track_name: Option<String>,
+    pub collection_id: Option<u64>,
+    pub collection_name: Option<String>,
+    pub track_view_url: Option<String>,
+}
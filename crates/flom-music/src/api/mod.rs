@@ -1 +1,4 @@
+pub mod deezer;
+pub mod itunes;
 pub mod odesli;
+pub mod spotify;
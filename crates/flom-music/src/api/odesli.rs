@@ -1,16 +1,125 @@
 use std::collections::HashMap;
 
-use flom_core::{FlomError, FlomResult};
-use reqwest::Client;
-use serde::Deserialize;
+use flom_core::{FlomError, FlomResult, find_platform_key};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 const API_BASE: &str = "https://api.song.link/v1-alpha.1/links";
 
+/// Optional query parameters for the links endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OdesliOptions {
+    /// Resolve single-track albums to the track instead of the album.
+    pub song_if_single: bool,
+    /// Sent as `Accept-Language` so localized metadata is returned where available.
+    pub locale: Option<String>,
+}
+
+impl OdesliOptions {
+    /// What sets answers under these options apart from the default ones, for
+    /// keying stored answers; empty for the defaults.
+    pub fn cache_tag(&self) -> String {
+        let mut tag = Vec::new();
+        if self.song_if_single {
+            tag.push("songIfSingle".to_string());
+        }
+        if let Some(locale) = &self.locale {
+            tag.push(format!("lang={locale}"));
+        }
+        tag.join(";")
+    }
+
+    fn query_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if self.song_if_single {
+            params.push(("songIfSingle", "true".to_string()));
+        }
+        params
+    }
+}
+
+/// Platforms accepted by the `platform` query parameter.
+pub const ID_PLATFORMS: &[&str] = &[
+    "spotify",
+    "itunes",
+    "appleMusic",
+    "youtube",
+    "youtubeMusic",
+    "google",
+    "googleStore",
+    "pandora",
+    "deezer",
+    "tidal",
+    "amazonStore",
+    "amazonMusic",
+    "soundcloud",
+    "napster",
+    "yandex",
+    "spinrilla",
+    "audius",
+    "audiomack",
+    "anghami",
+    "boomplay",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EntityType {
+    #[default]
+    Song,
+    Album,
+}
+
+impl EntityType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EntityType::Song => "song",
+            EntityType::Album => "album",
+        }
+    }
+}
+
+/// Looks an entity up by its platform-native ID rather than by URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityQuery {
+    /// One of [`ID_PLATFORMS`].
+    pub platform: String,
+    pub entity_type: EntityType,
+    pub id: String,
+}
+
+impl EntityQuery {
+    /// Builds a query, accepting platform names as [`find_platform_key`]
+    /// matches them.
+    pub fn new(platform: &str, entity_type: EntityType, id: &str) -> FlomResult<Self> {
+        let platform = find_platform_key(ID_PLATFORMS, platform)
+            .ok_or_else(|| FlomError::InvalidInput(format!("unknown platform: {platform}")))?;
+        let id = id.trim();
+        if id.is_empty() {
+            return Err(FlomError::InvalidInput("id must not be empty".to_string()));
+        }
+        Ok(Self {
+            platform: platform.to_string(),
+            entity_type,
+            id: id.to_string(),
+        })
+    }
+
+    fn query_params(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("platform", self.platform.clone()),
+            ("type", self.entity_type.as_str().to_string()),
+            ("id", self.id.clone()),
+        ]
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OdesliClient {
     client: Client,
     api_key: Option<String>,
     user_country: String,
+    options: OdesliOptions,
 }
 
 impl OdesliClient {
@@ -19,25 +128,55 @@ impl OdesliClient {
             client,
             api_key,
             user_country: user_country.into(),
+            options: OdesliOptions::default(),
         }
     }
 
+    pub fn with_options(mut self, options: OdesliOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn options(&self) -> &OdesliOptions {
+        &self.options
+    }
+
+    pub fn user_country(&self) -> &str {
+        &self.user_country
+    }
+
     pub async fn fetch_links(&self, url: &str) -> FlomResult<OdesliResponse> {
-        let mut params: Vec<(&str, String)> = vec![
-            ("url", url.to_string()),
-            ("userCountry", self.user_country.clone()),
-        ];
+        parse_response(self.fetch_raw(url).await?)
+    }
+
+    pub async fn fetch_by_id(&self, query: &EntityQuery) -> FlomResult<OdesliResponse> {
+        parse_response(self.fetch(query.query_params()).await?)
+    }
+
+    /// The unmodified response body for `url`, including the fields
+    /// [`OdesliResponse`] leaves out (thumbnails, app URIs, ...).
+    pub async fn fetch_raw(&self, url: &str) -> FlomResult<Value> {
+        self.fetch(vec![("url", url.to_string())]).await
+    }
+
+    async fn fetch(&self, mut params: Vec<(&'static str, String)>) -> FlomResult<Value> {
+        params.push(("userCountry", self.user_country.clone()));
         if let Some(key) = &self.api_key
             && !key.trim().is_empty() {
                 params.push(("key", key.clone()));
             }
+        params.extend(self.options.query_params());
 
-        let response = self
+        let mut request = self
             .client
             .get(API_BASE)
             .query(&params)
             .header("Accept", "application/json")
-            .header("User-Agent", "flom/0.1")
+            .header("User-Agent", "flom/0.1");
+        if let Some(locale) = &self.options.locale {
+            request = request.header("Accept-Language", locale);
+        }
+        let response = request
             .send()
             .await
             .map_err(|err| FlomError::Network(format!("odesli request failed: {err}")))?;
@@ -45,19 +184,66 @@ impl OdesliClient {
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(FlomError::Api(format!(
-                "odesli error: status={status} body={body}"
-            )));
+            return Err(api_error(status, &body));
         }
 
         response
-            .json::<OdesliResponse>()
+            .json::<Value>()
             .await
             .map_err(|err| FlomError::Parse(format!("odesli response parse failed: {err}")))
     }
 }
 
+/// The error body Odesli sends with a failed lookup.
 #[derive(Debug, Deserialize)]
+struct ErrorBody {
+    code: Option<String>,
+    #[serde(rename = "statusCode")]
+    status_code: Option<u16>,
+}
+
+/// Turns a failed lookup into an error that says what went wrong and what to
+/// do about it. Anything but an unresolvable link carries the status, which
+/// is what retries look at.
+fn api_error(status: StatusCode, body: &str) -> FlomError {
+    let failed = |status: StatusCode, message: String| FlomError::ApiStatus {
+        status: status.as_u16(),
+        message,
+    };
+    let Ok(error) = serde_json::from_str::<ErrorBody>(body) else {
+        return failed(status, format!("odesli error: status={status} body={body}"));
+    };
+    let code = error.code.as_deref().unwrap_or("unknown");
+    let status = error
+        .status_code
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .filter(|code| code.is_client_error() || code.is_server_error())
+        .unwrap_or(status);
+    if code == "could_not_resolve_entity" {
+        return FlomError::UnsupportedInput(
+            "odesli could not resolve this link; check that it opens in a browser, \
+             or look the track up with `flom search`"
+                .to_string(),
+        );
+    }
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return failed(
+            status,
+            format!(
+                "odesli rate limit reached (status={status} code={code}); wait a minute, \
+                 or set `api.odesli_key` (FLOM_ODESLI_KEY) for a higher limit"
+            ),
+        );
+    }
+    failed(status, format!("odesli error: status={status} code={code}"))
+}
+
+fn parse_response(body: Value) -> FlomResult<OdesliResponse> {
+    serde_json::from_value(body)
+        .map_err(|err| FlomError::Parse(format!("odesli response parse failed: {err}")))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OdesliResponse {
     #[serde(rename = "entityUniqueId")]
     pub entity_unique_id: String,
@@ -69,29 +255,71 @@ pub struct OdesliResponse {
     pub entities_by_unique_id: HashMap<String, OdesliEntity>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OdesliLink {
     #[serde(rename = "entityUniqueId")]
     pub entity_unique_id: String,
     pub url: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct OdesliEntity {
     pub id: Option<String>,
+    /// `song` or `album`.
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
     pub title: Option<String>,
     #[serde(rename = "artistName")]
     pub artist_name: Option<String>,
     #[serde(rename = "albumName")]
     pub album_name: Option<String>,
+    #[serde(rename = "thumbnailUrl")]
+    pub thumbnail_url: Option<String>,
+    /// Track length in milliseconds.
+    pub duration: Option<u64>,
+    pub isrc: Option<String>,
+    #[serde(rename = "releaseDate")]
+    pub release_date: Option<String>,
     #[serde(rename = "apiProvider")]
     pub api_provider: Option<String>,
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{EntityQuery, EntityType, OdesliOptions, StatusCode, api_error, parse_response};
+    use flom_core::FlomError;
     use url::Url;
 
+    #[test]
+    fn test_options_query_params() {
+        assert!(OdesliOptions::default().query_params().is_empty());
+
+        let options = OdesliOptions {
+            song_if_single: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            options.query_params(),
+            vec![("songIfSingle", "true".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_entity_query_params() {
+        let query = EntityQuery::new("Apple-Music", EntityType::Album, " 1440 ").unwrap();
+        assert_eq!(query.platform, "appleMusic");
+        assert_eq!(
+            query.query_params(),
+            vec![
+                ("platform", "appleMusic".to_string()),
+                ("type", "album".to_string()),
+                ("id", "1440".to_string()),
+            ]
+        );
+        assert!(EntityQuery::new("myspace", EntityType::Song, "1").is_err());
+        assert!(EntityQuery::new("spotify", EntityType::Song, " ").is_err());
+    }
+
     #[test]
     fn test_validate_url_with_valid() {
         let result = Url::parse("https://example.com");
@@ -109,4 +337,69 @@ mod tests {
         let result = Url::parse("://no-scheme");
         assert!(result.is_err(), "URL without scheme should fail to parse");
     }
+
+    #[test]
+    fn test_parse_response_ignores_extra_fields() {
+        let body = serde_json::json!({
+            "entityUniqueId": "SPOTIFY_SONG::abc",
+            "userCountry": "US",
+            "pageUrl": "https://song.link/s/abc",
+            "linksByPlatform": {
+                "spotify": {
+                    "entityUniqueId": "SPOTIFY_SONG::abc",
+                    "url": "https://open.spotify.com/track/abc",
+                    "nativeAppUriDesktop": "spotify:track:abc"
+                }
+            },
+            "entitiesByUniqueId": {
+                "SPOTIFY_SONG::abc": {
+                    "id": "abc",
+                    "title": "Song",
+                    "thumbnailUrl": "https://i.scdn.co/image/abc",
+                    "apiProvider": "spotify"
+                }
+            }
+        });
+        let response = parse_response(body).unwrap();
+        assert_eq!(response.page_url, "https://song.link/s/abc");
+        assert_eq!(
+            response.entities_by_unique_id["SPOTIFY_SONG::abc"]
+                .title
+                .as_deref(),
+            Some("Song")
+        );
+        assert!(parse_response(serde_json::json!({"pageUrl": 1})).is_err());
+    }
+
+    #[test]
+    fn test_api_error_maps_known_codes() {
+        let unresolved = api_error(
+            StatusCode::BAD_REQUEST,
+            r#"{"statusCode":400,"code":"could_not_resolve_entity"}"#,
+        );
+        assert!(
+            matches!(unresolved, FlomError::UnsupportedInput(msg) if msg.contains("flom search"))
+        );
+
+        let limited = api_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            r#"{"code":"too_many_requests"}"#,
+        );
+        assert!(
+            matches!(&limited, FlomError::ApiStatus { status: 429, message } if message.contains("FLOM_ODESLI_KEY"))
+        );
+
+        let other = api_error(
+            StatusCode::BAD_REQUEST,
+            r#"{"statusCode":400,"code":"invalid_url"}"#,
+        );
+        assert!(
+            matches!(other, FlomError::ApiStatus { status: 400, message } if message == "odesli error: status=400 Bad Request code=invalid_url")
+        );
+
+        let raw = api_error(StatusCode::BAD_GATEWAY, "<html>bad gateway</html>");
+        assert!(
+            matches!(raw, FlomError::ApiStatus { status: 502, message } if message.contains("<html>"))
+        );
+    }
 }
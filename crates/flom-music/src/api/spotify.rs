@@ -0,0 +1,329 @@
+use flom_core::{FlomError, FlomResult};
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::parsers::spotify::parse_spotify_track_id;
+
+const TOKEN_URL: &str = "https://accounts.spotify.com/api/token";
+const API_BASE: &str = "https://api.spotify.com/v1";
+/// Most tracks Spotify accepts in one "add items" request.
+const ADD_BATCH: usize = 100;
+
+/// An app's client credentials and a user's refresh token, which must carry the
+/// `playlist-modify-private` scope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpotifyCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+    pub refresh_token: String,
+}
+
+/// Spotify Web API client acting on behalf of one user.
+#[derive(Debug, Clone)]
+pub struct SpotifyClient {
+    client: Client,
+    access_token: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedPlaylist {
+    pub id: String,
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaylistTracksPage {
+    items: Vec<PlaylistItem>,
+    next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaylistItem {
+    track: Option<PlaylistTrack>,
+}
+
+/// Local files and unavailable tracks have no Spotify link.
+#[derive(Debug, Deserialize)]
+struct PlaylistTrack {
+    #[serde(default)]
+    external_urls: TrackUrls,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct TrackUrls {
+    spotify: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Artist {
+    name: String,
+}
+
+/// A podcast episode as the Web API describes it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyEpisode {
+    pub name: String,
+    pub show: Option<SpotifyShow>,
+    #[serde(default)]
+    external_urls: TrackUrls,
+}
+
+impl SpotifyEpisode {
+    pub fn url(&self) -> Option<&str> {
+        self.external_urls.spotify.as_deref()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyShow {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrackSearch {
+    tracks: TrackPage,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrackPage {
+    items: Vec<PlaylistTrack>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EpisodeSearch {
+    episodes: EpisodePage,
+}
+
+/// Unavailable episodes come back as `null`.
+#[derive(Debug, Deserialize)]
+struct EpisodePage {
+    items: Vec<Option<SpotifyEpisode>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaylistResponse {
+    id: String,
+    external_urls: ExternalUrls,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExternalUrls {
+    spotify: String,
+}
+
+impl SpotifyClient {
+    /// Trades the refresh token for an access token.
+    pub async fn authorize(client: Client, credentials: &SpotifyCredentials) -> FlomResult<Self> {
+        let request = client
+            .post(TOKEN_URL)
+            .basic_auth(&credentials.client_id, Some(&credentials.client_secret))
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", credentials.refresh_token.as_str()),
+            ]);
+        let token: TokenResponse = send(request, "spotify authorization").await?;
+        Ok(Self {
+            client,
+            access_token: token.access_token,
+        })
+    }
+
+    /// Authorizes the app itself, which is enough to read public playlists.
+    pub async fn authorize_app(
+        client: Client,
+        client_id: &str,
+        client_secret: &str,
+    ) -> FlomResult<Self> {
+        let request = client
+            .post(TOKEN_URL)
+            .basic_auth(client_id, Some(client_secret))
+            .form(&[("grant_type", "client_credentials")]);
+        let token: TokenResponse = send(request, "spotify authorization").await?;
+        Ok(Self {
+            client,
+            access_token: token.access_token,
+        })
+    }
+
+    /// Links of every track in a playlist, in playlist order.
+    pub async fn playlist_tracks(&self, playlist_id: &str) -> FlomResult<Vec<String>> {
+        let mut urls = Vec::new();
+        let mut next = Some(format!(
+            "{API_BASE}/playlists/{playlist_id}/tracks?fields=items(track(external_urls)),next&limit=100"
+        ));
+        while let Some(page_url) = next {
+            let page: PlaylistTracksPage = send(
+                self.client.get(page_url).bearer_auth(&self.access_token),
+                "spotify playlist lookup",
+            )
+            .await?;
+            urls.extend(
+                page.items
+                    .into_iter()
+                    .filter_map(|item| item.track?.external_urls.spotify),
+            );
+            next = page.next;
+        }
+        Ok(urls)
+    }
+
+    pub async fn artist_name(&self, artist_id: &str) -> FlomResult<String> {
+        let artist: Artist = send(
+            self.client
+                .get(format!("{API_BASE}/artists/{artist_id}"))
+                .bearer_auth(&self.access_token),
+            "spotify artist lookup",
+        )
+        .await?;
+        Ok(artist.name)
+    }
+
+    /// The Spotify link of the track with this ISRC, if Spotify has one.
+    pub async fn track_by_isrc(&self, isrc: &str) -> FlomResult<Option<String>> {
+        let search: TrackSearch = send(
+            self.client
+                .get(format!("{API_BASE}/search"))
+                .query(&[("q", format!("isrc:{isrc}").as_str()), ("type", "track")])
+                .bearer_auth(&self.access_token),
+            "spotify isrc search",
+        )
+        .await?;
+        Ok(search
+            .tracks
+            .items
+            .into_iter()
+            .find_map(|track| track.external_urls.spotify))
+    }
+
+    /// Episodes are region-locked, so lookups name a `market` (country code).
+    pub async fn episode(&self, episode_id: &str, market: &str) -> FlomResult<SpotifyEpisode> {
+        send(
+            self.client
+                .get(format!("{API_BASE}/episodes/{episode_id}"))
+                .query(&[("market", market)])
+                .bearer_auth(&self.access_token),
+            "spotify episode lookup",
+        )
+        .await
+    }
+
+    pub async fn search_episodes(
+        &self,
+        query: &str,
+        market: &str,
+        limit: u32,
+    ) -> FlomResult<Vec<SpotifyEpisode>> {
+        let limit = limit.to_string();
+        let search: EpisodeSearch = send(
+            self.client
+                .get(format!("{API_BASE}/search"))
+                .query(&[
+                    ("q", query),
+                    ("type", "episode"),
+                    ("market", market),
+                    ("limit", &limit),
+                ])
+                .bearer_auth(&self.access_token),
+            "spotify episode search",
+        )
+        .await?;
+        Ok(search.episodes.items.into_iter().flatten().collect())
+    }
+
+    /// Creates a private playlist on the user's account.
+    pub async fn create_playlist(
+        &self,
+        name: &str,
+        description: &str,
+    ) -> FlomResult<CreatedPlaylist> {
+        let user: User = send(
+            self.client
+                .get(format!("{API_BASE}/me"))
+                .bearer_auth(&self.access_token),
+            "spotify user lookup",
+        )
+        .await?;
+        let request = self
+            .client
+            .post(format!("{API_BASE}/users/{}/playlists", user.id))
+            .bearer_auth(&self.access_token)
+            .json(&json!({
+                "name": name,
+                "description": description,
+                "public": false,
+            }));
+        let playlist: PlaylistResponse = send(request, "spotify playlist creation").await?;
+        Ok(CreatedPlaylist {
+            id: playlist.id,
+            url: playlist.external_urls.spotify,
+        })
+    }
+
+    /// Appends tracks, given as `spotify:track:` URIs, in order.
+    pub async fn add_tracks(&self, playlist_id: &str, uris: &[String]) -> FlomResult<()> {
+        for batch in uris.chunks(ADD_BATCH) {
+            let request = self
+                .client
+                .post(format!("{API_BASE}/playlists/{playlist_id}/tracks"))
+                .bearer_auth(&self.access_token)
+                .json(&json!({ "uris": batch }));
+            let _: serde_json::Value = send(request, "spotify add tracks").await?;
+        }
+        Ok(())
+    }
+}
+
+/// The `spotify:track:` URI for a Spotify track link.
+pub fn track_uri(url: &str) -> Option<String> {
+    parse_spotify_track_id(url).map(|id| format!("spotify:track:{id}"))
+}
+
+async fn send<T: serde::de::DeserializeOwned>(
+    request: RequestBuilder,
+    what: &str,
+) -> FlomResult<T> {
+    let response = request
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|err| FlomError::Network(format!("{what} failed: {err}")))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(FlomError::ApiStatus {
+            status: status.as_u16(),
+            message: format!("{what} error: status={status} body={body}"),
+        });
+    }
+
+    response
+        .json::<T>()
+        .await
+        .map_err(|err| FlomError::Parse(format!("{what} response parse failed: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_uri_only_accepts_track_links() {
+        assert_eq!(
+            track_uri("https://open.spotify.com/intl-ja/track/4Km5HrUvYTaSUfiSGPJeQR?si=x"),
+            Some("spotify:track:4Km5HrUvYTaSUfiSGPJeQR".to_string())
+        );
+        assert_eq!(track_uri("https://open.spotify.com/album/abc"), None);
+    }
+}
@@ -0,0 +1,167 @@
+//! Artist pages, which Odesli can't resolve: their releases are listed
+//! through Apple Music so each one can be converted instead.
+
+use flom_core::{FlomError, FlomResult};
+use reqwest::Client;
+use url::Url;
+
+use crate::api::deezer::DeezerClient;
+use crate::api::itunes::ItunesClient;
+use crate::api::spotify::SpotifyClient;
+use crate::urls::Platform;
+
+/// An artist page on a streaming platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtistLink {
+    pub platform: Platform,
+    pub id: String,
+}
+
+impl ArtistLink {
+    pub fn parse(input: &str) -> Option<Self> {
+        let url = Url::parse(input.trim()).ok()?;
+        let domain = url.domain()?.trim_start_matches("www.");
+        let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+        // Drop locale prefixes such as `intl-ja` (Spotify) or `fr` (Deezer).
+        let path = match segments.first() {
+            Some(first) if first.starts_with("intl-") || first.len() == 2 => &segments[1..],
+            _ => &segments[..],
+        };
+        let (platform, id) = match (domain, path) {
+            ("open.spotify.com", ["artist", id, ..]) => (Platform::Spotify, *id),
+            // Apple puts the country first and the ID after the slug.
+            ("music.apple.com", ["artist", .., id]) => (Platform::AppleMusic, *id),
+            ("deezer.com", ["artist", id, ..]) => (Platform::Deezer, *id),
+            (
+                "tidal.com" | "listen.tidal.com",
+                ["browse", "artist", id, ..] | ["artist", id, ..],
+            ) => (Platform::Tidal, *id),
+            ("music.amazon.com", ["artists", id, ..]) => (Platform::AmazonMusic, *id),
+            _ => return None,
+        };
+        Some(Self {
+            platform,
+            id: id.to_string(),
+        })
+    }
+}
+
+/// One album of an artist, linked on Apple Music.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtistRelease {
+    pub title: String,
+    pub url: String,
+}
+
+/// The artist's name and up to `limit` of their albums, in the order Apple
+/// Music ranks them. Spotify links need `spotify` to look the name up; Tidal
+/// and Amazon Music artists can't be resolved without an account.
+pub async fn artist_releases(
+    client: &Client,
+    link: &ArtistLink,
+    spotify: Option<&SpotifyClient>,
+    country: &str,
+    limit: u32,
+) -> FlomResult<(String, Vec<ArtistRelease>)> {
+    let itunes = ItunesClient::new(client.clone(), country);
+    let name = match link.platform {
+        Platform::AppleMusic => None,
+        Platform::Deezer => Some(
+            DeezerClient::new(client.clone())
+                .artist_name(&link.id)
+                .await?,
+        ),
+        Platform::Spotify => {
+            let spotify = spotify.ok_or_else(|| {
+                FlomError::Config("reading spotify artists needs spotify credentials".to_string())
+            })?;
+            Some(spotify.artist_name(&link.id).await?)
+        }
+        platform => {
+            return Err(FlomError::UnsupportedInput(format!(
+                "{} artist links can't be resolved; pass a Spotify, Apple Music or Deezer artist link",
+                platform.key()
+            )));
+        }
+    };
+    let (artist_id, name) = match name {
+        Some(name) => {
+            let artist = itunes.find_artist(&name).await?.ok_or_else(|| {
+                FlomError::UnsupportedInput(format!("artist not found on Apple Music: {name}"))
+            })?;
+            (artist.artist_id, artist.artist_name)
+        }
+        None => {
+            let id = link.id.parse().map_err(|_| {
+                FlomError::InvalidInput(format!("invalid apple music artist id: {}", link.id))
+            })?;
+            (id, String::new())
+        }
+    };
+
+    let albums = itunes.artist_albums(artist_id, limit).await?;
+    let releases = albums
+        .into_iter()
+        .filter_map(|album| {
+            Some(ArtistRelease {
+                title: album.collection_name.unwrap_or_default(),
+                url: album.collection_view_url?,
+            })
+        })
+        .collect();
+    Ok((name, releases))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_artist_links() {
+        let cases = [
+            (
+                "https://open.spotify.com/intl-ja/artist/0gxyHStUsqpMadRV0Di1Qt?si=x",
+                Platform::Spotify,
+                "0gxyHStUsqpMadRV0Di1Qt",
+            ),
+            (
+                "https://music.apple.com/us/artist/rick-astley/669771",
+                Platform::AppleMusic,
+                "669771",
+            ),
+            (
+                "https://www.deezer.com/fr/artist/1234",
+                Platform::Deezer,
+                "1234",
+            ),
+            (
+                "https://tidal.com/browse/artist/3527",
+                Platform::Tidal,
+                "3527",
+            ),
+            (
+                "https://music.amazon.com/artists/B000QJQJ7U",
+                Platform::AmazonMusic,
+                "B000QJQJ7U",
+            ),
+        ];
+        for (url, platform, id) in cases {
+            assert_eq!(
+                ArtistLink::parse(url),
+                Some(ArtistLink {
+                    platform,
+                    id: id.to_string()
+                }),
+                "{url}"
+            );
+        }
+        assert_eq!(
+            ArtistLink::parse("https://open.spotify.com/track/4Km5HrUvYTaSUfiSGPJeQR"),
+            None
+        );
+        assert_eq!(
+            ArtistLink::parse("https://music.apple.com/us/album/x/1440?i=1441"),
+            None
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Synchronous wrapper around [`MusicConverter`] for tools without an async
+//! runtime, such as build scripts. Each call runs on a private current-thread
+//! runtime, so it must not be used from inside another async runtime.
+
+use flom_core::{ConversionResult, FlomError, FlomResult};
+use tokio::runtime::{Builder, Runtime};
+
+use crate::api::odesli::{EntityQuery, OdesliResponse};
+use crate::converter::MusicConverter;
+use crate::matching::{MatchThresholds, TrackRef};
+
+#[derive(Debug)]
+pub struct BlockingConverter {
+    inner: MusicConverter,
+    runtime: Runtime,
+}
+
+impl MusicConverter {
+    /// Wraps this converter in a [`BlockingConverter`].
+    pub fn blocking(self) -> BlockingConverter {
+        BlockingConverter {
+            inner: self,
+            runtime: runtime(),
+        }
+    }
+}
+
+impl BlockingConverter {
+    /// The wrapped converter, for its synchronous methods.
+    pub fn inner(&self) -> &MusicConverter {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> MusicConverter {
+        self.inner
+    }
+
+    /// Converts `url` to the platform `target` (e.g. `spotify` or `apple-music`).
+    pub fn convert(&self, url: &str, target: &str) -> FlomResult<ConversionResult> {
+        let target_key = MusicConverter::normalize_target(target)
+            .ok_or_else(|| FlomError::InvalidInput(format!("unknown target: {target}")))?;
+        let response = self.fetch_links(url)?;
+        self.inner.convert(&response, url, &target_key)
+    }
+
+    /// See [`MusicConverter::fetch_links`].
+    pub fn fetch_links(&self, url: &str) -> FlomResult<OdesliResponse> {
+        self.runtime.block_on(self.inner.fetch_links(url))
+    }
+
+    /// See [`MusicConverter::fetch_by_id`].
+    pub fn fetch_by_id(&self, query: &EntityQuery) -> FlomResult<OdesliResponse> {
+        self.runtime.block_on(self.inner.fetch_by_id(query))
+    }
+
+    /// See [`MusicConverter::search_link`].
+    pub fn search_link(
+        &self,
+        track: &TrackRef<'_>,
+        thresholds: &MatchThresholds,
+    ) -> FlomResult<String> {
+        self.runtime
+            .block_on(self.inner.search_link(track, thresholds))
+    }
+
+    /// See [`MusicConverter::search_text`].
+    pub fn search_text(&self, query: &str, thresholds: &MatchThresholds) -> FlomResult<String> {
+        self.runtime
+            .block_on(self.inner.search_text(query, thresholds))
+    }
+
+    /// See [`MusicConverter::album_tracks`].
+    pub fn album_tracks(&self, url: &str) -> FlomResult<Option<Vec<String>>> {
+        self.runtime.block_on(self.inner.album_tracks(url))
+    }
+}
+
+fn runtime() -> Runtime {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build blocking runtime")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flom_config::FlomConfigData;
+
+    #[test]
+    fn converts_without_an_async_runtime() {
+        let converter = MusicConverter::new(None, &FlomConfigData::default()).blocking();
+        assert!(matches!(
+            converter.convert("https://open.spotify.com/track/abc", "myspace"),
+            Err(FlomError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            converter.fetch_links("not a url"),
+            Err(FlomError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,230 @@
+//! On-disk cache of Odesli responses, one file per lookup.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use flom_core::{FlomError, FlomResult};
+use serde::{Deserialize, Serialize};
+
+use crate::api::odesli::OdesliResponse;
+
+/// Responses keyed by lookup and user country, valid for `ttl`.
+///
+/// Unlike the entity database, which only knows the links inside a response,
+/// the cache answers the exact input again, including short links and other
+/// links Odesli resolved but doesn't list.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    country: String,
+    ttl: Duration,
+}
+
+/// What [`ResponseCache::stats`] found on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub entries: usize,
+    /// Entries past the TTL, or unreadable, that [`ResponseCache::prune`] would remove.
+    pub expired: usize,
+    pub bytes: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    fetched_at: u64,
+    response: OdesliResponse,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>, country: impl Into<String>, ttl: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            country: country.into(),
+            ttl,
+        }
+    }
+
+    /// The stored response for `key`, unless it is missing, damaged or expired.
+    pub fn get(&self, key: &str) -> Option<OdesliResponse> {
+        let key = self.full_key(key);
+        let path = self.path(&key);
+        let entry: Entry = serde_json::from_slice(&fs::read(&path).ok()?).ok()?;
+        if entry.key != key {
+            return None;
+        }
+        if now().saturating_sub(entry.fetched_at) >= self.ttl.as_secs() {
+            let _ = fs::remove_file(path);
+            return None;
+        }
+        Some(entry.response)
+    }
+
+    /// Stores `response` for `key`. A failed write only costs a request later,
+    /// so it is ignored.
+    pub fn put(&self, key: &str, response: &OdesliResponse) {
+        let key = self.full_key(key);
+        let path = self.path(&key);
+        let entry = Entry {
+            key,
+            fetched_at: now(),
+            response: response.clone(),
+        };
+        if let Ok(body) = serde_json::to_vec(&entry)
+            && fs::create_dir_all(&self.dir).is_ok()
+        {
+            let _ = fs::write(path, body);
+        }
+    }
+
+    /// Counts the entries of every country and their size on disk.
+    pub fn stats(&self) -> FlomResult<CacheStats> {
+        let mut stats = CacheStats::default();
+        for path in self.files()? {
+            stats.entries += 1;
+            stats.bytes += fs::metadata(&path).map_or(0, |meta| meta.len());
+            if self.is_expired(&path) {
+                stats.expired += 1;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Removes every entry and returns how many there were.
+    pub fn clear(&self) -> FlomResult<usize> {
+        self.remove_where(|_| true)
+    }
+
+    /// Removes expired and unreadable entries and returns how many there were.
+    pub fn prune(&self) -> FlomResult<usize> {
+        self.remove_where(|path| self.is_expired(path))
+    }
+
+    fn remove_where(&self, mut remove: impl FnMut(&Path) -> bool) -> FlomResult<usize> {
+        let mut removed = 0;
+        for path in self.files()? {
+            if remove(&path) {
+                fs::remove_file(&path).map_err(|err| {
+                    FlomError::Config(format!("failed to remove {}: {err}", path.display()))
+                })?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// The entry files in the cache directory; none when it doesn't exist yet.
+    fn files(&self) -> FlomResult<Vec<PathBuf>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(FlomError::Config(format!(
+                    "failed to read {}: {err}",
+                    self.dir.display()
+                )));
+            }
+        };
+        Ok(entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect())
+    }
+
+    fn is_expired(&self, path: &Path) -> bool {
+        let fetched_at = fs::read(path)
+            .ok()
+            .and_then(|body| serde_json::from_slice::<Stamp>(&body).ok())
+            .map(|stamp| stamp.fetched_at);
+        fetched_at.is_none_or(|fetched_at| now().saturating_sub(fetched_at) >= self.ttl.as_secs())
+    }
+
+    fn full_key(&self, key: &str) -> String {
+        format!("{}\n{key}", self.country)
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", fnv1a(key)))
+    }
+}
+
+/// Just the age of an entry, read without parsing the response.
+#[derive(Deserialize)]
+struct Stamp {
+    fetched_at: u64,
+}
+
+/// FNV-1a, for file names that stay the same across builds.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn response() -> OdesliResponse {
+        OdesliResponse {
+            entity_unique_id: "SPOTIFY_SONG::abc".to_string(),
+            page_url: "https://song.link/s/abc".to_string(),
+            links_by_platform: HashMap::new(),
+            entities_by_unique_id: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn entries_are_keyed_by_country_and_expire() {
+        let dir = std::env::temp_dir().join("flom-test-response-cache");
+        let _ = fs::remove_dir_all(&dir);
+        let url = "https://spotify.link/abc";
+
+        let cache = ResponseCache::new(&dir, "US", Duration::from_secs(60));
+        assert!(cache.get(url).is_none());
+        cache.put(url, &response());
+        assert_eq!(cache.get(url).unwrap().page_url, "https://song.link/s/abc");
+        assert!(
+            ResponseCache::new(&dir, "JP", Duration::from_secs(60))
+                .get(url)
+                .is_none()
+        );
+        assert!(
+            ResponseCache::new(&dir, "US", Duration::ZERO)
+                .get(url)
+                .is_none()
+        );
+        // The expired entry was removed.
+        assert!(cache.get(url).is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn stats_prune_and_clear() {
+        let dir = std::env::temp_dir().join("flom-test-response-cache-admin");
+        let _ = fs::remove_dir_all(&dir);
+        let cache = ResponseCache::new(&dir, "US", Duration::from_secs(60));
+        assert_eq!(cache.stats().unwrap(), CacheStats::default());
+
+        cache.put("https://spotify.link/a", &response());
+        cache.put("https://spotify.link/b", &response());
+        fs::write(dir.join("0000000000000000.json"), "damaged").unwrap();
+        let stats = cache.stats().unwrap();
+        assert_eq!((stats.entries, stats.expired), (3, 1));
+        assert!(stats.bytes > 0);
+
+        assert_eq!(cache.prune().unwrap(), 1);
+        assert_eq!(cache.stats().unwrap().entries, 2);
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert_eq!(cache.stats().unwrap().entries, 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
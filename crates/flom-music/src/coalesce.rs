@@ -0,0 +1,100 @@
+//! Sharing one upstream request between concurrent lookups of the same key, so
+//! a burst of identical links (e.g. a popular track posted to a bot) costs a
+//! single Odesli call.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Mutex;
+
+use flom_core::FlomResult;
+use futures_util::FutureExt;
+use futures_util::future::{BoxFuture, Shared};
+
+type Flight<T> = Shared<BoxFuture<'static, FlomResult<T>>>;
+
+pub(crate) struct Coalescer<T> {
+    flights: Mutex<HashMap<String, Flight<T>>>,
+}
+
+impl<T> Default for Coalescer<T> {
+    fn default() -> Self {
+        Self {
+            flights: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Coalescer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Coalescer").finish_non_exhaustive()
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Coalescer<T> {
+    /// Awaits the request already in flight for `key`, or starts one with `start`.
+    /// Every caller gets a clone of the same result.
+    pub(crate) async fn run<F>(&self, key: String, start: impl FnOnce() -> F) -> FlomResult<T>
+    where
+        F: Future<Output = FlomResult<T>> + Send + 'static,
+    {
+        let flight = self
+            .flights()
+            .entry(key.clone())
+            .or_insert_with(|| start().boxed().shared())
+            .clone();
+        let result = flight.clone().await;
+        let mut flights = self.flights();
+        if flights
+            .get(&key)
+            .is_some_and(|current| current.ptr_eq(&flight))
+        {
+            flights.remove(&key);
+        }
+        result
+    }
+
+    fn flights(&self) -> std::sync::MutexGuard<'_, HashMap<String, Flight<T>>> {
+        self.flights.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn concurrent_lookups_share_one_request() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let coalescer = Coalescer::<u32>::default();
+        let start = || {
+            let calls = Arc::clone(&calls);
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+                Ok(7)
+            }
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let (a, b) = runtime.block_on(async {
+            tokio::join!(
+                coalescer.run("spotify:abc".to_string(), start),
+                coalescer.run("spotify:abc".to_string(), start)
+            )
+        });
+        assert_eq!((a.unwrap(), b.unwrap()), (7, 7));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(coalescer.flights().is_empty());
+
+        // Once settled, the next lookup asks again.
+        runtime
+            .block_on(coalescer.run("spotify:abc".to_string(), start))
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,10 +1,32 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use flom_config::{FlomConfigData, resolve_user_country};
-use flom_core::{ConversionResult, FlomError, FlomResult, MediaInfo, validate_url};
+use flom_config::{
+    FlomConfigData, TARGET_KEYS, resolve_locale, resolve_song_if_single, resolve_user_country,
+};
+use flom_core::{
+    ConversionResult, FlomError, FlomResult, MediaInfo, MediaType, find_platform_key,
+    validate_music_url,
+};
 use reqwest::Client;
 
-use crate::api::odesli::{OdesliClient, OdesliResponse};
+use crate::api::deezer::DeezerClient;
+use crate::api::itunes::{ItunesClient, ItunesTrack};
+use crate::api::odesli::{EntityQuery, OdesliClient, OdesliOptions, OdesliResponse};
+use crate::artist::ArtistLink;
+use crate::cache::ResponseCache;
+use crate::coalesce::Coalescer;
+use crate::lru::Lru;
+use crate::matching::{MatchThresholds, TrackRef, score};
+use crate::memo::{EntityIndex, StoredEntity, append_entity};
+use crate::metadata::{clean_title, main_artist};
+use crate::middleware::{Chain, LinkRequest, Middleware};
+use crate::parsers::parse_platform_id;
+use crate::parsers::youtube::parse_youtube_collection;
+use crate::podcast::EpisodeLink;
+use crate::urls::clean_share_url;
 
 #[derive(Debug, Clone)]
 pub struct TargetOption {
@@ -12,9 +34,37 @@ pub struct TargetOption {
     pub label: String,
 }
 
+/// How lookups use what earlier runs stored: the response cache and the
+/// entity database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    #[default]
+    Use,
+    /// Ask Odesli again and store the fresh answers.
+    Refresh,
+    /// Neither read nor write stored answers.
+    Off,
+    /// Only answer from stored answers; never ask Odesli.
+    Offline,
+}
+
+/// Responses kept in memory for repeated links of one run.
+const RECENT_CAPACITY: usize = 512;
+
 #[derive(Debug, Clone)]
 pub struct MusicConverter {
     client: OdesliClient,
+    search: ItunesClient,
+    memo: Arc<Mutex<EntityIndex>>,
+    middleware: Chain,
+    entity_db: Option<PathBuf>,
+    cache: Option<ResponseCache>,
+    cache_mode: CacheMode,
+    /// Responses of this run by lookup, whatever the cache mode.
+    recent: Arc<Mutex<Lru<OdesliResponse>>>,
+    strict: bool,
+    /// Odesli requests in flight, shared by concurrent lookups of the same entity.
+    in_flight: Arc<Coalescer<OdesliResponse>>,
 }
 
 impl MusicConverter {
@@ -24,14 +74,389 @@ impl MusicConverter {
             .build()
             .expect("failed to build http client");
         let user_country = resolve_user_country(config);
+        let options = OdesliOptions {
+            song_if_single: resolve_song_if_single(config).unwrap_or(false),
+            locale: resolve_locale(config),
+        };
         Self {
-            client: OdesliClient::new(client, api_key, user_country),
+            search: ItunesClient::new(client.clone(), user_country.clone()),
+            client: OdesliClient::new(client, api_key, user_country).with_options(options),
+            memo: Arc::new(Mutex::new(EntityIndex::new())),
+            middleware: Chain::default(),
+            entity_db: None,
+            cache: None,
+            cache_mode: CacheMode::Use,
+            recent: Arc::new(Mutex::new(Lru::new(RECENT_CAPACITY))),
+            strict: false,
+            in_flight: Arc::default(),
+        }
+    }
+
+    /// The HTTP client behind this converter, for other lookups to share its
+    /// connection pool.
+    pub fn http_client(&self) -> &Client {
+        self.search.http_client()
+    }
+
+    pub fn with_options(mut self, options: OdesliOptions) -> Self {
+        self.client = self.client.with_options(options);
+        // Tracks stored under other options don't answer these lookups.
+        if let Some(path) = &self.entity_db {
+            self.memo = Arc::new(Mutex::new(EntityIndex::load(path, &self.options_tag())));
         }
+        self
+    }
+
+    /// Appends `middleware` to the conversion pipeline; see [`Middleware`].
+    /// Clones of this converter made afterwards share it.
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Makes [`MusicConverter::convert`] fail on results that carry a warning,
+    /// so only exact conversions come out.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
     }
 
+    /// Keeps resolved tracks in a JSON-lines database at `path`, so links to a
+    /// track seen in any earlier run resolve instantly and offline.
+    pub fn with_entity_db(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        self.memo = Arc::new(Mutex::new(EntityIndex::load(&path, &self.options_tag())));
+        self.entity_db = Some(path);
+        self
+    }
+
+    /// Keeps Odesli responses in `dir` for `ttl`, keyed by the lookup, the
+    /// user country and the [`OdesliOptions`], so repeating a batch doesn't
+    /// spend the rate limit again.
+    pub fn with_response_cache(mut self, dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        self.cache = Some(ResponseCache::new(dir, self.client.user_country(), ttl));
+        self
+    }
+
+    /// Sets whether lookups may be answered from, and are written to, the
+    /// response cache and the entity database.
+    pub fn with_cache_mode(mut self, mode: CacheMode) -> Self {
+        self.cache_mode = mode;
+        self
+    }
+
+    /// Records the ISRC of the already resolved track behind `url`, so
+    /// [`MusicConverter::lookup_isrc`] finds it from now on.
+    pub fn remember_isrc(&self, url: &str, isrc: &str) {
+        let Ok(url) = clean_share_url(url) else {
+            return;
+        };
+        if self.lookup_isrc(isrc).is_some() {
+            return;
+        }
+        let Some(response) = self.memo().lookup(&url) else {
+            return;
+        };
+        let stored = StoredEntity {
+            isrc: Some(isrc.trim().to_string()),
+            options: self.options_tag(),
+            response,
+        };
+        self.persist(&stored);
+        self.memo().insert_stored(stored);
+    }
+
+    /// Links for a track by ISRC, if it was resolved and tagged before.
+    pub fn lookup_isrc(&self, isrc: &str) -> Option<OdesliResponse> {
+        self.memo().lookup_isrc(isrc)
+    }
+
+    /// Fetches links for `url`, answering from earlier responses (of this session, or
+    /// from the entity database) when the same track was already resolved through
+    /// another platform's link.
     pub async fn fetch_links(&self, url: &str) -> FlomResult<OdesliResponse> {
-        validate_url(url)?;
-        self.client.fetch_links(url).await
+        let url = checked_url(url)?;
+        self.fetch(LinkRequest::Url(url)).await
+    }
+
+    /// The unmodified Odesli response body for `url`, for debugging and scripts.
+    /// Always asks Odesli: the entity database and middleware are bypassed.
+    pub async fn fetch_raw(&self, url: &str) -> FlomResult<serde_json::Value> {
+        self.client.fetch_raw(&checked_url(url)?).await
+    }
+
+    /// Fetches links for an entity given by platform-native ID. The response is
+    /// remembered, so converting any of its links (or looking the ID up again)
+    /// afterwards needs no request.
+    pub async fn fetch_by_id(&self, query: &EntityQuery) -> FlomResult<OdesliResponse> {
+        self.fetch(LinkRequest::Id(query.clone())).await
+    }
+
+    /// Finds a link for a track known only by title and artist: the search hit
+    /// scoring best against `track`, if `thresholds` accept it.
+    pub async fn search_link(
+        &self,
+        track: &TrackRef<'_>,
+        thresholds: &MatchThresholds,
+    ) -> FlomResult<String> {
+        let term = format!("{} {}", main_artist(track.artist), clean_title(track.title));
+        let candidates = self.search.search_songs(&term, SEARCH_LIMIT).await?;
+        best_match(track, &candidates, thresholds)
+            .map(str::to_string)
+            .ok_or_else(|| {
+                FlomError::UnsupportedInput(format!(
+                    "no match found for {} / {}",
+                    track.title, track.artist
+                ))
+            })
+    }
+
+    /// Finds a link for free text such as `Daft Punk - One More Time`. With an
+    /// `artist - title` separator the hit has to match like [`Self::search_link`]
+    /// (in either order); plain text takes the top search hit.
+    pub async fn search_text(
+        &self,
+        query: &str,
+        thresholds: &MatchThresholds,
+    ) -> FlomResult<String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(FlomError::InvalidInput("search query is empty".to_string()));
+        }
+        let Some((artist, title)) = split_query(query) else {
+            let candidates = self.search.search_songs(query, SEARCH_LIMIT).await?;
+            return candidates
+                .into_iter()
+                .find_map(|candidate| candidate.track_view_url)
+                .ok_or_else(|| FlomError::UnsupportedInput(format!("no track found for {query}")));
+        };
+        let candidates = self.search.search_songs(query, SEARCH_LIMIT).await?;
+        let as_given = TrackRef {
+            title,
+            artist,
+            duration: None,
+        };
+        let swapped = TrackRef {
+            title: artist,
+            artist: title,
+            duration: None,
+        };
+        best_match(&as_given, &candidates, thresholds)
+            .or_else(|| best_match(&swapped, &candidates, thresholds))
+            .map(str::to_string)
+            .ok_or_else(|| FlomError::UnsupportedInput(format!("no track found for {query}")))
+    }
+
+    /// Links to the tracks of the album behind `url`, in album order, or `None`
+    /// when `url` is not an album. Track lists come from the iTunes lookup API,
+    /// so the album has to be on Apple Music.
+    pub async fn album_tracks(&self, url: &str) -> FlomResult<Option<Vec<String>>> {
+        let response = self.fetch_links(url).await?;
+        if !response.entity_unique_id.contains("_ALBUM::") {
+            return Ok(None);
+        }
+        let collection_id = itunes_album_id(&response).ok_or_else(|| {
+            FlomError::UnsupportedInput(format!(
+                "album is not on Apple Music, so its tracks can't be listed: {url}"
+            ))
+        })?;
+        let tracks = self.search.album_tracks(collection_id).await?;
+        if tracks.is_empty() {
+            return Err(FlomError::Api(format!("no tracks listed for album: {url}")));
+        }
+        Ok(Some(
+            tracks
+                .into_iter()
+                .filter_map(|track| track.track_view_url)
+                .collect(),
+        ))
+    }
+
+    /// Adds the release date, year and label to the source of `results`, all
+    /// of one link. They come from Deezer, with the iTunes store as a fallback
+    /// for the date, so this costs up to two more requests.
+    pub async fn add_release_info(&self, results: &mut [ConversionResult]) -> FlomResult<()> {
+        let Some(first) = results.first() else {
+            return Ok(());
+        };
+        let response = self.fetch_links(&first.source_url).await?;
+        let mut release = MediaInfo::default();
+        if let Some((kind, id)) = provider_entity(&response, "DEEZER") {
+            let deezer = DeezerClient::new(self.http_client().clone());
+            let album_id = if kind == "ALBUM" {
+                Some(id.to_string())
+            } else {
+                let track = deezer.track(id).await?;
+                release.complete_release_date(track.release_date.as_deref());
+                track.album.map(|album| album.id.to_string())
+            };
+            if let Some(album_id) = album_id {
+                let album = deezer.album(&album_id).await?;
+                release.complete_release_date(album.release_date.as_deref());
+                release.label = album.label;
+            }
+        }
+        if release.release_date.is_none()
+            && let Some((_, id)) = provider_entity(&response, "ITUNES")
+            && let Some(item) = self.search.lookup(id).await?
+        {
+            release.complete_release_date(item.release_date.as_deref());
+        }
+        for result in results {
+            let info = result.source_info.get_or_insert_with(MediaInfo::default);
+            info.complete_release_date(release.release_date.as_deref());
+            if info.label.is_none() {
+                info.label = release.label.clone();
+            }
+        }
+        Ok(())
+    }
+
+    async fn fetch(&self, mut request: LinkRequest) -> FlomResult<OdesliResponse> {
+        if let Some(response) = self.middleware.before_request(&mut request)? {
+            self.memo().insert(response.clone());
+            return Ok(response);
+        }
+        // Lookups of the same link or ID share one request and one cache entry.
+        let key = match &request {
+            LinkRequest::Url(url) => url.clone(),
+            LinkRequest::Id(query) => format!(
+                "{}:{}:{}",
+                query.platform,
+                query.entity_type.as_str(),
+                query.id
+            ),
+        };
+        if let Some(response) = self.recent().get(&key) {
+            return Ok(response);
+        }
+        let stored = matches!(self.cache_mode, CacheMode::Use | CacheMode::Offline);
+        let mut response = match &request {
+            LinkRequest::Url(url) => {
+                if stored && let Some(response) = self.memo().lookup(url) {
+                    return Ok(response);
+                }
+                if stored && let Some(response) = self.cached(&key) {
+                    self.memo().insert(response.clone());
+                    return Ok(response);
+                }
+                if self.cache_mode == CacheMode::Offline {
+                    return Err(not_cached(url));
+                }
+                let (client, owned) = (self.client.clone(), url.clone());
+                let response = self
+                    .in_flight
+                    .run(
+                        key.clone(),
+                        || async move { client.fetch_links(&owned).await },
+                    )
+                    .await?;
+                // A concurrent lookup sharing the request may have stored it already.
+                if stored && let Some(response) = self.memo().lookup(url) {
+                    return Ok(response);
+                }
+                response
+            }
+            LinkRequest::Id(query) => {
+                if stored && let Some(response) = self.memo().lookup_id(query) {
+                    return Ok(response);
+                }
+                if stored && let Some(response) = self.cached(&key) {
+                    self.memo().insert(response.clone());
+                    return Ok(response);
+                }
+                if self.cache_mode == CacheMode::Offline {
+                    return Err(not_cached(&key));
+                }
+                let (client, owned) = (self.client.clone(), query.clone());
+                let response = self
+                    .in_flight
+                    .run(
+                        key.clone(),
+                        || async move { client.fetch_by_id(&owned).await },
+                    )
+                    .await?;
+                if stored && let Some(response) = self.memo().lookup_id(query) {
+                    return Ok(response);
+                }
+                response
+            }
+        };
+        self.middleware.after_response(&request, &mut response)?;
+        self.recent().insert(key.clone(), response.clone());
+        let stored = StoredEntity {
+            isrc: None,
+            options: self.options_tag(),
+            response: response.clone(),
+        };
+        if self.cache_mode != CacheMode::Off {
+            self.store_cached(&key, &response);
+            self.persist(&stored);
+        }
+        self.memo().insert_stored(stored);
+        Ok(response)
+    }
+
+    fn recent(&self) -> std::sync::MutexGuard<'_, Lru<OdesliResponse>> {
+        self.recent.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// A cached response, already through the middleware when it was stored.
+    fn cached(&self, key: &str) -> Option<OdesliResponse> {
+        self.cache.as_ref()?.get(&self.cache_key(key))
+    }
+
+    fn store_cached(&self, key: &str, response: &OdesliResponse) {
+        if let Some(cache) = &self.cache {
+            cache.put(&self.cache_key(key), response);
+        }
+    }
+
+    /// The response cache key for a lookup under this converter's options;
+    /// the lookup alone under the default ones.
+    fn cache_key(&self, key: &str) -> String {
+        match self.options_tag() {
+            tag if tag.is_empty() => key.to_string(),
+            tag => format!("{tag}\n{key}"),
+        }
+    }
+
+    fn options_tag(&self) -> String {
+        self.client.options().cache_tag()
+    }
+
+    fn persist(&self, stored: &StoredEntity) {
+        // A lost entry only costs a request later, so it never fails a conversion.
+        if let Some(path) = &self.entity_db {
+            let _ = append_entity(path, stored);
+        }
+    }
+
+    /// Builds the result for `target_key` and passes it through the middleware.
+    pub fn convert(
+        &self,
+        response: &OdesliResponse,
+        source_url: &str,
+        target_key: &str,
+    ) -> FlomResult<ConversionResult> {
+        let mut result = Self::convert_from_response(response, source_url, target_key)?;
+        self.middleware.after_convert(&mut result)?;
+        if self.strict
+            && let Some(warning) = &result.warning
+        {
+            return Err(FlomError::UnsupportedInput(format!(
+                "strict mode: {warning}"
+            )));
+        }
+        Ok(result)
+    }
+
+    fn memo(&self) -> std::sync::MutexGuard<'_, EntityIndex> {
+        self.memo.lock().unwrap_or_else(|err| err.into_inner())
     }
 
     pub fn targets_from_response(response: &OdesliResponse) -> Vec<TargetOption> {
@@ -45,19 +470,21 @@ impl MusicConverter {
             .collect()
     }
 
+    /// Every platform [`MusicConverter::normalize_target`] accepts, for choosing
+    /// a target before any link is looked up.
+    pub fn known_targets() -> Vec<TargetOption> {
+        TARGET_KEYS
+            .iter()
+            .map(|key| TargetOption {
+                key: key.to_string(),
+                label: display_name(key).to_string(),
+            })
+            .collect()
+    }
+
+    /// The target key `input` names, as [`find_platform_key`] matches it.
     pub fn normalize_target(input: &str) -> Option<String> {
-        let normalized = input.trim().to_lowercase();
-        match normalized.as_str() {
-            "spotify" => Some("spotify".to_string()),
-            "applemusic" | "apple-music" | "apple_music" => Some("appleMusic".to_string()),
-            "itunes" => Some("itunes".to_string()),
-            "youtube" => Some("youtube".to_string()),
-            "youtubemusic" | "youtube-music" | "youtube_music" => Some("youtubeMusic".to_string()),
-            "tidal" => Some("tidal".to_string()),
-            "deezer" => Some("deezer".to_string()),
-            "amazonmusic" | "amazon-music" | "amazon_music" => Some("amazonMusic".to_string()),
-            _ => None,
-        }
+        find_platform_key(&TARGET_KEYS, input).map(str::to_string)
     }
 
     pub fn convert_from_response(
@@ -69,7 +496,6 @@ impl MusicConverter {
             .entities_by_unique_id
             .get(&response.entity_unique_id);
 
-        let source_info = source_entity.map(entity_to_media);
         let source_platform = source_entity
             .and_then(|entity| entity.api_provider.clone())
             .or_else(|| infer_source_platform(&response.links_by_platform, source_url));
@@ -82,16 +508,145 @@ impl MusicConverter {
             .entities_by_unique_id
             .get(&target_link.entity_unique_id);
 
+        // Source entities often lack metadata; borrow it from the target entity first,
+        // then from any other entity describing the same track.
+        let mut fallbacks: Vec<_> = response.entities_by_unique_id.iter().collect();
+        fallbacks.sort_by(|a, b| a.0.cmp(b.0));
+        let source_info = complete_media(
+            source_entity.map(entity_to_media),
+            target_entity
+                .into_iter()
+                .chain(fallbacks.into_iter().map(|(_, entity)| entity)),
+        );
+
+        let source_id = source_entity
+            .and_then(|entity| entity.id.clone())
+            .or_else(|| parse_platform_id(source_url));
+        let target_id = target_entity
+            .and_then(|entity| entity.id.clone())
+            .or_else(|| parse_platform_id(&target_link.url));
+
         Ok(ConversionResult {
             source_url: source_url.to_string(),
             target_url: Some(target_link.url.clone()),
             source_platform,
             target_platform: Some(target_key.to_string()),
+            source_id,
+            target_id,
             source_info,
             target_info: target_entity.map(entity_to_media),
+            page_url: Some(response.page_url.clone()),
+            media_type: Self::media_type(response),
             warning: None,
         })
     }
+
+    /// Kind of the entity a lookup was made for, falling back to the type
+    /// encoded in its unique ID (`SPOTIFY_SONG::…`).
+    pub fn media_type(response: &OdesliResponse) -> Option<MediaType> {
+        response
+            .entities_by_unique_id
+            .get(&response.entity_unique_id)
+            .and_then(|entity| entity.kind.as_deref())
+            .and_then(MediaType::parse)
+            .or_else(|| {
+                let (prefix, _) = response.entity_unique_id.split_once("::")?;
+                MediaType::parse(prefix.rsplit('_').next()?)
+            })
+    }
+}
+
+const SEARCH_LIMIT: u32 = 10;
+
+/// Normalizes a link and rejects the kinds Odesli can't convert, with guidance.
+fn checked_url(url: &str) -> FlomResult<String> {
+    let url = clean_share_url(url)?;
+    if EpisodeLink::parse(&url).is_some() {
+        return Err(FlomError::UnsupportedInput(
+            "podcast episodes can't be converted like songs; use `flom podcast`".to_string(),
+        ));
+    }
+    validate_music_url(&url)?;
+    // Odesli answers these with a vague error, so explain them up front.
+    if let Some(collection) = parse_youtube_collection(&url) {
+        return Err(FlomError::UnsupportedInput(
+            collection.guidance().to_string(),
+        ));
+    }
+    if ArtistLink::parse(&url).is_some() {
+        return Err(FlomError::UnsupportedInput(
+            "artist pages can't be converted as one link; use `flom artist` to convert their releases"
+                .to_string(),
+        ));
+    }
+    Ok(url)
+}
+
+/// The error for a lookup that offline mode can't answer. Asking again
+/// won't help, so it isn't a network error that batches would retry.
+fn not_cached(lookup: &str) -> FlomError {
+    FlomError::UnsupportedInput(format!(
+        "{lookup} is not cached, and offline mode doesn't ask Odesli"
+    ))
+}
+
+/// Splits `artist - title` at the first dash surrounded by spaces.
+fn split_query(query: &str) -> Option<(&str, &str)> {
+    [" - ", " – ", " — "]
+        .iter()
+        .filter_map(|separator| query.split_once(separator))
+        .min_by_key(|(artist, _)| artist.len())
+        .map(|(artist, title)| (artist.trim(), title.trim()))
+        .filter(|(artist, title)| !artist.is_empty() && !title.is_empty())
+}
+
+/// The kind (`SONG`, `ALBUM`) and ID of the entity `provider` (`DEEZER`,
+/// `ITUNES`) lists for the looked-up item.
+fn provider_entity<'a>(response: &'a OdesliResponse, provider: &str) -> Option<(&'a str, &'a str)> {
+    std::iter::once(&response.entity_unique_id)
+        .chain(
+            response
+                .links_by_platform
+                .values()
+                .map(|link| &link.entity_unique_id),
+        )
+        .find_map(|unique_id| {
+            let (prefix, id) = unique_id.split_once("::")?;
+            Some((prefix.strip_prefix(provider)?.strip_prefix('_')?, id))
+        })
+}
+
+fn itunes_album_id(response: &OdesliResponse) -> Option<&str> {
+    std::iter::once(&response.entity_unique_id)
+        .chain(
+            response
+                .links_by_platform
+                .values()
+                .map(|link| &link.entity_unique_id),
+        )
+        .find_map(|id| id.strip_prefix("ITUNES_ALBUM::"))
+}
+
+/// The link of the candidate scoring highest against `track`, if good enough.
+fn best_match<'a>(
+    track: &TrackRef<'_>,
+    candidates: &'a [ItunesTrack],
+    thresholds: &MatchThresholds,
+) -> Option<&'a str> {
+    candidates
+        .iter()
+        .filter_map(|candidate| {
+            let found = TrackRef {
+                title: candidate.track_name.as_deref()?,
+                artist: candidate.artist_name.as_deref()?,
+                duration: candidate.track_time_millis.map(Duration::from_millis),
+            };
+            let url = candidate.track_view_url.as_deref()?;
+            Some((score(track, &found, thresholds.duration_tolerance), url))
+        })
+        .filter(|(score, _)| *score >= thresholds.min_score)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, url)| url)
 }
 
 fn display_name(key: &str) -> &str {
@@ -109,11 +664,61 @@ fn display_name(key: &str) -> &str {
 }
 
 fn entity_to_media(entity: &crate::api::odesli::OdesliEntity) -> MediaInfo {
-    MediaInfo {
+    let mut info = MediaInfo {
         title: entity.title.clone(),
         artist: entity.artist_name.clone(),
         album: entity.album_name.clone(),
+        kind: entity.kind.clone(),
+        duration_ms: entity.duration,
+        artwork_url: entity.thumbnail_url.clone(),
+        isrc: entity.isrc.clone(),
+        ..Default::default()
+    };
+    info.complete_release_date(entity.release_date.as_deref());
+    info
+}
+
+fn complete_media<'a>(
+    info: Option<MediaInfo>,
+    candidates: impl Iterator<Item = &'a crate::api::odesli::OdesliEntity>,
+) -> Option<MediaInfo> {
+    let mut info = info.unwrap_or_default();
+    for entity in candidates {
+        if info.title.is_some()
+            && info.artist.is_some()
+            && info.artwork_url.is_some()
+            && info.duration_ms.is_some()
+        {
+            break;
+        }
+        if info.title.is_none() {
+            info.title = entity.title.clone();
+        }
+        if info.artist.is_none() {
+            info.artist = entity.artist_name.clone();
+        }
+        if info.album.is_none() {
+            info.album = entity.album_name.clone();
+        }
+        if info.kind.is_none() {
+            info.kind = entity.kind.clone();
+        }
+        if info.duration_ms.is_none() {
+            info.duration_ms = entity.duration;
+        }
+        if info.artwork_url.is_none() {
+            info.artwork_url = entity.thumbnail_url.clone();
+        }
+        if info.isrc.is_none() {
+            info.isrc = entity.isrc.clone();
+        }
+        info.complete_release_date(entity.release_date.as_deref());
+    }
+
+    if info.title.is_none() && info.artist.is_none() && info.album.is_none() {
+        return None;
     }
+    Some(info)
 }
 
 fn infer_source_platform(
@@ -129,6 +734,7 @@ fn infer_source_platform(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use flom_core::validate_url;
 
     #[test]
     fn normalize_target_maps_common_inputs() {
@@ -271,6 +877,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn known_targets_normalize_to_themselves() {
+        for target in MusicConverter::known_targets() {
+            assert_eq!(
+                MusicConverter::normalize_target(&target.key).as_deref(),
+                Some(target.key.as_str())
+            );
+            assert_ne!(
+                target.label, target.key,
+                "{} has no display name",
+                target.key
+            );
+        }
+    }
+
+    #[test]
+    fn finds_the_itunes_album_id() {
+        let mut response = OdesliResponse {
+            entity_unique_id: "SPOTIFY_ALBUM::abc".to_string(),
+            page_url: "https://album.link/s/abc".to_string(),
+            links_by_platform: HashMap::new(),
+            entities_by_unique_id: HashMap::new(),
+        };
+        assert_eq!(itunes_album_id(&response), None);
+        response.links_by_platform.insert(
+            "appleMusic".to_string(),
+            crate::api::odesli::OdesliLink {
+                entity_unique_id: "ITUNES_ALBUM::1440".to_string(),
+                url: "https://geo.music.apple.com/us/album/_/1440".to_string(),
+            },
+        );
+        assert_eq!(itunes_album_id(&response), Some("1440"));
+    }
+
+    #[test]
+    fn finds_provider_entities() {
+        let mut response = OdesliResponse {
+            entity_unique_id: "SPOTIFY_SONG::abc".to_string(),
+            page_url: "https://song.link/s/abc".to_string(),
+            links_by_platform: HashMap::new(),
+            entities_by_unique_id: HashMap::new(),
+        };
+        response.links_by_platform.insert(
+            "deezer".to_string(),
+            crate::api::odesli::OdesliLink {
+                entity_unique_id: "DEEZER_SONG::3135556".to_string(),
+                url: "https://www.deezer.com/track/3135556".to_string(),
+            },
+        );
+        assert_eq!(
+            provider_entity(&response, "DEEZER"),
+            Some(("SONG", "3135556"))
+        );
+        assert_eq!(provider_entity(&response, "SPOTIFY"), Some(("SONG", "abc")));
+        assert_eq!(provider_entity(&response, "ITUNES"), None);
+    }
+
     #[test]
     fn test_entity_to_media_full() {
         // Test through convert_from_response
@@ -288,6 +951,11 @@ mod tests {
                 title: Some("Test Song".to_string()),
                 artist_name: Some("Test Artist".to_string()),
                 album_name: Some("Test Album".to_string()),
+                kind: Some("song".to_string()),
+                thumbnail_url: Some("https://i.scdn.co/image/abc".to_string()),
+                duration: Some(213_573),
+                isrc: Some("GBARL9300135".to_string()),
+                release_date: Some("1987-07-27".to_string()),
                 api_provider: Some("spotify".to_string()),
             },
         );
@@ -310,6 +978,13 @@ mod tests {
                 title: Some("Test Song".to_string()),
                 artist: Some("Test Artist".to_string()),
                 album: Some("Test Album".to_string()),
+                kind: Some("song".to_string()),
+                duration_ms: Some(213_573),
+                artwork_url: Some("https://i.scdn.co/image/abc".to_string()),
+                isrc: Some("GBARL9300135".to_string()),
+                release_date: Some("1987-07-27".to_string()),
+                release_year: Some(1987),
+                label: None,
             })
         );
     }
@@ -330,8 +1005,8 @@ mod tests {
                 id: None,
                 title: Some("Test Song".to_string()),
                 artist_name: Some("Test Artist".to_string()),
-                album_name: None,
                 api_provider: Some("spotify".to_string()),
+                ..Default::default()
             },
         );
 
@@ -352,11 +1027,142 @@ mod tests {
             Some(MediaInfo {
                 title: Some("Test Song".to_string()),
                 artist: Some("Test Artist".to_string()),
-                album: None,
+                ..Default::default()
             })
         );
     }
 
+    #[test]
+    fn test_source_info_falls_back_to_target_entity() {
+        let mut response = OdesliResponse {
+            entity_unique_id: "source-id".to_string(),
+            page_url: "https://example.com".to_string(),
+            links_by_platform: HashMap::new(),
+            entities_by_unique_id: HashMap::new(),
+        };
+        response.entities_by_unique_id.insert(
+            "source-id".to_string(),
+            crate::api::odesli::OdesliEntity {
+                id: None,
+                title: None,
+                artist_name: None,
+                api_provider: Some("youtube".to_string()),
+                ..Default::default()
+            },
+        );
+        response.entities_by_unique_id.insert(
+            "target-id".to_string(),
+            crate::api::odesli::OdesliEntity {
+                id: None,
+                title: Some("Test Song".to_string()),
+                artist_name: Some("Test Artist".to_string()),
+                api_provider: Some("spotify".to_string()),
+                ..Default::default()
+            },
+        );
+        response.links_by_platform.insert(
+            "spotify".to_string(),
+            crate::api::odesli::OdesliLink {
+                entity_unique_id: "target-id".to_string(),
+                url: "https://spotify.com".to_string(),
+            },
+        );
+
+        let result =
+            MusicConverter::convert_from_response(&response, "https://youtube.com", "spotify")
+                .unwrap();
+        assert_eq!(
+            result.source_info,
+            Some(MediaInfo {
+                title: Some("Test Song".to_string()),
+                artist: Some("Test Artist".to_string()),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_convert_includes_platform_ids() {
+        let mut response = OdesliResponse {
+            entity_unique_id: "SPOTIFY_SONG::abc".to_string(),
+            page_url: "https://example.com".to_string(),
+            links_by_platform: HashMap::new(),
+            entities_by_unique_id: HashMap::new(),
+        };
+        response.entities_by_unique_id.insert(
+            "SPOTIFY_SONG::abc".to_string(),
+            crate::api::odesli::OdesliEntity {
+                id: Some("abc".to_string()),
+                title: None,
+                artist_name: None,
+                api_provider: Some("spotify".to_string()),
+                ..Default::default()
+            },
+        );
+        response.links_by_platform.insert(
+            "youtube".to_string(),
+            crate::api::odesli::OdesliLink {
+                entity_unique_id: "YOUTUBE_VIDEO::xyz".to_string(),
+                url: "https://www.youtube.com/watch?v=xyz".to_string(),
+            },
+        );
+
+        let result = MusicConverter::convert_from_response(
+            &response,
+            "https://open.spotify.com/track/abc",
+            "youtube",
+        )
+        .unwrap();
+        assert_eq!(result.source_id, Some("abc".to_string()));
+        assert_eq!(result.target_id, Some("xyz".to_string()));
+        assert_eq!(result.media_type, Some(MediaType::Song));
+    }
+
+    #[tokio::test]
+    async fn offline_lookups_fail_without_a_stored_answer() {
+        let dir = std::env::temp_dir().join("flom-test-offline");
+        let _ = std::fs::remove_dir_all(&dir);
+        let converter = MusicConverter::new(None, &FlomConfigData::default())
+            .with_response_cache(&dir, Duration::from_secs(60))
+            .with_cache_mode(CacheMode::Offline);
+        let err = converter
+            .fetch_links("https://open.spotify.com/track/abc")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, FlomError::UnsupportedInput(_)));
+        assert!(err.to_string().contains("not cached"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn cached_answers_only_serve_the_same_options() {
+        let dir = std::env::temp_dir().join("flom-test-cache-options");
+        let _ = std::fs::remove_dir_all(&dir);
+        let url = "https://open.spotify.com/album/abc";
+        let converter = |song_if_single| {
+            MusicConverter::new(None, &FlomConfigData::default())
+                .with_options(OdesliOptions {
+                    song_if_single,
+                    locale: None,
+                })
+                .with_response_cache(&dir, Duration::from_secs(60))
+                .with_cache_mode(CacheMode::Offline)
+        };
+        let album = OdesliResponse {
+            entity_unique_id: "SPOTIFY_ALBUM::abc".to_string(),
+            page_url: "https://album.link/s/abc".to_string(),
+            links_by_platform: HashMap::new(),
+            entities_by_unique_id: HashMap::new(),
+        };
+        converter(false).store_cached(url, &album);
+
+        let response = converter(false).fetch_links(url).await.unwrap();
+        assert_eq!(response.entity_unique_id, "SPOTIFY_ALBUM::abc");
+        let err = converter(true).fetch_links(url).await.unwrap_err();
+        assert!(err.to_string().contains("not cached"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_validate_url_https() {
         assert!(validate_url("https://example.com").is_ok());
@@ -388,4 +1194,50 @@ mod tests {
             _ => panic!("Expected InvalidInput error"),
         }
     }
+
+    #[test]
+    fn best_match_picks_the_closest_acceptable_hit() {
+        let hit = |title: &str, artist: &str, ms: u64, url: &str| ItunesTrack {
+            track_name: Some(title.to_string()),
+            artist_name: Some(artist.to_string()),
+            track_view_url: Some(url.to_string()),
+            track_time_millis: Some(ms),
+            release_date: None,
+        };
+        let candidates = [
+            hit("Mr. Brightside (Live)", "The Killers", 260_000, "https://a"),
+            hit("Mr. Brightside", "The Killers", 222_000, "https://b"),
+            hit("Brightside", "Someone Else", 222_000, "https://c"),
+        ];
+        let thresholds = MatchThresholds::default();
+        let track = TrackRef {
+            title: "Mr Brightside",
+            artist: "Killers",
+            duration: Some(Duration::from_secs(222)),
+        };
+        assert_eq!(
+            best_match(&track, &candidates, &thresholds),
+            Some("https://b")
+        );
+        let unknown = TrackRef {
+            title: "Nothing Alike",
+            artist: "Nobody",
+            duration: None,
+        };
+        assert_eq!(best_match(&unknown, &candidates, &thresholds), None);
+    }
+
+    #[test]
+    fn splits_artist_and_title_queries() {
+        assert_eq!(
+            split_query("Daft Punk - One More Time"),
+            Some(("Daft Punk", "One More Time"))
+        );
+        assert_eq!(
+            split_query("Jay-Z – 99 Problems - Remastered"),
+            Some(("Jay-Z", "99 Problems - Remastered"))
+        );
+        assert_eq!(split_query("One More Time"), None);
+        assert_eq!(split_query(" - One More Time"), None);
+    }
 }
@@ -0,0 +1,63 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use flom_core::validate_music_url;
+use regex::Regex;
+
+static URL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"https?://[^\s<>"'`\]]+"#).expect("valid url regex"));
+
+/// Finds links to supported music services in free text (plain lists, Markdown,
+/// HTML), in order of first appearance and without duplicates.
+pub fn extract_music_urls(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    URL_RE
+        .find_iter(text)
+        .map(|m| trim_trailing_punctuation(m.as_str()))
+        .filter(|url| validate_music_url(url).is_ok())
+        .filter(|url| seen.insert(url.to_string()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Drops sentence punctuation and unbalanced closing parentheses that the greedy
+/// match picked up, e.g. from `(see https://x/y).` or `[t](https://x/y)`.
+fn trim_trailing_punctuation(url: &str) -> &str {
+    let mut url = url.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+    while url.ends_with(')') && url.matches('(').count() < url.matches(')').count() {
+        url = url[..url.len() - 1].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_from_markdown_and_prose() {
+        let text = "\
+# Weekly picks
+- [Song](https://open.spotify.com/track/abc)
+- Also (https://music.apple.com/us/album/x/1?i=2), and https://example.com/blog.
+- Repeat: https://open.spotify.com/track/abc.
+<a href=\"https://youtu.be/xyz\">video</a>
+";
+        assert_eq!(
+            extract_music_urls(text),
+            vec![
+                "https://open.spotify.com/track/abc",
+                "https://music.apple.com/us/album/x/1?i=2",
+                "https://youtu.be/xyz",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_balanced_parentheses() {
+        assert_eq!(
+            extract_music_urls("https://music.apple.com/us/album/song-(live)/1"),
+            vec!["https://music.apple.com/us/album/song-(live)/1"]
+        );
+    }
+}
@@ -0,0 +1,88 @@
+//! Explanations for failures that are common but puzzling, shown below the
+//! error message.
+
+use flom_core::FlomError;
+
+/// A known failure: the hint applies when the input link contains one of
+/// `url` and the error message one of `error`. An empty list matches anything.
+struct Rule {
+    url: &'static [&'static str],
+    error: &'static [&'static str],
+    hint: &'static str,
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        url: &[],
+        error: &["target platform not available"],
+        hint: "the track may be unreleased or region-locked there; try another \
+               `default.user_country` (FLOM_USER_COUNTRY), or `--to all` to see where it is",
+    },
+    Rule {
+        url: &["youtube.com/watch", "youtu.be/", "youtube.com/shorts/"],
+        error: &["could not resolve", "target platform not available"],
+        hint: "user-uploaded YouTube videos (covers, live recordings, fan uploads) are not \
+               in music catalogs; only official uploads and YouTube Music tracks convert",
+    },
+    Rule {
+        url: &["open.spotify.com/show/", "podcasts.apple.com/"],
+        error: &["could not resolve", "not a podcast episode"],
+        hint: "podcasts convert one episode at a time with `flom podcast <episode link>`",
+    },
+    Rule {
+        url: &["playlist"],
+        error: &["status=401", "status=403", "status=404"],
+        hint: "private playlists can't be read; make the playlist public, or for Spotify \
+               set `spotify.refresh_token` so your own playlists are readable",
+    },
+];
+
+/// A hint for why converting `url` failed with `err`, if the failure is a
+/// known one.
+pub fn hint_for(url: &str, err: &FlomError) -> Option<&'static str> {
+    let message = err.to_string();
+    let any = |patterns: &[&str], text: &str| {
+        patterns.is_empty() || patterns.iter().any(|pattern| text.contains(pattern))
+    };
+    // Specific rules come after general ones, so the last match wins.
+    RULES
+        .iter()
+        .rev()
+        .find(|rule| any(rule.url, url) && any(rule.error, &message))
+        .map(|rule| rule.hint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_failures_get_hints() {
+        let unresolved = FlomError::UnsupportedInput(
+            "odesli could not resolve this link; check that it opens in a browser".to_string(),
+        );
+        let missing = FlomError::UnsupportedInput("target platform not available: tidal".into());
+
+        let youtube = hint_for("https://www.youtube.com/watch?v=abc", &unresolved).unwrap();
+        assert!(youtube.contains("user-uploaded"));
+        let region = hint_for("https://open.spotify.com/track/abc", &missing).unwrap();
+        assert!(region.contains("region-locked"));
+        let podcast = hint_for("https://open.spotify.com/show/abc", &unresolved).unwrap();
+        assert!(podcast.contains("flom podcast"));
+        let private = FlomError::Api("playlist page error: status=404 Not Found".into());
+        let playlist = hint_for("https://music.apple.com/us/playlist/x/pl.abc", &private);
+        assert!(playlist.unwrap().contains("private"));
+
+        assert_eq!(
+            hint_for("https://open.spotify.com/track/abc", &unresolved),
+            None
+        );
+        assert_eq!(
+            hint_for(
+                "https://open.spotify.com/track/abc",
+                &FlomError::Network("timeout".into())
+            ),
+            None
+        );
+    }
+}
@@ -1,5 +1,26 @@
 pub mod api;
+pub mod artist;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+mod coalesce;
 pub mod converter;
+pub mod extract;
+pub mod hints;
+mod lru;
+pub mod matching;
+pub mod memo;
+pub mod metadata;
+pub mod middleware;
 pub mod parsers;
+pub mod playlist;
+pub mod podcast;
+pub mod urls;
 
-pub use converter::{MusicConverter, TargetOption};
+#[cfg(feature = "blocking")]
+pub use blocking::BlockingConverter;
+pub use converter::{CacheMode, MusicConverter, TargetOption};
+pub use middleware::{LinkRequest, Middleware};
+pub use urls::{Platform, build_radio_url, build_url, clean_share_url};
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
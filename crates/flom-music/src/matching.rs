@@ -0,0 +1,174 @@
+//! Fuzzy scoring of whether two track descriptions refer to the same recording.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use flom_config::{FlomConfigData, resolve_match_duration_tolerance, resolve_match_min_score};
+
+use crate::metadata::{artist_key, title_key};
+
+const TITLE_WEIGHT: f64 = 0.6;
+const ARTIST_WEIGHT: f64 = 0.4;
+
+/// What is known about a track on one platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackRef<'a> {
+    pub title: &'a str,
+    pub artist: &'a str,
+    pub duration: Option<Duration>,
+}
+
+/// When two tracks are close enough to count as the same.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchThresholds {
+    /// Minimum [`score`], from 0.0 to 1.0.
+    pub min_score: f64,
+    /// Largest duration difference tolerated when both durations are known.
+    pub duration_tolerance: Duration,
+}
+
+impl Default for MatchThresholds {
+    fn default() -> Self {
+        Self {
+            min_score: 0.85,
+            duration_tolerance: Duration::from_secs(3),
+        }
+    }
+}
+
+impl MatchThresholds {
+    /// Defaults overridden by the `[matching]` config section and its env vars.
+    pub fn from_config(config: &FlomConfigData) -> Self {
+        let defaults = Self::default();
+        Self {
+            min_score: resolve_match_min_score(config)
+                .map_or(defaults.min_score, |score| score.clamp(0.0, 1.0)),
+            duration_tolerance: resolve_match_duration_tolerance(config)
+                .map_or(defaults.duration_tolerance, Duration::from_secs),
+        }
+    }
+
+    pub fn is_match(&self, a: &TrackRef, b: &TrackRef) -> bool {
+        score(a, b, self.duration_tolerance) >= self.min_score
+    }
+}
+
+/// Similarity of two tracks from 0.0 to 1.0: a weighted token-set ratio of
+/// their normalized titles and main artists. Durations further apart than
+/// `duration_tolerance` rule a match out and score 0.0.
+pub fn score(a: &TrackRef, b: &TrackRef, duration_tolerance: Duration) -> f64 {
+    if let (Some(left), Some(right)) = (a.duration, b.duration)
+        && left.abs_diff(right) > duration_tolerance
+    {
+        return 0.0;
+    }
+    let title = token_set_ratio(&title_key(a.title), &title_key(b.title));
+    let artist = token_set_ratio(&artist_key(a.artist), &artist_key(b.artist));
+    TITLE_WEIGHT * title + ARTIST_WEIGHT * artist
+}
+
+/// Token-set ratio from 0.0 to 1.0: word order and duplicates are ignored, and
+/// a string whose words are all contained in the other scores 1.0.
+pub fn token_set_ratio(a: &str, b: &str) -> f64 {
+    let left: BTreeSet<&str> = a.split_whitespace().collect();
+    let right: BTreeSet<&str> = b.split_whitespace().collect();
+    if left.is_empty() || right.is_empty() {
+        return 0.0;
+    }
+    let join = |words: Vec<&str>| words.join(" ");
+    let common = join(left.intersection(&right).copied().collect());
+    let with_rest = |rest: Vec<&str>| {
+        let rest = join(rest);
+        match (common.is_empty(), rest.is_empty()) {
+            (true, _) => rest,
+            (false, true) => common.clone(),
+            (false, false) => format!("{common} {rest}"),
+        }
+    };
+    let left_all = with_rest(left.difference(&right).copied().collect());
+    let right_all = with_rest(right.difference(&left).copied().collect());
+    [
+        ratio(&common, &left_all),
+        ratio(&common, &right_all),
+        ratio(&left_all, &right_all),
+    ]
+    .into_iter()
+    .fold(0.0, f64::max)
+}
+
+/// Indel similarity: twice the longest common subsequence over the total length.
+fn ratio(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let mut previous = vec![0usize; b.len() + 1];
+    for &left in &a {
+        let mut current = vec![0usize; b.len() + 1];
+        for (j, &right) in b.iter().enumerate() {
+            current[j + 1] = if left == right {
+                previous[j] + 1
+            } else {
+                current[j].max(previous[j + 1])
+            };
+        }
+        previous = current;
+    }
+    2.0 * previous[b.len()] as f64 / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track<'a>(title: &'a str, artist: &'a str, secs: Option<u64>) -> TrackRef<'a> {
+        TrackRef {
+            title,
+            artist,
+            duration: secs.map(Duration::from_secs),
+        }
+    }
+
+    #[test]
+    fn token_set_ratio_ignores_order_and_extra_words() {
+        assert_eq!(token_set_ratio("mr brightside", "brightside mr"), 1.0);
+        assert_eq!(token_set_ratio("mr brightside", "mr brightside 2004"), 1.0);
+        assert_eq!(token_set_ratio("", "anything"), 0.0);
+        let close = token_set_ratio("colour", "color");
+        assert!(close > 0.9 && close < 1.0, "{close}");
+        assert!(token_set_ratio("yesterday", "tomorrow") < 0.5);
+    }
+
+    #[test]
+    fn matches_across_platform_spellings() {
+        let thresholds = MatchThresholds::default();
+        assert!(thresholds.is_match(
+            &track("Mr. Brightside", "The Killers", Some(222)),
+            &track("Mr Brightside - 2004 Remaster", "Killers, The", Some(223)),
+        ));
+        assert!(thresholds.is_match(
+            &track("Hello (feat. Ann)", "Bob & Ann", None),
+            &track("Hello", "Bob", Some(200)),
+        ));
+        assert!(!thresholds.is_match(&track("Hello", "Bob", None), &track("Hello", "Adele", None)));
+    }
+
+    #[test]
+    fn durations_outside_tolerance_never_match() {
+        let a = track("Song", "Ann", Some(200));
+        let b = track("Song", "Ann", Some(260));
+        assert_eq!(score(&a, &b, Duration::from_secs(3)), 0.0);
+        assert_eq!(score(&a, &b, Duration::from_secs(60)), 1.0);
+    }
+
+    #[test]
+    fn thresholds_come_from_config() {
+        let mut config = FlomConfigData::default();
+        config.matching.min_score = Some(1.5);
+        config.matching.duration_tolerance_secs = Some(10);
+        let thresholds = MatchThresholds::from_config(&config);
+        assert_eq!(thresholds.min_score, 1.0);
+        assert_eq!(thresholds.duration_tolerance, Duration::from_secs(10));
+    }
+}
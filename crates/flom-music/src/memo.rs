@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use flom_core::{FlomError, FlomResult};
+use serde::{Deserialize, Serialize};
+
+use crate::api::odesli::{EntityQuery, EntityType, OdesliResponse};
+use crate::parsers::apple_music::parse_apple_music_track_id;
+use crate::parsers::spotify::parse_spotify_track_id;
+use crate::urls::clean_share_url;
+
+/// Index of fetched Odesli responses keyed by every link and entity they contain.
+///
+/// Once a track has been resolved from one platform, links to the same track on any
+/// other platform can be answered from the stored response without another request.
+#[derive(Debug, Default)]
+pub struct EntityIndex {
+    responses: Vec<OdesliResponse>,
+    by_url: HashMap<String, (usize, String)>,
+    by_entity: HashMap<String, usize>,
+    by_isrc: HashMap<String, usize>,
+}
+
+/// One line of the entity database: a resolved track and, once known, its ISRC.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoredEntity {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isrc: Option<String>,
+    /// [`OdesliOptions::cache_tag`](crate::api::odesli::OdesliOptions::cache_tag)
+    /// of the lookup, since other options get other answers.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub options: String,
+    pub response: OdesliResponse,
+}
+
+impl EntityIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+
+    /// Loads the entries of the JSON-lines database written by
+    /// [`append_entity`] that were looked up with the `options` tag. A missing
+    /// file gives an empty index; damaged lines are skipped.
+    pub fn load(path: &Path, options: &str) -> Self {
+        let mut index = Self::new();
+        let Ok(content) = fs::read_to_string(path) else {
+            return index;
+        };
+        for line in content.lines() {
+            if let Ok(stored) = serde_json::from_str::<StoredEntity>(line)
+                && stored.options == options
+            {
+                index.insert_stored(stored);
+            }
+        }
+        index
+    }
+
+    pub fn insert_stored(&mut self, stored: StoredEntity) {
+        let index = self.responses.len();
+        if let Some(isrc) = stored.isrc {
+            self.by_isrc.insert(isrc_key(&isrc), index);
+        }
+        self.insert(stored.response);
+    }
+
+    pub fn insert(&mut self, response: OdesliResponse) {
+        let index = self.responses.len();
+        for link in response.links_by_platform.values() {
+            self.by_url
+                .insert(url_key(&link.url), (index, link.entity_unique_id.clone()));
+        }
+        for entity_id in response.entities_by_unique_id.keys() {
+            self.by_entity.insert(entity_id.clone(), index);
+        }
+        self.by_entity
+            .insert(response.entity_unique_id.clone(), index);
+        self.responses.push(response);
+    }
+
+    /// Returns a stored response for `url`, re-rooted so the entity behind `url`
+    /// is reported as the source.
+    pub fn lookup(&self, url: &str) -> Option<OdesliResponse> {
+        if let Some((index, entity_id)) = self.by_url.get(&url_key(url)) {
+            return Some(self.rooted(*index, entity_id));
+        }
+        entity_keys_for_url(url).into_iter().find_map(|key| {
+            self.by_entity
+                .get(&key)
+                .map(|index| self.rooted(*index, &key))
+        })
+    }
+
+    /// Returns the stored response for a track identified by ISRC.
+    pub fn lookup_isrc(&self, isrc: &str) -> Option<OdesliResponse> {
+        self.by_isrc
+            .get(&isrc_key(isrc))
+            .map(|index| self.responses[*index].clone())
+    }
+
+    /// Returns a stored response for a platform-native ID lookup, re-rooted on
+    /// the entity the ID names.
+    pub fn lookup_id(&self, query: &EntityQuery) -> Option<OdesliResponse> {
+        let suffix = format!("::{}", query.id);
+        self.responses
+            .iter()
+            .enumerate()
+            .find_map(|(index, response)| {
+                let entity_id = &response
+                    .links_by_platform
+                    .get(&query.platform)?
+                    .entity_unique_id;
+                let is_album = entity_id.contains("_ALBUM::");
+                (entity_id.ends_with(&suffix)
+                    && is_album == (query.entity_type == EntityType::Album))
+                    .then(|| self.rooted(index, entity_id))
+            })
+    }
+
+    fn rooted(&self, index: usize, entity_id: &str) -> OdesliResponse {
+        let mut response = self.responses[index].clone();
+        response.entity_unique_id = entity_id.to_string();
+        response
+    }
+}
+
+/// Appends `entity` to the database at `path`, creating it if needed.
+pub fn append_entity(path: &Path, entity: &StoredEntity) -> FlomResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| FlomError::Config(format!("failed to create entity db dir: {err}")))?;
+    }
+    let mut line = serde_json::to_string(entity)
+        .map_err(|err| FlomError::Parse(format!("failed to serialize entity: {err}")))?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|err| FlomError::Config(format!("failed to write entity db: {err}")))
+}
+
+fn isrc_key(isrc: &str) -> String {
+    isrc.trim().to_uppercase()
+}
+
+fn url_key(url: &str) -> String {
+    clean_share_url(url).unwrap_or_else(|_| url.to_string())
+}
+
+/// Odesli entity IDs that `url` may correspond to, derived from the platform-native ID.
+pub fn entity_keys_for_url(url: &str) -> Vec<String> {
+    let mut keys = Vec::new();
+    if let Some(id) = parse_spotify_track_id(url) {
+        keys.push(format!("SPOTIFY_SONG::{id}"));
+    }
+    if let Some(id) = parse_apple_music_track_id(url) {
+        keys.push(format!("ITUNES_SONG::{id}"));
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::odesli::{OdesliEntity, OdesliLink};
+
+    fn entity(id: &str, title: &str, provider: &str) -> OdesliEntity {
+        OdesliEntity {
+            id: Some(id.to_string()),
+            title: Some(title.to_string()),
+            artist_name: None,
+            api_provider: Some(provider.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn sample_response() -> OdesliResponse {
+        let mut response = OdesliResponse {
+            entity_unique_id: "SPOTIFY_SONG::abc".to_string(),
+            page_url: "https://song.link/s/abc".to_string(),
+            links_by_platform: HashMap::new(),
+            entities_by_unique_id: HashMap::new(),
+        };
+        response.links_by_platform.insert(
+            "spotify".to_string(),
+            OdesliLink {
+                entity_unique_id: "SPOTIFY_SONG::abc".to_string(),
+                url: "https://open.spotify.com/track/abc".to_string(),
+            },
+        );
+        response.links_by_platform.insert(
+            "appleMusic".to_string(),
+            OdesliLink {
+                entity_unique_id: "ITUNES_SONG::123".to_string(),
+                url: "https://geo.music.apple.com/us/album/_/1?i=123".to_string(),
+            },
+        );
+        response.entities_by_unique_id.insert(
+            "SPOTIFY_SONG::abc".to_string(),
+            entity("abc", "Song", "spotify"),
+        );
+        response.entities_by_unique_id.insert(
+            "ITUNES_SONG::123".to_string(),
+            entity("123", "Song", "itunes"),
+        );
+        response
+    }
+
+    #[test]
+    fn lookup_by_exact_link_url() {
+        let mut index = EntityIndex::new();
+        index.insert(sample_response());
+
+        let response = index
+            .lookup("https://geo.music.apple.com/us/album/_/1?i=123")
+            .unwrap();
+        assert_eq!(response.entity_unique_id, "ITUNES_SONG::123");
+        let response = index
+            .lookup("https://GEO.Music.Apple.com/us/album/_/1?i=123#share")
+            .unwrap();
+        assert_eq!(response.entity_unique_id, "ITUNES_SONG::123");
+    }
+
+    #[test]
+    fn lookup_by_entity_from_other_share_url() {
+        let mut index = EntityIndex::new();
+        index.insert(sample_response());
+
+        let response = index
+            .lookup("https://music.apple.com/jp/album/song/999?i=123")
+            .unwrap();
+        assert_eq!(response.entity_unique_id, "ITUNES_SONG::123");
+
+        let response = index
+            .lookup("https://open.spotify.com/intl-ja/track/abc")
+            .unwrap();
+        assert_eq!(response.entity_unique_id, "SPOTIFY_SONG::abc");
+    }
+
+    #[test]
+    fn lookup_by_platform_id() {
+        let mut index = EntityIndex::new();
+        index.insert(sample_response());
+
+        let query = EntityQuery::new("apple-music", EntityType::Song, "123").unwrap();
+        let response = index.lookup_id(&query).unwrap();
+        assert_eq!(response.entity_unique_id, "ITUNES_SONG::123");
+
+        let album = EntityQuery::new("spotify", EntityType::Album, "abc").unwrap();
+        assert!(index.lookup_id(&album).is_none());
+        let other = EntityQuery::new("spotify", EntityType::Song, "ab").unwrap();
+        assert!(index.lookup_id(&other).is_none());
+    }
+
+    #[test]
+    fn lookup_miss() {
+        let mut index = EntityIndex::new();
+        index.insert(sample_response());
+        assert!(
+            index
+                .lookup("https://open.spotify.com/track/other")
+                .is_none()
+        );
+        assert_eq!(index.len(), 1);
+    }
+
+    #[test]
+    fn database_roundtrip_resolves_links_and_isrc() {
+        let path = std::env::temp_dir().join("flom-test-entity-db/entities.jsonl");
+        let _ = fs::remove_file(&path);
+        assert!(EntityIndex::load(&path, "").is_empty());
+
+        append_entity(
+            &path,
+            &StoredEntity {
+                isrc: None,
+                options: String::new(),
+                response: sample_response(),
+            },
+        )
+        .unwrap();
+        append_entity(
+            &path,
+            &StoredEntity {
+                isrc: Some("usabc1234567".to_string()),
+                options: String::new(),
+                response: sample_response(),
+            },
+        )
+        .unwrap();
+        append_entity(
+            &path,
+            &StoredEntity {
+                isrc: Some("usother00000".to_string()),
+                options: "songIfSingle".to_string(),
+                response: sample_response(),
+            },
+        )
+        .unwrap();
+        fs::write(&path, fs::read_to_string(&path).unwrap() + "not json\n").unwrap();
+
+        let index = EntityIndex::load(&path, "");
+        assert_eq!(index.len(), 2);
+        let response = index.lookup("https://open.spotify.com/track/abc").unwrap();
+        assert_eq!(response.page_url, "https://song.link/s/abc");
+        let response = index.lookup_isrc(" USABC1234567 ").unwrap();
+        assert_eq!(response.entity_unique_id, "SPOTIFY_SONG::abc");
+        assert!(index.lookup_isrc("USXYZ0000000").is_none());
+        assert!(index.lookup_isrc("USOTHER00000").is_none());
+        assert_eq!(EntityIndex::load(&path, "songIfSingle").len(), 1);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
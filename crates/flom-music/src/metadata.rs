@@ -0,0 +1,205 @@
+//! Normalization of track titles and artist names, so the same recording
+//! compares equal across platforms, re-releases and featuring credits.
+
+use any_ascii::any_ascii;
+
+/// Words marking a release variant rather than a different recording.
+const VERSION_MARKERS: [&str; 12] = [
+    "remaster",
+    "version",
+    "edit",
+    "mix",
+    "live",
+    "mono",
+    "stereo",
+    "deluxe",
+    "acoustic",
+    "demo",
+    "explicit",
+    "bonus track",
+];
+
+const FEATURING_MARKERS: [&str; 4] = ["feat.", "feat ", "ft.", "featuring "];
+
+/// Separators between credited artists; the first artist is the main one.
+const ARTIST_SEPARATORS: [&str; 9] = [
+    ",",
+    ";",
+    " & ",
+    " feat.",
+    " feat ",
+    " ft.",
+    " featuring ",
+    " x ",
+    " and ",
+];
+
+/// Drops bracketed version and featuring tags such as "(2011 Remaster)",
+/// "[Live]" or "(feat. X)", a trailing "- Radio Edit" style suffix, and an
+/// unbracketed "feat. X". Casing is kept, so the result is fit for display.
+pub fn clean_title(title: &str) -> String {
+    let mut out = strip_brackets(title);
+    while let Some(index) = out.rfind(" - ") {
+        if !is_version_tag(&out[index + 3..]) {
+            break;
+        }
+        out.truncate(index);
+    }
+    let lower = out.to_ascii_lowercase();
+    if let Some(index) = FEATURING_MARKERS
+        .iter()
+        .filter_map(|marker| lower.find(&format!(" {marker}")))
+        .min()
+    {
+        out.truncate(index);
+    }
+    collapse_spaces(&out)
+}
+
+/// The first credited artist: "Ann, Bob & Cid" and "Ann feat. Bob" give "Ann".
+pub fn main_artist(artist: &str) -> &str {
+    // ASCII lowercasing keeps byte offsets valid for the original string.
+    let lower = artist.to_ascii_lowercase();
+    let end = ARTIST_SEPARATORS
+        .iter()
+        .filter_map(|separator| lower.find(separator))
+        .min()
+        .unwrap_or(artist.len());
+    artist[..end].trim()
+}
+
+/// Lowercases with the rules of `locale` where they differ from the default,
+/// e.g. Turkish and Azerbaijani map `I` to dotless `ı` and `İ` to `i`.
+pub fn lowercase(value: &str, locale: Option<&str>) -> String {
+    let language = locale
+        .and_then(|locale| locale.split(['-', '_']).next())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if matches!(language.as_str(), "tr" | "az") {
+        return value
+            .chars()
+            .map(|c| match c {
+                'I' => 'ı',
+                'İ' => 'i',
+                c => c,
+            })
+            .collect::<String>()
+            .to_lowercase();
+    }
+    value.to_lowercase()
+}
+
+/// Comparison key for a title: cleaned, folded to lowercase ASCII, and with
+/// punctuation collapsed, so "Café - 2011 Remaster" and "CAFE" match.
+pub fn title_key(title: &str) -> String {
+    match_key(&clean_title(title))
+}
+
+/// Comparison key for the main artist of an artist credit.
+pub fn artist_key(artist: &str) -> String {
+    match_key(main_artist(artist))
+}
+
+fn match_key(value: &str) -> String {
+    any_ascii(value)
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_version_tag(value: &str) -> bool {
+    let value = value.to_lowercase();
+    VERSION_MARKERS.iter().any(|marker| value.contains(marker))
+}
+
+fn is_featuring(value: &str) -> bool {
+    let value = value.trim_start().to_lowercase();
+    FEATURING_MARKERS
+        .iter()
+        .any(|marker| value.starts_with(marker))
+}
+
+/// Removes `(...)` and `[...]` groups holding a version or featuring tag.
+fn strip_brackets(title: &str) -> String {
+    let mut out = String::with_capacity(title.len());
+    let mut rest = title;
+    while let Some(open) = rest.find(['(', '[']) {
+        let close = if rest[open..].starts_with('(') {
+            ')'
+        } else {
+            ']'
+        };
+        let Some(length) = rest[open + 1..].find(close) else {
+            break;
+        };
+        let inner = &rest[open + 1..open + 1 + length];
+        let end = open + 1 + length + 1;
+        if is_version_tag(inner) || is_featuring(inner) {
+            out.push_str(&rest[..open]);
+        } else {
+            out.push_str(&rest[..end]);
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn collapse_spaces(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_title_drops_version_and_featuring_tags() {
+        let cases = [
+            ("Here Comes the Sun - 2019 Mix", "Here Comes the Sun"),
+            ("Hey Jude (Remastered 2015)", "Hey Jude"),
+            ("Song [Live at Wembley]", "Song"),
+            ("Song (feat. Ann) - Radio Edit", "Song"),
+            ("Song ft. Ann & Bob", "Song"),
+            ("Song [Deluxe Edition] (Mono)", "Song"),
+            ("Come Together - Remastered - Mono", "Come Together"),
+            ("Let It Be (Naked)", "Let It Be (Naked)"),
+            ("Anti-Hero", "Anti-Hero"),
+            ("Rock - Paper - Scissors", "Rock - Paper - Scissors"),
+            ("Unclosed (Remaster", "Unclosed (Remaster"),
+            ("  Spaced   Out  ", "Spaced Out"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(clean_title(input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn main_artist_takes_first_credit() {
+        assert_eq!(main_artist("Ann, Bob"), "Ann");
+        assert_eq!(main_artist("Ann & Bob"), "Ann");
+        assert_eq!(main_artist("Ann FEAT. Bob"), "Ann");
+        assert_eq!(main_artist("Ann x Bob"), "Ann");
+        assert_eq!(main_artist("Björk"), "Björk");
+        assert_eq!(main_artist("Sigur Rós and Friends"), "Sigur Rós");
+    }
+
+    #[test]
+    fn lowercase_follows_locale() {
+        assert_eq!(lowercase("ISTANBUL", None), "istanbul");
+        assert_eq!(lowercase("ISTANBUL", Some("tr-TR")), "ıstanbul");
+        assert_eq!(lowercase("İZMİR", Some("tr")), "izmir");
+        assert_eq!(lowercase("ISTANBUL", Some("en_US")), "istanbul");
+    }
+
+    #[test]
+    fn keys_match_across_spellings() {
+        assert_eq!(title_key("Café - 2011 Remaster"), title_key("CAFE"));
+        assert_eq!(title_key("Song (Live)"), "song");
+        assert_eq!(title_key("Don't Stop"), "don t stop");
+        assert_eq!(artist_key("Béa feat. Cid"), artist_key("Bea & Dan"));
+        assert_ne!(title_key("Let It Be (Naked)"), title_key("Let It Be"));
+    }
+}
@@ -0,0 +1,195 @@
+use std::fmt;
+use std::sync::Arc;
+
+use flom_core::{ConversionResult, FlomResult};
+
+use crate::api::odesli::{EntityQuery, OdesliResponse};
+
+/// What `MusicConverter` is about to ask Odesli for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkRequest {
+    /// A normalized, validated music link.
+    Url(String),
+    /// A platform-native entity ID.
+    Id(EntityQuery),
+}
+
+/// Hooks into the conversion pipeline of a `MusicConverter`.
+///
+/// Every method has a no-op default, so an implementation only overrides the
+/// stages it cares about. Hooks run in the order the middleware was added, and
+/// an error from any hook aborts the conversion with that error.
+pub trait Middleware: Send + Sync {
+    /// Runs before a lookup. The request may be rewritten; returning a response
+    /// answers it without contacting Odesli (e.g. from a cache), and the
+    /// remaining `before_request` hooks and all `after_response` hooks are skipped.
+    fn before_request(&self, _request: &mut LinkRequest) -> FlomResult<Option<OdesliResponse>> {
+        Ok(None)
+    }
+
+    /// Runs after Odesli answered, before the response is remembered for the
+    /// session. Responses answered from memory or by `before_request` skip it.
+    fn after_response(
+        &self,
+        _request: &LinkRequest,
+        _response: &mut OdesliResponse,
+    ) -> FlomResult<()> {
+        Ok(())
+    }
+
+    /// Runs on each result built by `MusicConverter::convert`.
+    fn after_convert(&self, _result: &mut ConversionResult) -> FlomResult<()> {
+        Ok(())
+    }
+}
+
+/// The middleware registered on a converter; cheap to clone.
+#[derive(Clone, Default)]
+pub(crate) struct Chain(Vec<Arc<dyn Middleware>>);
+
+impl Chain {
+    pub(crate) fn push(&mut self, middleware: Arc<dyn Middleware>) {
+        self.0.push(middleware);
+    }
+
+    pub(crate) fn before_request(
+        &self,
+        request: &mut LinkRequest,
+    ) -> FlomResult<Option<OdesliResponse>> {
+        for middleware in &self.0 {
+            if let Some(response) = middleware.before_request(request)? {
+                return Ok(Some(response));
+            }
+        }
+        Ok(None)
+    }
+
+    pub(crate) fn after_response(
+        &self,
+        request: &LinkRequest,
+        response: &mut OdesliResponse,
+    ) -> FlomResult<()> {
+        self.0
+            .iter()
+            .try_for_each(|middleware| middleware.after_response(request, response))
+    }
+
+    pub(crate) fn after_convert(&self, result: &mut ConversionResult) -> FlomResult<()> {
+        self.0
+            .iter()
+            .try_for_each(|middleware| middleware.after_convert(result))
+    }
+}
+
+impl fmt::Debug for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Chain({} middleware)", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use flom_config::FlomConfigData;
+    use flom_core::FlomError;
+
+    use crate::MusicConverter;
+    use crate::api::odesli::OdesliLink;
+
+    fn canned_response() -> OdesliResponse {
+        let mut response = OdesliResponse {
+            entity_unique_id: "SPOTIFY_SONG::abc".to_string(),
+            page_url: "https://song.link/s/abc".to_string(),
+            links_by_platform: HashMap::new(),
+            entities_by_unique_id: HashMap::new(),
+        };
+        response.links_by_platform.insert(
+            "appleMusic".to_string(),
+            OdesliLink {
+                entity_unique_id: "ITUNES_SONG::123".to_string(),
+                url: "https://geo.music.apple.com/us/album/_/1?i=123".to_string(),
+            },
+        );
+        response
+    }
+
+    /// Answers every URL lookup itself and tags each result.
+    #[derive(Default)]
+    struct Canned {
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl Middleware for Canned {
+        fn before_request(&self, request: &mut LinkRequest) -> FlomResult<Option<OdesliResponse>> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            Ok(matches!(request, LinkRequest::Url(_)).then(canned_response))
+        }
+
+        fn after_convert(&self, result: &mut ConversionResult) -> FlomResult<()> {
+            result.warning = Some("from cache".to_string());
+            Ok(())
+        }
+    }
+
+    struct Deny;
+
+    impl Middleware for Deny {
+        fn before_request(&self, _request: &mut LinkRequest) -> FlomResult<Option<OdesliResponse>> {
+            Err(FlomError::InvalidInput("denied".to_string()))
+        }
+    }
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn before_request_can_answer_and_after_convert_can_edit() {
+        let canned = Canned::default();
+        let requests = canned.requests.clone();
+        let converter = MusicConverter::new(None, &FlomConfigData::default())
+            .with_middleware(canned)
+            .with_middleware(Deny);
+        let url = "https://open.spotify.com/track/abc";
+
+        let response = runtime().block_on(converter.fetch_links(url)).unwrap();
+        assert_eq!(response.page_url, "https://song.link/s/abc");
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let result = converter.convert(&response, url, "appleMusic").unwrap();
+        assert_eq!(result.warning.as_deref(), Some("from cache"));
+        assert_eq!(
+            MusicConverter::convert_from_response(&response, url, "appleMusic")
+                .unwrap()
+                .warning,
+            None
+        );
+    }
+
+    #[test]
+    fn strict_converters_reject_warnings() {
+        let converter = MusicConverter::new(None, &FlomConfigData::default())
+            .with_middleware(Canned::default())
+            .with_strict(true);
+        let url = "https://open.spotify.com/track/abc";
+        let response = runtime().block_on(converter.fetch_links(url)).unwrap();
+        let err = converter.convert(&response, url, "appleMusic").unwrap_err();
+        assert!(
+            matches!(err, FlomError::UnsupportedInput(message) if message == "strict mode: from cache")
+        );
+    }
+
+    #[test]
+    fn hook_errors_abort_the_lookup() {
+        let converter = MusicConverter::new(None, &FlomConfigData::default()).with_middleware(Deny);
+        let err = runtime()
+            .block_on(converter.fetch_links("https://open.spotify.com/track/abc"))
+            .unwrap_err();
+        assert!(matches!(err, FlomError::InvalidInput(message) if message == "denied"));
+    }
+}
@@ -1,6 +1,12 @@
+use std::sync::LazyLock;
+
 use regex::Regex;
 use url::Url;
 
+static TRACK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"music\.apple\.com/.*/(?:song|album)/.+/(\d+)").expect("valid apple music regex")
+});
+
 pub fn parse_apple_music_track_id(input: &str) -> Option<String> {
     let url = Url::parse(input).ok()?;
     if url.domain()? != "music.apple.com" {
@@ -11,8 +17,7 @@ pub fn parse_apple_music_track_id(input: &str) -> Option<String> {
         return Some(value.to_string());
     }
 
-    let regex = Regex::new(r"music\.apple\.com/.*/(?:song|album)/.+/(\d+)").ok()?;
-    let captures = regex.captures(input)?;
+    let captures = TRACK_RE.captures(input)?;
     captures.get(1).map(|m| m.as_str().to_string())
 }
 
@@ -1,2 +1,89 @@
+use std::sync::LazyLock;
+
+use regex::{RegexSet, RegexSetBuilder};
+
+use crate::urls::Platform;
+
 pub mod apple_music;
 pub mod spotify;
+pub mod youtube;
+
+/// Link prefixes of each platform, in [`Platform::ALL`] order.
+const LINK_PATTERNS: [&str; 7] = [
+    r"^(?:https?://)?(?:[^/?#]*\.)?(?:spotify\.com|spotify\.link)(?:[/?#:]|$)",
+    r"^(?:https?://)?(?:[^/?#]*\.)?(?:music|itunes)\.apple\.com(?:[/?#:]|$)",
+    r"^(?:https?://)?(?:(?:www|m)\.)?(?:youtube\.com|youtu\.be)(?:[/?#:]|$)",
+    r"^(?:https?://)?music\.youtube\.com(?:[/?#:]|$)",
+    r"^(?:https?://)?(?:[^/?#]*\.)?(?:deezer\.com|deezer\.page\.link)(?:[/?#:]|$)",
+    r"^(?:https?://)?(?:[^/?#]*\.)?tidal\.com(?:[/?#:]|$)",
+    r"^(?:https?://)?music\.amazon\.[a-z.]+(?:[/?#:]|$)",
+];
+
+static LINKS: LazyLock<RegexSet> = LazyLock::new(|| {
+    RegexSetBuilder::new(LINK_PATTERNS)
+        .case_insensitive(true)
+        .build()
+        .expect("valid link patterns")
+});
+
+/// The platform a link belongs to, sniffed in a single pass over the link.
+pub fn detect_platform(input: &str) -> Option<Platform> {
+    LINKS
+        .matches(input.trim())
+        .iter()
+        .next()
+        .map(|index| Platform::ALL[index])
+}
+
+/// Extracts the platform-native track or video ID from a supported URL.
+pub fn parse_platform_id(input: &str) -> Option<String> {
+    match detect_platform(input)? {
+        Platform::Spotify => spotify::parse_spotify_track_id(input),
+        Platform::AppleMusic => apple_music::parse_apple_music_track_id(input),
+        Platform::Youtube | Platform::YoutubeMusic => youtube::parse_youtube_video_id(input),
+        Platform::Deezer | Platform::Tidal | Platform::AmazonMusic => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_platforms_by_host() {
+        let cases = [
+            (
+                "https://open.spotify.com/track/abc",
+                Some(Platform::Spotify),
+            ),
+            ("https://spotify.link/xyz", Some(Platform::Spotify)),
+            (
+                "https://music.apple.com/us/album/x/1",
+                Some(Platform::AppleMusic),
+            ),
+            (
+                "https://www.youtube.com/watch?v=abc",
+                Some(Platform::Youtube),
+            ),
+            ("https://youtu.be/abc", Some(Platform::Youtube)),
+            (
+                "https://music.youtube.com/watch?v=abc",
+                Some(Platform::YoutubeMusic),
+            ),
+            (
+                "https://www.deezer.com/track/3135556",
+                Some(Platform::Deezer),
+            ),
+            ("https://listen.tidal.com/track/1", Some(Platform::Tidal)),
+            (
+                "https://music.amazon.co.jp/albums/B0",
+                Some(Platform::AmazonMusic),
+            ),
+            ("https://notspotify.com.evil.example/track/abc", None),
+            ("https://example.com/?u=open.spotify.com", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(detect_platform(url), expected, "{url}");
+        }
+    }
+}
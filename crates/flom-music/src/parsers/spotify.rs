@@ -1,8 +1,14 @@
+use std::sync::LazyLock;
+
 use regex::Regex;
 
+static TRACK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"open\.spotify\.com/(?:intl-[a-z]{2}/)?track/([A-Za-z0-9]+)")
+        .expect("valid spotify track regex")
+});
+
 pub fn parse_spotify_track_id(input: &str) -> Option<String> {
-    let regex = Regex::new(r"open\.spotify\.com/(?:intl-[a-z]{2}/)?track/([A-Za-z0-9]+)").ok()?;
-    let captures = regex.captures(input)?;
+    let captures = TRACK_RE.captures(input)?;
     captures.get(1).map(|m| m.as_str().to_string())
 }
 
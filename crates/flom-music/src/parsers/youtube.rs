@@ -0,0 +1,121 @@
+use url::Url;
+
+pub fn parse_youtube_video_id(input: &str) -> Option<String> {
+    let url = Url::parse(input).ok()?;
+    let domain = url.domain()?;
+    if domain == "youtu.be" {
+        return url
+            .path_segments()?
+            .next()
+            .filter(|id| !id.is_empty())
+            .map(|id| id.to_string());
+    }
+    if domain != "youtube.com" && !domain.ends_with(".youtube.com") {
+        return None;
+    }
+    if let Some((_, value)) = url.query_pairs().find(|(key, _)| key == "v") {
+        return Some(value.to_string());
+    }
+
+    let mut segments = url.path_segments()?;
+    match (segments.next(), segments.next()) {
+        (Some("shorts" | "embed"), Some(id)) if !id.is_empty() => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// YouTube pages that list many videos rather than showing one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YoutubeCollection {
+    Playlist,
+    Channel,
+}
+
+impl YoutubeCollection {
+    /// Why the link can't be converted and what to pass instead.
+    pub fn guidance(self) -> &'static str {
+        match self {
+            YoutubeCollection::Playlist => {
+                "youtube playlists can't be converted as one link; use `flom playlist` to convert each track, or pass the watch?v= link of one video"
+            }
+            YoutubeCollection::Channel => {
+                "youtube channels can't be converted, only single tracks; pass the watch?v= link of one of its videos"
+            }
+        }
+    }
+}
+
+/// Detects playlist and channel pages. A `watch?v=` link that merely carries a
+/// `list=` parameter still points at one video and is not a collection.
+pub fn parse_youtube_collection(input: &str) -> Option<YoutubeCollection> {
+    let url = Url::parse(input).ok()?;
+    let domain = url.domain()?;
+    if domain != "youtube.com" && !domain.ends_with(".youtube.com") {
+        return None;
+    }
+    if url.query_pairs().any(|(key, _)| key == "v") {
+        return None;
+    }
+    let mut segments = url.path_segments()?;
+    match segments.next()? {
+        "playlist" => Some(YoutubeCollection::Playlist),
+        "channel" | "c" | "user" => Some(YoutubeCollection::Channel),
+        handle if handle.starts_with('@') => Some(YoutubeCollection::Channel),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{YoutubeCollection, parse_youtube_collection, parse_youtube_video_id};
+
+    #[test]
+    fn parses_youtube_video_id_from_watch_url() {
+        let url = "https://www.youtube.com/watch?v=4NRXx6U8ABQ&feature=share";
+        assert_eq!(parse_youtube_video_id(url), Some("4NRXx6U8ABQ".to_string()));
+    }
+
+    #[test]
+    fn parses_youtube_music_and_short_links() {
+        assert_eq!(
+            parse_youtube_video_id("https://music.youtube.com/watch?v=4NRXx6U8ABQ"),
+            Some("4NRXx6U8ABQ".to_string())
+        );
+        assert_eq!(
+            parse_youtube_video_id("https://youtu.be/4NRXx6U8ABQ"),
+            Some("4NRXx6U8ABQ".to_string())
+        );
+        assert_eq!(
+            parse_youtube_video_id("https://example.com/watch?v=x"),
+            None
+        );
+    }
+
+    #[test]
+    fn detects_playlists_and_channels() {
+        assert_eq!(
+            parse_youtube_collection("https://www.youtube.com/playlist?list=PL123"),
+            Some(YoutubeCollection::Playlist)
+        );
+        assert_eq!(
+            parse_youtube_collection("https://music.youtube.com/playlist?list=OLAK5uy"),
+            Some(YoutubeCollection::Playlist)
+        );
+        assert_eq!(
+            parse_youtube_collection("https://www.youtube.com/@artist/videos"),
+            Some(YoutubeCollection::Channel)
+        );
+        assert_eq!(
+            parse_youtube_collection("https://www.youtube.com/channel/UC123"),
+            Some(YoutubeCollection::Channel)
+        );
+        assert_eq!(
+            parse_youtube_collection("https://www.youtube.com/watch?v=4NRXx6U8ABQ&list=PL123"),
+            None
+        );
+        assert_eq!(
+            parse_youtube_collection("https://youtu.be/4NRXx6U8ABQ"),
+            None
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! Reading the track list of a playlist, so each track can be converted.
+
+use std::collections::HashSet;
+
+use flom_core::{FlomError, FlomResult};
+use regex::Regex;
+use reqwest::Client;
+use url::Url;
+
+use crate::api::spotify::SpotifyClient;
+
+/// A playlist link on one of the platforms `flom playlist` can read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaylistUrl {
+    /// Read through the Web API by playlist ID.
+    Spotify(String),
+    /// Read from the public playlist page.
+    AppleMusic(String),
+    /// Read from the public playlist page, by `list=` ID.
+    Youtube(String),
+}
+
+impl PlaylistUrl {
+    pub fn parse(input: &str) -> Option<Self> {
+        let url = Url::parse(input.trim()).ok()?;
+        let domain = url.domain()?;
+        let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+        match domain {
+            "open.spotify.com" => {
+                let segments = match segments.first() {
+                    Some(first) if first.starts_with("intl-") => &segments[1..],
+                    _ => &segments[..],
+                };
+                match segments {
+                    ["playlist", id, ..] => Some(PlaylistUrl::Spotify(id.to_string())),
+                    _ => None,
+                }
+            }
+            "music.apple.com" => match segments.as_slice() {
+                [_, "playlist", ..] => Some(PlaylistUrl::AppleMusic(input.trim().to_string())),
+                _ => None,
+            },
+            _ if domain == "youtube.com" || domain.ends_with(".youtube.com") => {
+                if segments.first() != Some(&"playlist") {
+                    return None;
+                }
+                url.query_pairs()
+                    .find(|(key, _)| key == "list")
+                    .map(|(_, id)| PlaylistUrl::Youtube(id.to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Links of the tracks in `playlist`, in playlist order. Spotify playlists
+/// need `spotify`; the other platforms are read without an account, which
+/// limits them to what their public pages list (about the first 100 tracks).
+pub async fn playlist_tracks(
+    client: &Client,
+    playlist: &PlaylistUrl,
+    spotify: Option<&SpotifyClient>,
+) -> FlomResult<Vec<String>> {
+    match playlist {
+        PlaylistUrl::Spotify(id) => {
+            let spotify = spotify.ok_or_else(|| {
+                FlomError::Config("reading spotify playlists needs spotify credentials".to_string())
+            })?;
+            spotify.playlist_tracks(id).await
+        }
+        PlaylistUrl::AppleMusic(url) => Ok(apple_music_tracks(&fetch_page(client, url).await?)),
+        PlaylistUrl::Youtube(id) => {
+            let url = format!("https://www.youtube.com/playlist?list={id}");
+            Ok(youtube_tracks(&fetch_page(client, &url).await?))
+        }
+    }
+}
+
+async fn fetch_page(client: &Client, url: &str) -> FlomResult<String> {
+    let response = client
+        .get(url)
+        // Both sites serve a consent or app-install page to unknown clients.
+        .header("User-Agent", "Mozilla/5.0 (compatible; flom)")
+        .header("Accept-Language", "en")
+        .send()
+        .await
+        .map_err(|err| FlomError::Network(format!("playlist page request failed: {err}")))?;
+    if !response.status().is_success() {
+        return Err(FlomError::Api(format!(
+            "playlist page error: status={}",
+            response.status()
+        )));
+    }
+    response
+        .text()
+        .await
+        .map_err(|err| FlomError::Network(format!("playlist page read failed: {err}")))
+}
+
+/// Apple lists the songs of a playlist page as `music:song` meta tags.
+fn apple_music_tracks(html: &str) -> Vec<String> {
+    let re = Regex::new(r#"<meta\s+property="music:song"\s+content="([^"]+)""#)
+        .expect("valid meta regex");
+    unique(
+        re.captures_iter(html)
+            .map(|captures| captures[1].to_string()),
+    )
+}
+
+/// YouTube embeds the first page of playlist entries as JSON in the page.
+fn youtube_tracks(html: &str) -> Vec<String> {
+    let re = Regex::new(r#""playlistVideoRenderer":\{"videoId":"([A-Za-z0-9_-]{11})""#)
+        .expect("valid video regex");
+    unique(
+        re.captures_iter(html)
+            .map(|captures| format!("https://www.youtube.com/watch?v={}", &captures[1])),
+    )
+}
+
+fn unique(urls: impl Iterator<Item = String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    urls.filter(|url| seen.insert(url.clone())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_playlist_links() {
+        assert_eq!(
+            PlaylistUrl::parse("https://open.spotify.com/intl-ja/playlist/37i9dQZF1DX?si=x"),
+            Some(PlaylistUrl::Spotify("37i9dQZF1DX".to_string()))
+        );
+        assert_eq!(
+            PlaylistUrl::parse("https://music.apple.com/us/playlist/chill/pl.u-abc"),
+            Some(PlaylistUrl::AppleMusic(
+                "https://music.apple.com/us/playlist/chill/pl.u-abc".to_string()
+            ))
+        );
+        assert_eq!(
+            PlaylistUrl::parse("https://music.youtube.com/playlist?list=PL123"),
+            Some(PlaylistUrl::Youtube("PL123".to_string()))
+        );
+        assert_eq!(
+            PlaylistUrl::parse("https://www.youtube.com/watch?v=abc&list=PL123"),
+            None
+        );
+        assert_eq!(
+            PlaylistUrl::parse("https://open.spotify.com/track/abc"),
+            None
+        );
+    }
+
+    #[test]
+    fn reads_tracks_from_pages() {
+        let apple = r#"<meta property="music:song" content="https://music.apple.com/us/song/a/1">
+<meta property="music:song:track" content="1">
+<meta property="music:song" content="https://music.apple.com/us/song/b/2">"#;
+        assert_eq!(
+            apple_music_tracks(apple),
+            vec![
+                "https://music.apple.com/us/song/a/1",
+                "https://music.apple.com/us/song/b/2",
+            ]
+        );
+
+        let youtube = r#"{"playlistVideoRenderer":{"videoId":"dQw4w9WgXcQ","thumbnail":{}},
+{"playlistVideoRenderer":{"videoId":"dQw4w9WgXcQ"}},{"playlistVideoRenderer":{"videoId":"9bZkp7q19f0"}}"#;
+        assert_eq!(
+            youtube_tracks(youtube),
+            vec![
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+                "https://www.youtube.com/watch?v=9bZkp7q19f0",
+            ]
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Podcast episodes, which Odesli doesn't resolve: an episode is looked up by
+//! its show and title on the target platform instead.
+
+use flom_core::{ConversionResult, FlomError, FlomResult, MediaInfo, MediaType};
+use reqwest::Client;
+use url::Url;
+
+use crate::api::itunes::{ItunesClient, ItunesPodcast};
+use crate::api::spotify::SpotifyClient;
+use crate::matching::token_set_ratio;
+use crate::metadata::{artist_key, title_key};
+
+/// Minimum title similarity for two episodes to count as the same.
+const EPISODE_MIN_SCORE: f64 = 0.9;
+const SEARCH_LIMIT: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PodcastPlatform {
+    ApplePodcasts,
+    Spotify,
+    PocketCasts,
+}
+
+impl PodcastPlatform {
+    pub const ALL: [PodcastPlatform; 3] = [
+        PodcastPlatform::ApplePodcasts,
+        PodcastPlatform::Spotify,
+        PodcastPlatform::PocketCasts,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            PodcastPlatform::ApplePodcasts => "applePodcasts",
+            PodcastPlatform::Spotify => "spotify",
+            PodcastPlatform::PocketCasts => "pocketCasts",
+        }
+    }
+
+    /// Looks a platform up by key, case-insensitively and ignoring `-`/`_`
+    /// separators (e.g. `apple-podcasts`).
+    pub fn from_key(key: &str) -> Option<Self> {
+        let wanted: String = key
+            .trim()
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_'))
+            .collect();
+        Self::ALL
+            .into_iter()
+            .find(|platform| platform.key().eq_ignore_ascii_case(&wanted))
+    }
+}
+
+/// A link to one podcast episode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpisodeLink {
+    pub platform: PodcastPlatform,
+    pub episode_id: String,
+    /// The show's ID, when the link carries it (Apple Podcasts).
+    pub show_id: Option<String>,
+}
+
+impl EpisodeLink {
+    pub fn parse(input: &str) -> Option<Self> {
+        let url = Url::parse(input.trim()).ok()?;
+        let domain = url.domain()?.trim_start_matches("www.");
+        let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+        match domain {
+            "podcasts.apple.com" => {
+                let show_id = segments.last()?.strip_prefix("id")?;
+                let (_, episode_id) = url.query_pairs().find(|(key, _)| key == "i")?;
+                Some(Self {
+                    platform: PodcastPlatform::ApplePodcasts,
+                    episode_id: episode_id.to_string(),
+                    show_id: Some(show_id.to_string()),
+                })
+            }
+            "open.spotify.com" => {
+                let path = match segments.first() {
+                    Some(first) if first.starts_with("intl-") => &segments[1..],
+                    _ => &segments[..],
+                };
+                match path {
+                    ["episode", id, ..] => Some(Self {
+                        platform: PodcastPlatform::Spotify,
+                        episode_id: id.to_string(),
+                        show_id: None,
+                    }),
+                    _ => None,
+                }
+            }
+            "pca.st" | "pocketcasts.com" | "play.pocketcasts.com" => match segments.as_slice() {
+                ["episode", id, ..] => Some(Self {
+                    platform: PodcastPlatform::PocketCasts,
+                    episode_id: id.to_string(),
+                    show_id: None,
+                }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// What identifies an episode across platforms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Episode {
+    title: String,
+    show: String,
+}
+
+/// Converts episode links between Apple Podcasts, Spotify and Pocket Casts.
+/// Spotify on either side needs an authorized client. Pocket Casts has no
+/// public API, so its links can only be targets, and lead to the show.
+#[derive(Debug, Clone)]
+pub struct PodcastConverter {
+    itunes: ItunesClient,
+    spotify: Option<SpotifyClient>,
+    country: String,
+}
+
+impl PodcastConverter {
+    pub fn new(client: Client, country: &str, spotify: Option<SpotifyClient>) -> Self {
+        Self {
+            itunes: ItunesClient::new(client, country),
+            spotify,
+            country: country.to_string(),
+        }
+    }
+
+    pub async fn convert(
+        &self,
+        url: &str,
+        target: PodcastPlatform,
+    ) -> FlomResult<ConversionResult> {
+        let link = EpisodeLink::parse(url).ok_or_else(|| {
+            FlomError::UnsupportedInput(format!("not a podcast episode link: {url}"))
+        })?;
+        let episode = self.episode(&link).await?;
+        let mut result = ConversionResult {
+            source_url: url.to_string(),
+            source_platform: Some(link.platform.key().to_string()),
+            target_platform: Some(target.key().to_string()),
+            source_id: Some(link.episode_id.clone()),
+            source_info: Some(MediaInfo {
+                title: Some(episode.title.clone()),
+                artist: Some(episode.show.clone()),
+                kind: Some("episode".to_string()),
+                ..Default::default()
+            }),
+            media_type: Some(MediaType::Podcast),
+            ..Default::default()
+        };
+        match target {
+            PodcastPlatform::ApplePodcasts => {
+                let show = self.apple_show(&episode.show).await?;
+                let found = self
+                    .itunes
+                    .podcast_episodes(show.collection_id)
+                    .await?
+                    .into_iter()
+                    .filter_map(|candidate| {
+                        let title = candidate.track_name?;
+                        Some((title, (candidate.track_view_url?, candidate.track_id)))
+                    });
+                let (target_url, target_id) = best_episode(&episode.title, found)
+                    .ok_or_else(|| not_found(&episode, target))?;
+                result.target_url = Some(target_url);
+                result.target_id = target_id.map(|id| id.to_string());
+            }
+            PodcastPlatform::Spotify => {
+                let query = format!("{} {}", episode.title, episode.show);
+                let found = self
+                    .spotify()?
+                    .search_episodes(&query, &self.country, SEARCH_LIMIT)
+                    .await?
+                    .into_iter()
+                    .filter_map(|candidate| {
+                        let url = candidate.url()?.to_string();
+                        Some((candidate.name, url))
+                    });
+                let target_url = best_episode(&episode.title, found)
+                    .ok_or_else(|| not_found(&episode, target))?;
+                result.target_id = EpisodeLink::parse(&target_url).map(|link| link.episode_id);
+                result.target_url = Some(target_url);
+            }
+            PodcastPlatform::PocketCasts => {
+                let show = self.apple_show(&episode.show).await?;
+                result.target_url = Some(format!("https://pca.st/itunes/{}", show.collection_id));
+                result.warning =
+                    Some("Pocket Casts links open the show; find the episode there".to_string());
+            }
+        }
+        result.target_info = result.source_info.clone();
+        Ok(result)
+    }
+
+    async fn episode(&self, link: &EpisodeLink) -> FlomResult<Episode> {
+        match link.platform {
+            PodcastPlatform::ApplePodcasts => {
+                let show_id = link
+                    .show_id
+                    .as_deref()
+                    .and_then(|id| id.parse().ok())
+                    .ok_or_else(|| FlomError::InvalidInput("invalid podcast id".to_string()))?;
+                let episode = self
+                    .itunes
+                    .podcast_episodes(show_id)
+                    .await?
+                    .into_iter()
+                    .find(|episode| {
+                        episode.track_id.map(|id| id.to_string()).as_deref()
+                            == Some(link.episode_id.as_str())
+                    })
+                    .ok_or_else(|| {
+                        FlomError::UnsupportedInput(
+                            "episode not found among the podcast's latest 200".to_string(),
+                        )
+                    })?;
+                Ok(Episode {
+                    title: episode.track_name.unwrap_or_default(),
+                    show: episode.collection_name.unwrap_or_default(),
+                })
+            }
+            PodcastPlatform::Spotify => {
+                let episode = self
+                    .spotify()?
+                    .episode(&link.episode_id, &self.country)
+                    .await?;
+                Ok(Episode {
+                    title: episode.name,
+                    show: episode.show.map(|show| show.name).unwrap_or_default(),
+                })
+            }
+            PodcastPlatform::PocketCasts => Err(FlomError::UnsupportedInput(
+                "pocket casts episodes can't be read; pass the Apple Podcasts or Spotify link"
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// The show on Apple Podcasts, preferring an exact name match.
+    async fn apple_show(&self, name: &str) -> FlomResult<ItunesPodcast> {
+        let shows = self.itunes.search_podcasts(name, SEARCH_LIMIT).await?;
+        let wanted = artist_key(name);
+        shows
+            .iter()
+            .find(|show| artist_key(&show.collection_name) == wanted)
+            .or(shows.first())
+            .cloned()
+            .ok_or_else(|| {
+                FlomError::UnsupportedInput(format!("podcast not found on Apple Podcasts: {name}"))
+            })
+    }
+
+    fn spotify(&self) -> FlomResult<&SpotifyClient> {
+        self.spotify.as_ref().ok_or_else(|| {
+            FlomError::Config("spotify podcast links need spotify credentials".to_string())
+        })
+    }
+}
+
+fn not_found(episode: &Episode, target: PodcastPlatform) -> FlomError {
+    FlomError::UnsupportedInput(format!(
+        "episode not found on {}: {} / {}",
+        target.key(),
+        episode.title,
+        episode.show
+    ))
+}
+
+/// The value of the candidate whose title is closest to `title`, if close
+/// enough. Ties go to the earlier candidate, i.e. the newer episode.
+fn best_episode<T>(title: &str, candidates: impl Iterator<Item = (String, T)>) -> Option<T> {
+    let wanted = title_key(title);
+    candidates
+        .map(|(candidate, value)| (token_set_ratio(&wanted, &title_key(&candidate)), value))
+        .filter(|(score, _)| *score >= EPISODE_MIN_SCORE)
+        .fold(None, |best, (score, value)| match best {
+            Some((best_score, _)) if best_score >= score => best,
+            _ => Some((score, value)),
+        })
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_episode_links() {
+        assert_eq!(
+            EpisodeLink::parse(
+                "https://podcasts.apple.com/us/podcast/the-daily/id1200361736?i=1000650000000"
+            ),
+            Some(EpisodeLink {
+                platform: PodcastPlatform::ApplePodcasts,
+                episode_id: "1000650000000".to_string(),
+                show_id: Some("1200361736".to_string()),
+            })
+        );
+        assert_eq!(
+            EpisodeLink::parse("https://open.spotify.com/episode/5Xt5DXGzch68nYYamXrNxZ?si=x")
+                .map(|link| (link.platform, link.episode_id)),
+            Some((
+                PodcastPlatform::Spotify,
+                "5Xt5DXGzch68nYYamXrNxZ".to_string()
+            ))
+        );
+        assert_eq!(
+            EpisodeLink::parse("https://pca.st/episode/0a1b2c3d-0000-4000-8000-000000000000")
+                .map(|link| link.platform),
+            Some(PodcastPlatform::PocketCasts)
+        );
+        // A show page without an episode is not an episode link.
+        assert_eq!(
+            EpisodeLink::parse("https://podcasts.apple.com/us/podcast/the-daily/id1200361736"),
+            None
+        );
+        assert_eq!(
+            EpisodeLink::parse("https://open.spotify.com/track/4Km5HrUvYTaSUfiSGPJeQR"),
+            None
+        );
+    }
+
+    #[test]
+    fn picks_the_closest_episode_title() {
+        let candidates = || {
+            vec![
+                ("Episode 12: The Return".to_string(), 12),
+                ("Episode 120: Unrelated".to_string(), 120),
+                ("episode 12 - the return (rerun)".to_string(), 13),
+            ]
+            .into_iter()
+        };
+        assert_eq!(
+            best_episode("Episode 12: The Return", candidates()),
+            Some(12)
+        );
+        assert_eq!(best_episode("Something else entirely", candidates()), None);
+        assert_eq!(
+            PodcastPlatform::from_key("apple-podcasts"),
+            Some(PodcastPlatform::ApplePodcasts)
+        );
+    }
+}
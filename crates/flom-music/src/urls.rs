@@ -0,0 +1,316 @@
+//! Canonical link formats for platform-native IDs, the inverse of [`crate::parsers`].
+
+use flom_core::{FlomError, FlomResult, find_platform_key, normalize_url};
+use url::Url;
+
+use crate::api::odesli::EntityType;
+
+/// Platforms whose links can be built from an ID alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Spotify,
+    AppleMusic,
+    Youtube,
+    YoutubeMusic,
+    Deezer,
+    Tidal,
+    AmazonMusic,
+}
+
+impl Platform {
+    pub const ALL: [Platform; 7] = [
+        Platform::Spotify,
+        Platform::AppleMusic,
+        Platform::Youtube,
+        Platform::YoutubeMusic,
+        Platform::Deezer,
+        Platform::Tidal,
+        Platform::AmazonMusic,
+    ];
+
+    /// The platform's key in Odesli responses (`linksByPlatform`).
+    pub fn key(self) -> &'static str {
+        match self {
+            Platform::Spotify => "spotify",
+            Platform::AppleMusic => "appleMusic",
+            Platform::Youtube => "youtube",
+            Platform::YoutubeMusic => "youtubeMusic",
+            Platform::Deezer => "deezer",
+            Platform::Tidal => "tidal",
+            Platform::AmazonMusic => "amazonMusic",
+        }
+    }
+
+    /// Looks a platform up by Odesli key, as [`find_platform_key`] matches it.
+    pub fn from_key(key: &str) -> Option<Self> {
+        let key = find_platform_key(&Self::ALL.map(Platform::key), key)?;
+        Self::ALL.into_iter().find(|platform| platform.key() == key)
+    }
+}
+
+/// Builds the public link for a track or album on `platform`.
+///
+/// IDs are the ones each platform puts in its own links: a Spotify base-62 ID,
+/// an Apple Music or Deezer number, a YouTube video ID, an Amazon ASIN, and so on.
+pub fn build_url(platform: Platform, entity_type: EntityType, id: &str) -> FlomResult<String> {
+    let id = validate_id(id)?;
+    let url = match (platform, entity_type) {
+        (Platform::Spotify, EntityType::Song) => format!("https://open.spotify.com/track/{id}"),
+        (Platform::Spotify, EntityType::Album) => format!("https://open.spotify.com/album/{id}"),
+        // Apple ignores the slug, but the path needs one.
+        (Platform::AppleMusic, EntityType::Song) => {
+            format!("https://music.apple.com/us/song/_/{id}")
+        }
+        (Platform::AppleMusic, EntityType::Album) => {
+            format!("https://music.apple.com/us/album/_/{id}")
+        }
+        (Platform::Youtube, EntityType::Song) => format!("https://www.youtube.com/watch?v={id}"),
+        (Platform::Youtube, EntityType::Album) => {
+            return Err(FlomError::UnsupportedInput(
+                "youtube has no album pages; use youtubeMusic".to_string(),
+            ));
+        }
+        (Platform::YoutubeMusic, EntityType::Song) => {
+            format!("https://music.youtube.com/watch?v={id}")
+        }
+        (Platform::YoutubeMusic, EntityType::Album) => {
+            format!("https://music.youtube.com/browse/{id}")
+        }
+        (Platform::Deezer, EntityType::Song) => format!("https://www.deezer.com/track/{id}"),
+        (Platform::Deezer, EntityType::Album) => format!("https://www.deezer.com/album/{id}"),
+        (Platform::Tidal, EntityType::Song) => format!("https://tidal.com/browse/track/{id}"),
+        (Platform::Tidal, EntityType::Album) => format!("https://tidal.com/browse/album/{id}"),
+        (Platform::AmazonMusic, EntityType::Song) => {
+            format!("https://music.amazon.com/tracks/{id}")
+        }
+        (Platform::AmazonMusic, EntityType::Album) => {
+            format!("https://music.amazon.com/albums/{id}")
+        }
+    };
+    Ok(url)
+}
+
+/// Builds the link to the radio station or mix `platform` seeds with a track.
+/// Deezer, Tidal and Amazon Music have no shareable track radio.
+pub fn build_radio_url(platform: Platform, track_id: &str) -> FlomResult<String> {
+    let id = validate_id(track_id)?;
+    let url = match platform {
+        // Track radio only has an app URI; the web player has no radio page.
+        Platform::Spotify => format!("spotify:station:track:{id}"),
+        Platform::AppleMusic => format!("https://music.apple.com/us/station/_/ra.{id}"),
+        Platform::Youtube => format!("https://www.youtube.com/watch?v={id}&list=RD{id}"),
+        Platform::YoutubeMusic => {
+            format!("https://music.youtube.com/watch?v={id}&list=RDAMVM{id}")
+        }
+        Platform::Deezer | Platform::Tidal | Platform::AmazonMusic => {
+            return Err(FlomError::UnsupportedInput(format!(
+                "{} has no track radio links",
+                platform.key()
+            )));
+        }
+    };
+    Ok(url)
+}
+
+/// [`normalize_url`] plus removal of what share sheets add: tracking parameters
+/// (`si`, `utm_*`, YouTube's `feature=share`) and locale path segments
+/// (Spotify's `intl-ja`, Deezer's `fr`). Every share of a track therefore
+/// reaches Odesli, and the caches, as the same link.
+pub fn clean_share_url(url: &str) -> FlomResult<String> {
+    let normalized = normalize_url(url)?;
+    let mut parsed = Url::parse(&normalized)
+        .map_err(|err| FlomError::InvalidInput(format!("invalid url: {err}")))?;
+
+    let host = parsed.host_str().unwrap_or_default().to_string();
+    let segments: Vec<String> = parsed
+        .path_segments()
+        .map(|segments| segments.map(str::to_string).collect())
+        .unwrap_or_default();
+    let locale = match segments.first() {
+        Some(first) if host == "open.spotify.com" => first.starts_with("intl-"),
+        Some(first) if host.ends_with("deezer.com") => {
+            first.len() == 2 && first.chars().all(|c| c.is_ascii_lowercase()) && segments.len() > 2
+        }
+        _ => false,
+    };
+    if locale {
+        parsed.set_path(&format!("/{}", segments[1..].join("/")));
+    }
+
+    let pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    let kept: Vec<_> = pairs
+        .iter()
+        .filter(|(name, value)| !is_share_param(name, value))
+        .collect();
+    if kept.len() < pairs.len() {
+        if kept.is_empty() {
+            parsed.set_query(None);
+        } else {
+            parsed.query_pairs_mut().clear().extend_pairs(kept);
+        }
+    }
+    Ok(parsed.into())
+}
+
+fn is_share_param(name: &str, value: &str) -> bool {
+    name == "si" || name.starts_with("utm_") || (name == "feature" && value.starts_with("share"))
+}
+
+fn validate_id(id: &str) -> FlomResult<&str> {
+    let id = id.trim();
+    if id.is_empty() {
+        return Err(FlomError::InvalidInput("id must not be empty".to_string()));
+    }
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+    {
+        return Err(FlomError::InvalidInput(format!("invalid id: {id}")));
+    }
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::parse_platform_id;
+    use flom_core::validate_music_url;
+
+    #[test]
+    fn clean_share_url_drops_tracking_and_locale() {
+        let cases = [
+            (
+                "https://open.spotify.com/intl-ja/track/4Km5HrUvYTaSUfiSGPJeQR?si=abc123",
+                "https://open.spotify.com/track/4Km5HrUvYTaSUfiSGPJeQR",
+            ),
+            (
+                "https://youtu.be/dQw4w9WgXcQ?si=x&feature=shared",
+                "https://youtu.be/dQw4w9WgXcQ",
+            ),
+            (
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ&utm_source=chat&t=42",
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42",
+            ),
+            (
+                "https://www.deezer.com/fr/track/3135556?utm_campaign=x",
+                "https://www.deezer.com/track/3135556",
+            ),
+            (
+                "https://music.apple.com/jp/album/x/1440?i=1441&ls",
+                "https://music.apple.com/jp/album/x/1440?i=1441&ls",
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(clean_share_url(input).unwrap(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn builds_track_and_album_links() {
+        let cases = [
+            (
+                Platform::Spotify,
+                EntityType::Song,
+                "4Km5HrUvYTaSUfiSGPJeQR",
+                "https://open.spotify.com/track/4Km5HrUvYTaSUfiSGPJeQR",
+            ),
+            (
+                Platform::AppleMusic,
+                EntityType::Album,
+                "1496794033",
+                "https://music.apple.com/us/album/_/1496794033",
+            ),
+            (
+                Platform::YoutubeMusic,
+                EntityType::Album,
+                "MPREb_abc",
+                "https://music.youtube.com/browse/MPREb_abc",
+            ),
+            (
+                Platform::Deezer,
+                EntityType::Song,
+                "3135556",
+                "https://www.deezer.com/track/3135556",
+            ),
+            (
+                Platform::Tidal,
+                EntityType::Album,
+                "77646168",
+                "https://tidal.com/browse/album/77646168",
+            ),
+            (
+                Platform::AmazonMusic,
+                EntityType::Song,
+                "B08HRQZJ5N",
+                "https://music.amazon.com/tracks/B08HRQZJ5N",
+            ),
+        ];
+        for (platform, entity_type, id, expected) in cases {
+            assert_eq!(build_url(platform, entity_type, id).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn built_links_are_accepted_and_parse_back() {
+        for platform in Platform::ALL {
+            let url = build_url(platform, EntityType::Song, "abc123").unwrap();
+            validate_music_url(&url).unwrap();
+        }
+        for platform in [Platform::Spotify, Platform::AppleMusic, Platform::Youtube] {
+            let url = build_url(platform, EntityType::Song, "123456").unwrap();
+            assert_eq!(parse_platform_id(&url).as_deref(), Some("123456"), "{url}");
+        }
+    }
+
+    #[test]
+    fn rejects_bad_ids_and_unknown_combinations() {
+        assert!(matches!(
+            build_url(Platform::Spotify, EntityType::Song, " "),
+            Err(FlomError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            build_url(Platform::Deezer, EntityType::Song, "1/../2"),
+            Err(FlomError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            build_url(Platform::Youtube, EntityType::Album, "PL1"),
+            Err(FlomError::UnsupportedInput(_))
+        ));
+    }
+
+    #[test]
+    fn builds_radio_links() {
+        assert_eq!(
+            build_radio_url(Platform::Youtube, "dQw4w9WgXcQ").unwrap(),
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=RDdQw4w9WgXcQ"
+        );
+        assert_eq!(
+            build_radio_url(Platform::Spotify, "4cOdK2wGLETKBW3PvgPWqT").unwrap(),
+            "spotify:station:track:4cOdK2wGLETKBW3PvgPWqT"
+        );
+        assert!(matches!(
+            build_radio_url(Platform::Tidal, "1"),
+            Err(FlomError::UnsupportedInput(_))
+        ));
+        assert!(matches!(
+            build_radio_url(Platform::Youtube, "a&list=x"),
+            Err(FlomError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn from_key_accepts_separators() {
+        assert_eq!(
+            Platform::from_key("apple-music"),
+            Some(Platform::AppleMusic)
+        );
+        assert_eq!(
+            Platform::from_key("YOUTUBE_MUSIC"),
+            Some(Platform::YoutubeMusic)
+        );
+        assert_eq!(Platform::from_key("napster"), None);
+    }
+}
@@ -0,0 +1,42 @@
+//! Synchronous wrapper around [`ShortenClient`] for tools without an async
+//! runtime. Calls run on a private current-thread runtime, so it must not be
+//! used from inside another async runtime.
+
+use flom_core::FlomResult;
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{ShortLink, ShortenClient};
+
+#[derive(Debug)]
+pub struct BlockingShortenClient {
+    inner: ShortenClient,
+    runtime: Runtime,
+}
+
+impl ShortenClient {
+    /// Wraps this client in a [`BlockingShortenClient`].
+    pub fn blocking(self) -> BlockingShortenClient {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build blocking runtime");
+        BlockingShortenClient {
+            inner: self,
+            runtime,
+        }
+    }
+}
+
+impl BlockingShortenClient {
+    pub fn shorten(&self, input: &str) -> FlomResult<String> {
+        self.runtime.block_on(self.inner.shorten(input))
+    }
+
+    pub fn shorten_link(&self, input: &str) -> FlomResult<ShortLink> {
+        self.runtime.block_on(self.inner.shorten_link(input))
+    }
+
+    pub fn into_inner(self) -> ShortenClient {
+        self.inner
+    }
+}
@@ -1,10 +1,63 @@
+#[cfg(feature = "blocking")]
+mod blocking;
+mod provider;
+mod state;
+mod verify;
+
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
 use flom_core::{FlomError, FlomResult, validate_url};
-use reqwest::Client;
-use serde::Deserialize;
+use reqwest::{Client, redirect};
+use serde::Serialize;
+
+#[cfg(feature = "blocking")]
+pub use blocking::BlockingShortenClient;
+pub use provider::Provider;
+pub use state::{ProviderState, ShortenState};
+
+use provider::Attempt;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// How long a provider is skipped after it reports a rate limit.
+pub const RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(60 * 60);
+
+/// A short link together with the provider that produced it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortLink {
+    pub url: String,
+    pub short_url: String,
+    pub provider: &'static str,
+    /// Whether the short link was checked to redirect straight to `url`.
+    pub verified: bool,
+}
 
-#[derive(Debug, Clone)]
+type SleepFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Timer used to wait out a provider's minimum interval between requests.
+#[derive(Clone)]
+struct Sleep(Arc<dyn Fn(Duration) -> SleepFuture + Send + Sync>);
+
+impl fmt::Debug for Sleep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sleep")
+    }
+}
+
+#[derive(Debug)]
 pub struct ShortenClient {
     client: Client,
+    /// Client that does not follow redirects, used by `--verify`.
+    verify_client: Option<Client>,
+    providers: Vec<Provider>,
+    state_path: Option<PathBuf>,
+    state: Mutex<ShortenState>,
+    sleep: Option<Sleep>,
 }
 
 impl Default for ShortenClient {
@@ -19,46 +72,161 @@ impl ShortenClient {
             .user_agent("flom/0.1")
             .build()
             .expect("failed to build http client");
-        Self { client }
+        Self {
+            client,
+            verify_client: None,
+            providers: Provider::ALL.to_vec(),
+            state_path: None,
+            state: Mutex::new(ShortenState::default()),
+            sleep: default_sleep(),
+        }
+    }
+
+    /// Waits with `sleep` instead of the default timer (Tokio's, with the
+    /// `tokio` feature), for embedders running on another executor. Without
+    /// either, a provider that needs a pause fails with a config error.
+    pub fn with_sleep<F, Fut>(mut self, sleep: F) -> Self
+    where
+        F: Fn(Duration) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.sleep = Some(Sleep(Arc::new(move |wait| Box::pin(sleep(wait)))));
+        self
+    }
+
+    /// Persists rate-limit bookkeeping in `path` so limits hold across runs.
+    pub fn with_state_file(mut self, path: PathBuf) -> Self {
+        self.state = Mutex::new(ShortenState::load(&path));
+        self.state_path = Some(path);
+        self
+    }
+
+    /// Overrides the provider order; the first entry is the primary.
+    pub fn with_providers(mut self, providers: Vec<Provider>) -> Self {
+        self.providers = providers;
+        self
+    }
+
+    /// Checks each short link redirects directly to the original URL and
+    /// falls back to the next provider when it does not.
+    pub fn with_verify(mut self, verify: bool) -> Self {
+        self.verify_client = verify.then(|| {
+            Client::builder()
+                .user_agent("flom/0.1")
+                .redirect(redirect::Policy::none())
+                .build()
+                .expect("failed to build http client")
+        });
+        self
     }
 
     pub async fn shorten(&self, input: &str) -> FlomResult<String> {
+        self.shorten_link(input).await.map(|link| link.short_url)
+    }
+
+    pub async fn shorten_link(&self, input: &str) -> FlomResult<ShortLink> {
         validate_url(input)?;
-        let response = self
-            .client
-            .get("https://is.gd/create.php")
-            .query(&[("format", "json"), ("url", input)])
-            .send()
-            .await
-            .map_err(|err| FlomError::Network(format!("shorten request failed: {err}")))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(FlomError::Api(format!(
-                "shorten error: status={status} body={body}"
+        let mut last_error = None;
+        for &provider in &self.providers {
+            let name = provider.name();
+            let Some(wait) = self.reserve(provider)? else {
+                continue;
+            };
+            if !wait.is_zero()
+                && let Some(Sleep(sleep)) = &self.sleep
+            {
+                sleep(wait).await;
+            }
+
+            match provider.shorten(&self.client, input).await {
+                Attempt::Shortened(short_url) => {
+                    let Some(verify_client) = &self.verify_client else {
+                        return Ok(ShortLink {
+                            url: input.to_string(),
+                            short_url,
+                            provider: name,
+                            verified: false,
+                        });
+                    };
+                    if verify::redirects_to(verify_client, &short_url, input).await? {
+                        return Ok(ShortLink {
+                            url: input.to_string(),
+                            short_url,
+                            provider: name,
+                            verified: true,
+                        });
+                    }
+                    last_error = Some(FlomError::Api(format!(
+                        "{name} did not redirect {short_url} directly to the original url"
+                    )));
+                }
+                Attempt::RateLimited(message) => {
+                    self.update_state(|state| {
+                        state.block(name, SystemTime::now(), RATE_LIMIT_COOLDOWN)
+                    });
+                    last_error = Some(FlomError::Api(format!(
+                        "{name} rate limit exceeded: {message}"
+                    )));
+                }
+                // Only rate limits fail over; other errors would repeat on every provider.
+                Attempt::Failed(err) => return Err(err),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            FlomError::Api("all shorten providers are rate limited; try again later".to_string())
+        }))
+    }
+
+    /// Books the next request to `provider` and returns how long to wait for
+    /// it, or `None` while the provider is blocked. The slot is recorded
+    /// before waiting, so concurrent calls line up behind each other instead
+    /// of all waiting out the same pause.
+    fn reserve(&self, provider: Provider) -> FlomResult<Option<Duration>> {
+        let name = provider.name();
+        let now = SystemTime::now();
+        let mut state = self.state();
+        if state.is_blocked(name, now) {
+            return Ok(None);
+        }
+        let wait = state.wait_time(name, now, provider.min_interval());
+        if !wait.is_zero() && self.sleep.is_none() {
+            return Err(FlomError::Config(format!(
+                "{name} needs a {}s pause between requests, but no timer is set; \
+                 enable the `tokio` feature or call `with_sleep`",
+                wait.as_secs_f32().ceil()
             )));
         }
+        state.record_request(name, now + wait);
+        self.save_state(&state);
+        Ok(Some(wait))
+    }
 
-        let payload = response
-            .json::<ShortenResponse>()
-            .await
-            .map_err(|err| FlomError::Parse(format!("shorten response parse failed: {err}")))?;
+    fn update_state(&self, update: impl FnOnce(&mut ShortenState)) {
+        let mut state = self.state();
+        update(&mut state);
+        self.save_state(&state);
+    }
 
-        if let Some(error_message) = payload.errormessage {
-            return Err(FlomError::Api(error_message));
-        }
+    fn state(&self) -> std::sync::MutexGuard<'_, ShortenState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
 
-        payload
-            .shorturl
-            .ok_or_else(|| FlomError::Api("shorten response missing shorturl".to_string()))
+    fn save_state(&self, state: &ShortenState) {
+        if let Some(path) = &self.state_path {
+            // Losing bookkeeping only makes the next run less polite; don't fail the request.
+            let _ = state.save(path);
+        }
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct ShortenResponse {
-    shorturl: Option<String>,
-    errormessage: Option<String>,
+#[cfg(feature = "tokio")]
+fn default_sleep() -> Option<Sleep> {
+    Some(Sleep(Arc::new(|wait| Box::pin(tokio::time::sleep(wait)))))
+}
+
+#[cfg(not(feature = "tokio"))]
+fn default_sleep() -> Option<Sleep> {
+    None
 }
 
 #[cfg(test)]
@@ -113,4 +281,66 @@ mod tests {
         let result = rt.block_on(async { client.shorten("https://").await });
         assert!(result.is_err());
     }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_shorten_validates_url() {
+        let client = ShortenClient::new().blocking();
+        assert!(matches!(
+            client.shorten("not-a-url"),
+            Err(FlomError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_shorten_skips_blocked_providers() {
+        let client = ShortenClient::new().with_providers(vec![Provider::IsGd]);
+        client.update_state(|state| state.block("is.gd", SystemTime::now(), RATE_LIMIT_COOLDOWN));
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let result = rt.block_on(async { client.shorten("https://example.com").await });
+        match result {
+            Err(FlomError::Api(msg)) => assert!(msg.contains("rate limited")),
+            other => panic!("expected rate limit error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_shorten_without_timer_fails_for_paced_providers() {
+        let mut client = ShortenClient::new().with_providers(vec![Provider::IsGd]);
+        client.sleep = None;
+        client.update_state(|state| state.record_request("is.gd", SystemTime::now()));
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let result = rt.block_on(async { client.shorten("https://example.com").await });
+        match result {
+            Err(FlomError::Config(msg)) => assert!(msg.contains("no timer is set")),
+            other => panic!("expected missing timer error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_concurrent_requests_reserve_their_own_slot() {
+        let client = ShortenClient::new().with_sleep(|_| async {});
+        let first = client.reserve(Provider::IsGd).unwrap().unwrap();
+        let second = client.reserve(Provider::IsGd).unwrap().unwrap();
+        assert!(first.is_zero());
+        assert!(second > Provider::IsGd.min_interval() / 2);
+    }
+
+    #[test]
+    fn test_with_sleep_replaces_the_timer() {
+        let waits = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&waits);
+        let client = ShortenClient::new().with_sleep(move |wait| {
+            recorded.lock().unwrap().push(wait);
+            async {}
+        });
+        let Some(Sleep(sleep)) = &client.sleep else {
+            panic!("expected a timer");
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(sleep(Duration::from_millis(1500)));
+        assert_eq!(*waits.lock().unwrap(), vec![Duration::from_millis(1500)]);
+    }
 }
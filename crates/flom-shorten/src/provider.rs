@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use flom_core::FlomError;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+
+/// is.gd error code for "rate limit exceeded".
+const ISGD_RATE_LIMITED: u8 = 3;
+
+/// Public shortening services, tried in order until one succeeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    IsGd,
+    VGd,
+    TinyUrl,
+}
+
+/// Outcome of a single provider request.
+#[derive(Debug)]
+pub(crate) enum Attempt {
+    Shortened(String),
+    /// The provider asked us to back off; try another one.
+    RateLimited(String),
+    Failed(FlomError),
+}
+
+impl Provider {
+    pub const ALL: [Provider; 3] = [Provider::IsGd, Provider::VGd, Provider::TinyUrl];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Provider::IsGd => "is.gd",
+            Provider::VGd => "v.gd",
+            Provider::TinyUrl => "tinyurl.com",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|provider| provider.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Minimum spacing between requests that the service tolerates.
+    pub fn min_interval(self) -> Duration {
+        match self {
+            Provider::IsGd | Provider::VGd => Duration::from_secs(1),
+            Provider::TinyUrl => Duration::from_millis(500),
+        }
+    }
+
+    pub(crate) async fn shorten(self, client: &Client, url: &str) -> Attempt {
+        match self {
+            Provider::IsGd => gd_shorten(client, "https://is.gd/create.php", url).await,
+            Provider::VGd => gd_shorten(client, "https://v.gd/create.php", url).await,
+            Provider::TinyUrl => tinyurl_shorten(client, url).await,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GdResponse {
+    shorturl: Option<String>,
+    errorcode: Option<u8>,
+    errormessage: Option<String>,
+}
+
+async fn gd_shorten(client: &Client, endpoint: &str, url: &str) -> Attempt {
+    let response = match client
+        .get(endpoint)
+        .query(&[("format", "json"), ("url", url)])
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => {
+            return Attempt::Failed(FlomError::Network(format!("shorten request failed: {err}")));
+        }
+    };
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        return Attempt::RateLimited(format!("status={}", response.status()));
+    }
+    // is.gd reports errors (including rate limits) in the JSON body.
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    match serde_json::from_str::<GdResponse>(&body) {
+        Ok(payload) => gd_attempt(payload),
+        Err(_) if !status.is_success() => Attempt::Failed(FlomError::Api(format!(
+            "shorten error: status={status} body={body}"
+        ))),
+        Err(err) => Attempt::Failed(FlomError::Parse(format!(
+            "shorten response parse failed: {err}"
+        ))),
+    }
+}
+
+fn gd_attempt(payload: GdResponse) -> Attempt {
+    match payload {
+        GdResponse {
+            errorcode: Some(ISGD_RATE_LIMITED),
+            errormessage,
+            ..
+        } => Attempt::RateLimited(errormessage.unwrap_or_default()),
+        GdResponse {
+            errormessage: Some(message),
+            ..
+        } => Attempt::Failed(FlomError::Api(message)),
+        GdResponse {
+            shorturl: Some(url),
+            ..
+        } => Attempt::Shortened(url),
+        _ => Attempt::Failed(FlomError::Api(
+            "shorten response missing shorturl".to_string(),
+        )),
+    }
+}
+
+async fn tinyurl_shorten(client: &Client, url: &str) -> Attempt {
+    let response = match client
+        .get("https://tinyurl.com/api-create.php")
+        .query(&[("url", url)])
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => {
+            return Attempt::Failed(FlomError::Network(format!("shorten request failed: {err}")));
+        }
+    };
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Attempt::RateLimited(format!("status={status}"));
+    }
+    let body = response.text().await.unwrap_or_default();
+    let body = body.trim();
+    if !status.is_success() || !body.starts_with("http") {
+        return Attempt::Failed(FlomError::Api(format!(
+            "shorten error: status={status} body={body}"
+        )));
+    }
+    Attempt::Shortened(body.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gd_rate_limit_is_detected() {
+        let payload: GdResponse = serde_json::from_str(
+            r#"{"errorcode":3,"errormessage":"Rate limit exceeded - please wait 1 minute"}"#,
+        )
+        .unwrap();
+        assert!(matches!(gd_attempt(payload), Attempt::RateLimited(_)));
+
+        let payload: GdResponse =
+            serde_json::from_str(r#"{"errorcode":1,"errormessage":"bad url"}"#).unwrap();
+        assert!(matches!(
+            gd_attempt(payload),
+            Attempt::Failed(FlomError::Api(_))
+        ));
+
+        let payload: GdResponse =
+            serde_json::from_str(r#"{"shorturl":"https://is.gd/x"}"#).unwrap();
+        assert!(matches!(gd_attempt(payload), Attempt::Shortened(url) if url == "https://is.gd/x"));
+    }
+
+    #[test]
+    fn from_name_matches_case_insensitively() {
+        assert_eq!(Provider::from_name("IS.GD"), Some(Provider::IsGd));
+        assert_eq!(Provider::from_name("bit.ly"), None);
+    }
+}
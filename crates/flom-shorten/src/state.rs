@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use flom_core::{FlomError, FlomResult};
+use serde::{Deserialize, Serialize};
+
+/// Per-provider request bookkeeping, persisted so rate limits hold across runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShortenState {
+    #[serde(default)]
+    pub providers: BTreeMap<String, ProviderState>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderState {
+    /// Unix time in milliseconds of the last request.
+    #[serde(default)]
+    pub last_request_ms: u64,
+    /// Unix time in seconds until which the provider is skipped after a rate-limit response.
+    #[serde(default)]
+    pub blocked_until: u64,
+}
+
+impl ShortenState {
+    /// Loads the state file; a missing or unreadable file yields an empty state.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> FlomResult<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| {
+                FlomError::Config(format!("failed to create shorten state dir: {err}"))
+            })?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|err| FlomError::Parse(format!("failed to serialize shorten state: {err}")))?;
+        fs::write(path, content)
+            .map_err(|err| FlomError::Config(format!("failed to write shorten state: {err}")))
+    }
+
+    pub fn is_blocked(&self, provider: &str, now: SystemTime) -> bool {
+        self.providers
+            .get(provider)
+            .is_some_and(|state| state.blocked_until > unix_secs(now))
+    }
+
+    pub fn block(&mut self, provider: &str, now: SystemTime, cooldown: Duration) {
+        self.providers
+            .entry(provider.to_string())
+            .or_default()
+            .blocked_until = unix_secs(now) + cooldown.as_secs();
+    }
+
+    /// How long to wait before the next request to `provider` respects `min_interval`.
+    pub fn wait_time(&self, provider: &str, now: SystemTime, min_interval: Duration) -> Duration {
+        let Some(state) = self.providers.get(provider) else {
+            return Duration::ZERO;
+        };
+        let next = state.last_request_ms + min_interval.as_millis() as u64;
+        Duration::from_millis(next.saturating_sub(unix_millis(now)))
+    }
+
+    pub fn record_request(&mut self, provider: &str, now: SystemTime) {
+        self.providers
+            .entry(provider.to_string())
+            .or_default()
+            .last_request_ms = unix_millis(now);
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_and_wait_time() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let mut state = ShortenState::default();
+        assert!(!state.is_blocked("is.gd", now));
+        assert_eq!(
+            state.wait_time("is.gd", now, Duration::from_secs(1)),
+            Duration::ZERO
+        );
+
+        state.record_request("is.gd", now);
+        assert_eq!(
+            state.wait_time(
+                "is.gd",
+                now + Duration::from_millis(400),
+                Duration::from_secs(1)
+            ),
+            Duration::from_millis(600)
+        );
+
+        state.block("is.gd", now, Duration::from_secs(60));
+        assert!(state.is_blocked("is.gd", now + Duration::from_secs(59)));
+        assert!(!state.is_blocked("is.gd", now + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn save_and_load_roundtrip() {
+        let path = std::env::temp_dir().join("flom-test-shorten-state/state.json");
+        let _ = fs::remove_file(&path);
+        assert!(ShortenState::load(&path).providers.is_empty());
+
+        let mut state = ShortenState::default();
+        state.block(
+            "is.gd",
+            UNIX_EPOCH + Duration::from_secs(10),
+            Duration::from_secs(5),
+        );
+        state.save(&path).unwrap();
+        assert_eq!(
+            ShortenState::load(&path).providers["is.gd"].blocked_until,
+            15
+        );
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,51 @@
+use flom_core::{FlomError, FlomResult, normalize_url};
+use reqwest::Client;
+use reqwest::header::LOCATION;
+
+/// Checks that `short_url` redirects straight to `original`.
+///
+/// Providers that show an interstitial (ad or preview) page answer with a
+/// 200 page or redirect somewhere else, so both count as a mismatch.
+pub(crate) async fn redirects_to(
+    client: &Client,
+    short_url: &str,
+    original: &str,
+) -> FlomResult<bool> {
+    let response = client
+        .head(short_url)
+        .send()
+        .await
+        .map_err(|err| FlomError::Network(format!("verify request failed: {err}")))?;
+    if !response.status().is_redirection() {
+        return Ok(false);
+    }
+    Ok(response
+        .headers()
+        .get(LOCATION)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|location| same_destination(location, original)))
+}
+
+fn same_destination(location: &str, original: &str) -> bool {
+    match (normalize_url(location), normalize_url(original)) {
+        (Ok(location), Ok(original)) => location == original,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_destination_ignores_case_and_fragment() {
+        assert!(same_destination(
+            "https://Example.com/a?b=1",
+            "https://example.com/a?b=1#top"
+        ));
+        assert!(!same_destination(
+            "https://ads.example.net/preview?to=https%3A%2F%2Fexample.com%2Fa",
+            "https://example.com/a"
+        ));
+    }
+}
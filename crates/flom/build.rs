@@ -0,0 +1,21 @@
+use chrono::DateTime;
+
+fn main() {
+    let target = std::env::var("TARGET").unwrap_or_else(|_| "unknown".to_string());
+    println!("cargo:rustc-env=FLOM_TARGET={target}");
+
+    // Only a reproducible-build timestamp is recorded; the wall clock would
+    // make every build differ.
+    let build_date = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<i64>().ok())
+        .and_then(|epoch| DateTime::from_timestamp(epoch, 0));
+    if let Some(build_date) = build_date {
+        println!(
+            "cargo:rustc-env=FLOM_BUILD_DATE={}",
+            build_date.format("%Y-%m-%d")
+        );
+    }
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,63 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Commands that print the clipboard as text, tried in order.
+const READERS: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+    &[("pbpaste", &[])]
+} else if cfg!(target_os = "windows") {
+    &[("powershell", &["-NoProfile", "-Command", "Get-Clipboard"])]
+} else {
+    &[
+        ("wl-paste", &["--no-newline"]),
+        ("xclip", &["-selection", "clipboard", "-o"]),
+        ("xsel", &["--clipboard", "--output"]),
+    ]
+};
+
+/// Commands that replace the clipboard with their standard input, tried in order.
+const WRITERS: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+    &[("pbcopy", &[])]
+} else if cfg!(target_os = "windows") {
+    &[("clip", &[])]
+} else {
+    &[
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ]
+};
+
+/// Reads the clipboard as text using the platform's own tooling, or `None`
+/// when no reader is installed or the clipboard holds no text.
+pub fn read_text() -> Option<String> {
+    READERS.iter().find_map(|(program, args)| {
+        let output = Command::new(program).args(*args).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8(output.stdout)
+            .ok()
+            .filter(|text| !text.trim().is_empty())
+    })
+}
+
+/// Puts `text` on the clipboard with the first writer that works. Returns
+/// `false` when none is installed or all of them failed.
+pub fn write_text(text: &str) -> bool {
+    WRITERS.iter().any(|(program, args)| {
+        let Ok(mut child) = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            return false;
+        };
+        let written = child
+            .stdin
+            .take()
+            .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+        child.wait().is_ok_and(|status| status.success()) && written
+    })
+}
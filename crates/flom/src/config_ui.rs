@@ -0,0 +1,155 @@
+//! `flom config ui`: a form over every known config key, for editing the
+//! user config without touching TOML.
+
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal};
+
+use flom_config::{
+    CONFIG_KEYS, SECRET_KEYS, ValueKind, check_config_value, config_sources, update_config_values,
+};
+use flom_core::{FlomError, FlomResult};
+use flom_music::MusicConverter;
+
+use crate::prompt;
+use crate::ui::style;
+
+const UNSET: &str = "(unset)";
+
+pub fn run() -> FlomResult<()> {
+    if !io::stdin().is_terminal() {
+        return Err(FlomError::InvalidInput(
+            "`flom config ui` needs a terminal; use `flom config set` in scripts".to_string(),
+        ));
+    }
+    let current: BTreeMap<String, String> = config_sources()?
+        .into_iter()
+        .map(|entry| (entry.key, entry.value))
+        .collect();
+    // Edits so far: `Some` sets a key, `None` removes it.
+    let mut pending: BTreeMap<&'static str, Option<String>> = BTreeMap::new();
+
+    loop {
+        let mut items: Vec<String> = CONFIG_KEYS
+            .iter()
+            .map(|(key, _)| {
+                let value = match pending.get(key) {
+                    Some(value) => value.as_deref(),
+                    None => current.get(*key).map(String::as_str),
+                };
+                menu_line(key, value, pending.contains_key(key))
+            })
+            .collect();
+        items.push(format!("Save {} change(s) and quit", pending.len()));
+        items.push("Quit without saving".to_string());
+
+        let selection = prompt::select("Config (* = edited)", &items)?;
+        if selection == items.len() - 2 {
+            let changes: Vec<(&str, Option<&str>)> = pending
+                .iter()
+                .map(|(key, value)| (*key, value.as_deref()))
+                .collect();
+            update_config_values(&changes)?;
+            println!("{} Saved {} change(s)", style("✓").green(), changes.len());
+            return Ok(());
+        }
+        if selection == items.len() - 1 {
+            if pending.is_empty() || prompt::confirm("Discard your changes?", false)? {
+                return Ok(());
+            }
+            continue;
+        }
+
+        let (key, kind) = CONFIG_KEYS[selection];
+        let Some(value) = edit(key, kind)? else {
+            continue;
+        };
+        if let Some(value) = &value
+            && let Err(err) = check_config_value(key, value)
+        {
+            eprintln!("{} {err}", style("Invalid:").red());
+            continue;
+        }
+        pending.insert(key, value);
+    }
+}
+
+/// Asks for a new value of `key`: `Ok(None)` keeps it, `Ok(Some(None))` unsets it.
+fn edit(key: &str, kind: ValueKind) -> FlomResult<Option<Option<String>>> {
+    if SECRET_KEYS.contains(&key) {
+        let value = prompt::password(&format!("{key} (empty keeps the current value)"), false)?;
+        return Ok((!value.is_empty()).then_some(Some(value)));
+    }
+    let choose = |choices: Vec<String>| -> FlomResult<Option<Option<String>>> {
+        let mut items = choices.clone();
+        items.push(UNSET.to_string());
+        let selection = prompt::select(key, &items)?;
+        Ok(Some(choices.get(selection).cloned()))
+    };
+    match kind {
+        ValueKind::Bool => choose(vec!["true".to_string(), "false".to_string()]),
+        ValueKind::Choice(choices) => choose(choices.iter().map(|c| c.to_string()).collect()),
+        ValueKind::Platform => {
+            let mut targets = MusicConverter::known_targets();
+            targets.sort_by(|a, b| a.label.cmp(&b.label));
+            let mut keys: Vec<String> = targets.iter().map(|t| t.key.clone()).collect();
+            keys.extend(["all".to_string(), "songlink".to_string()]);
+            let picked =
+                prompt::multi_select(&format!("{key} (space picks, none unsets it)"), &keys)?;
+            Ok(Some(platform_list(&keys, &picked)))
+        }
+        ValueKind::Text | ValueKind::Integer | ValueKind::Number | ValueKind::Country => {
+            let value = prompt::text(&format!("{key} (empty unsets it)"))?;
+            let value = value.trim();
+            Ok(Some((!value.is_empty()).then(|| value.to_string())))
+        }
+    }
+}
+
+/// The value for the platforms picked from `keys`: a comma-separated list,
+/// or `None` to unset it.
+fn platform_list(keys: &[String], picked: &[usize]) -> Option<String> {
+    let list: Vec<&str> = picked.iter().map(|&index| keys[index].as_str()).collect();
+    (!list.is_empty()).then(|| list.join(","))
+}
+
+/// One row of the form, with secrets masked.
+fn menu_line(key: &str, value: Option<&str>, edited: bool) -> String {
+    let marker = if edited { "*" } else { " " };
+    let value = match value {
+        Some(_) if SECRET_KEYS.contains(&key) => "<set>",
+        Some(value) => value,
+        None => UNSET,
+    };
+    format!("{marker} {key} = {value}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picked_platforms_join_into_a_list() {
+        let keys = ["spotify", "tidal", "all"].map(String::from);
+        assert_eq!(
+            platform_list(&keys, &[0, 1]).as_deref(),
+            Some("spotify,tidal")
+        );
+        assert_eq!(platform_list(&keys, &[]), None);
+    }
+
+    #[test]
+    fn rows_mask_secrets() {
+        assert_eq!(
+            menu_line("api.odesli_key", Some("abc123"), false),
+            "  api.odesli_key = <set>"
+        );
+        assert_eq!(
+            menu_line("output.format", Some("json"), true),
+            "* output.format = json"
+        );
+        assert_eq!(
+            menu_line("default.target", None, false),
+            "  default.target = (unset)"
+        );
+    }
+}
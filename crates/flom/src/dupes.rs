@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use flom_music::api::odesli::OdesliResponse;
+use flom_music::extract::extract_music_urls;
+use flom_music::matching::{MatchThresholds, TrackRef};
+use flom_music::metadata::{artist_key, title_key};
+use serde::Serialize;
+
+/// One entry of a track list, as read from a CSV export or a plain list of links.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Track {
+    /// 1-based line in the input.
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isrc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artist: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+impl Track {
+    /// Fills in title and artist from a resolved Odesli response.
+    pub fn complete_from(&mut self, response: &OdesliResponse) {
+        let Some(entity) = response
+            .entities_by_unique_id
+            .get(&response.entity_unique_id)
+        else {
+            return;
+        };
+        if self.title.is_none() {
+            self.title = entity.title.clone();
+        }
+        if self.artist.is_none() {
+            self.artist = entity.artist_name.clone();
+        }
+    }
+
+    pub fn as_ref(&self) -> Option<TrackRef<'_>> {
+        Some(TrackRef {
+            title: self.title.as_deref()?,
+            artist: self.artist.as_deref()?,
+            duration: self.duration_ms.map(Duration::from_millis),
+        })
+    }
+
+    pub fn summary(&self) -> String {
+        let title = self.title.as_deref().unwrap_or("Unknown title");
+        let artist = self.artist.as_deref().unwrap_or("Unknown artist");
+        format!("{title} / {artist}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    Isrc,
+    TitleArtist,
+    /// Similar but not identical title and artist, see [`flom_music::matching`].
+    Fuzzy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    #[serde(rename = "match")]
+    pub kind: MatchKind,
+    /// The shared ISRC or normalized "title - artist" key (of the first track
+    /// for fuzzy matches).
+    pub key: String,
+    pub tracks: Vec<Track>,
+}
+
+/// How far down a CSV export may start; Shazam puts a title line above the header.
+const HEADER_SEARCH_LINES: usize = 3;
+
+/// Reads tracks from a CSV export with a header row (Exportify, Shazam and
+/// similar), or from any other text by picking out one music link per line.
+pub fn parse_tracks(content: &str) -> Vec<Track> {
+    let mut lines = content.lines().enumerate();
+    let header = lines
+        .by_ref()
+        .take(HEADER_SEARCH_LINES)
+        .map(|(_, line)| Columns::from_header(&split_csv_line(line)))
+        .find(Columns::is_useful);
+    let Some(columns) = header else {
+        return content
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let url = extract_music_urls(line).into_iter().next()?;
+                Some(Track {
+                    line: index + 1,
+                    url: Some(url),
+                    ..Track::default()
+                })
+            })
+            .collect();
+    };
+    lines
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| columns.track(index + 1, &split_csv_line(line)))
+        .collect()
+}
+
+/// Groups tracks sharing an ISRC, then by normalized title and main artist, and
+/// finally tracks still on their own that fuzzily match each other.
+pub fn find_duplicates(tracks: &[Track], thresholds: &MatchThresholds) -> Vec<DuplicateGroup> {
+    let mut groups = Vec::new();
+    let mut grouped = vec![false; tracks.len()];
+
+    for (key, indices) in group_by(tracks, |track| {
+        track.isrc.as_ref().map(|isrc| isrc.trim().to_uppercase())
+    }) {
+        for &index in &indices {
+            grouped[index] = true;
+        }
+        groups.push(DuplicateGroup {
+            kind: MatchKind::Isrc,
+            key,
+            tracks: indices.iter().map(|&i| tracks[i].clone()).collect(),
+        });
+    }
+
+    // A track already matched by ISRC can still pull in an entry that lacks one.
+    for (key, indices) in group_by(tracks, title_artist_key) {
+        if indices.iter().all(|&index| grouped[index]) {
+            continue;
+        }
+        for &index in &indices {
+            grouped[index] = true;
+        }
+        groups.push(DuplicateGroup {
+            kind: MatchKind::TitleArtist,
+            key,
+            tracks: indices.iter().map(|&i| tracks[i].clone()).collect(),
+        });
+    }
+
+    for (index, track) in tracks.iter().enumerate() {
+        let Some(reference) = track.as_ref().filter(|_| !grouped[index]) else {
+            continue;
+        };
+        let mut indices = vec![index];
+        for (other, candidate) in tracks.iter().enumerate().skip(index + 1) {
+            if !grouped[other]
+                && let Some(candidate) = candidate.as_ref()
+                && thresholds.is_match(&reference, &candidate)
+            {
+                indices.push(other);
+            }
+        }
+        if indices.len() < 2 {
+            continue;
+        }
+        for &index in &indices {
+            grouped[index] = true;
+        }
+        groups.push(DuplicateGroup {
+            kind: MatchKind::Fuzzy,
+            key: title_artist_key(track).unwrap_or_default(),
+            tracks: indices.iter().map(|&i| tracks[i].clone()).collect(),
+        });
+    }
+    groups
+}
+
+/// Keys with more than one track, in order of first appearance.
+fn group_by(tracks: &[Track], key: impl Fn(&Track) -> Option<String>) -> Vec<(String, Vec<usize>)> {
+    let mut order = Vec::new();
+    let mut by_key: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, track) in tracks.iter().enumerate() {
+        let Some(key) = key(track).filter(|key| !key.is_empty()) else {
+            continue;
+        };
+        let entry = by_key.entry(key.clone()).or_default();
+        if entry.is_empty() {
+            order.push(key);
+        }
+        entry.push(index);
+    }
+    order
+        .into_iter()
+        .filter_map(|key| {
+            let indices = by_key.remove(&key)?;
+            (indices.len() > 1).then_some((key, indices))
+        })
+        .collect()
+}
+
+fn title_artist_key(track: &Track) -> Option<String> {
+    let title = title_key(track.title.as_deref()?);
+    let artist = artist_key(track.artist.as_deref()?);
+    if title.is_empty() || artist.is_empty() {
+        return None;
+    }
+    Some(format!("{title} - {artist}"))
+}
+
+#[derive(Debug, Default)]
+struct Columns {
+    url: Option<usize>,
+    isrc: Option<usize>,
+    title: Option<usize>,
+    artist: Option<usize>,
+    duration_ms: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(header: &[String]) -> Self {
+        let mut columns = Self::default();
+        for (index, name) in header.iter().enumerate() {
+            let slot = match name.trim().to_lowercase().as_str() {
+                "url" | "link" | "track uri" | "spotify uri" | "uri" => &mut columns.url,
+                "isrc" => &mut columns.isrc,
+                "title" | "track name" | "track" | "name" | "song" => &mut columns.title,
+                "artist" | "artists" | "artist name(s)" | "artist name" => &mut columns.artist,
+                "duration (ms)" | "duration_ms" | "track duration (ms)" => &mut columns.duration_ms,
+                _ => continue,
+            };
+            slot.get_or_insert(index);
+        }
+        columns
+    }
+
+    fn is_useful(&self) -> bool {
+        self.url.is_some() || self.isrc.is_some() || self.title.is_some()
+    }
+
+    fn track(&self, line: usize, fields: &[String]) -> Track {
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| fields.get(index))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Track {
+            line,
+            url: field(self.url).map(|url| spotify_uri_to_url(&url)),
+            isrc: field(self.isrc),
+            title: field(self.title),
+            artist: field(self.artist),
+            duration_ms: field(self.duration_ms).and_then(|ms| ms.parse().ok()),
+        }
+    }
+}
+
+/// Exportify writes `spotify:track:<id>` URIs rather than links.
+fn spotify_uri_to_url(value: &str) -> String {
+    match value.strip_prefix("spotify:track:") {
+        Some(id) => format!("https://open.spotify.com/track/{id}"),
+        None => value.to_string(),
+    }
+}
+
+/// Splits one CSV record, honouring double-quoted fields and `""` escapes.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_exportify_csv() {
+        let csv = "\
+\"Track URI\",\"Track Name\",\"Artist Name(s)\",\"ISRC\"
+\"spotify:track:abc\",\"Hello, Again\",\"Ann, Bob\",\"USABC1234567\"
+
+\"spotify:track:def\",\"Other\",\"Cid\",\"\"
+";
+        let tracks = parse_tracks(csv);
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks[0].line, 2);
+        assert_eq!(
+            tracks[0].url.as_deref(),
+            Some("https://open.spotify.com/track/abc")
+        );
+        assert_eq!(tracks[0].title.as_deref(), Some("Hello, Again"));
+        assert_eq!(tracks[0].isrc.as_deref(), Some("USABC1234567"));
+        assert_eq!(tracks[1].line, 4);
+        assert_eq!(tracks[1].isrc, None);
+    }
+
+    #[test]
+    fn finds_header_below_a_title_line() {
+        let csv = "\
+Shazam Library
+Index,TagTime,Title,Artist,URL,TrackKey
+1,2024-05-01,Mr. Brightside,The Killers,https://www.shazam.com/track/123,123
+";
+        let tracks = parse_tracks(csv);
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].line, 3);
+        assert_eq!(tracks[0].artist.as_deref(), Some("The Killers"));
+    }
+
+    #[test]
+    fn plain_lists_use_one_link_per_line() {
+        let tracks =
+            parse_tracks("my likes\nhttps://youtu.be/x\n\nsee https://open.spotify.com/track/y\n");
+        let lines: Vec<_> = tracks.iter().map(|t| t.line).collect();
+        assert_eq!(lines, vec![2, 4]);
+    }
+
+    #[test]
+    fn finds_isrc_and_title_artist_duplicates() {
+        let track = |line, isrc: Option<&str>, title: &str, artist: &str| Track {
+            line,
+            url: None,
+            isrc: isrc.map(str::to_string),
+            title: Some(title.to_string()),
+            artist: Some(artist.to_string()),
+            duration_ms: None,
+        };
+        let tracks = [
+            track(1, Some("usabc1"), "Song", "Ann"),
+            track(2, Some("USABC1"), "Song (Live)", "Ann"),
+            track(3, None, "Café - 2011 Remaster", "Béa feat. Cid"),
+            track(4, None, "CAFE", "Bea & Dan"),
+            track(5, None, "Song", "Ann"),
+            track(6, None, "Different", "Ann"),
+        ];
+        let groups = find_duplicates(&tracks, &MatchThresholds::default());
+        let summary: Vec<_> = groups
+            .iter()
+            .map(|g| {
+                (
+                    g.kind,
+                    g.key.as_str(),
+                    g.tracks.iter().map(|t| t.line).collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (MatchKind::Isrc, "USABC1", vec![1, 2]),
+                (MatchKind::TitleArtist, "song - ann", vec![1, 2, 5]),
+                (MatchKind::TitleArtist, "cafe - bea", vec![3, 4]),
+            ]
+        );
+    }
+
+    #[test]
+    fn fuzzy_matches_respect_durations() {
+        let track = |line, title: &str, artist: &str, duration_ms| Track {
+            line,
+            title: Some(title.to_string()),
+            artist: Some(artist.to_string()),
+            duration_ms: Some(duration_ms),
+            ..Track::default()
+        };
+        let tracks = [
+            track(1, "Mr. Brightside", "The Killers", 222_000),
+            track(2, "Mr Brightside (2004)", "Killers", 223_500),
+            track(3, "Mr Brightside", "Killers", 320_000),
+        ];
+        let groups = find_duplicates(&tracks, &MatchThresholds::default());
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].kind, MatchKind::Fuzzy);
+        let lines: Vec<_> = groups[0].tracks.iter().map(|t| t.line).collect();
+        assert_eq!(lines, vec![1, 2]);
+    }
+}
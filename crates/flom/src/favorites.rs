@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use flom_config::flom_dir;
+use flom_core::{FlomError, FlomResult};
+use flom_music::api::odesli::OdesliResponse;
+use serde::{Deserialize, Serialize};
+
+use crate::output::csv_field;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Favorite {
+    pub id: u64,
+    pub url: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Links to the same track keyed by Odesli platform key.
+    #[serde(default)]
+    pub links: BTreeMap<String, String>,
+    pub added_at: DateTime<Utc>,
+}
+
+impl Favorite {
+    pub fn from_response(url: &str, response: &OdesliResponse, tags: Vec<String>) -> Self {
+        let entity = response
+            .entities_by_unique_id
+            .get(&response.entity_unique_id);
+        Self {
+            id: 0,
+            url: url.to_string(),
+            title: entity.and_then(|entity| entity.title.clone()),
+            artist: entity.and_then(|entity| entity.artist_name.clone()),
+            album: entity.and_then(|entity| entity.album_name.clone()),
+            tags,
+            links: response
+                .links_by_platform
+                .iter()
+                .map(|(key, link)| (key.clone(), link.url.clone()))
+                .collect(),
+            added_at: Utc::now(),
+        }
+    }
+
+    /// The link on `target`, or the bookmarked URL without a target.
+    pub fn link(&self, target: Option<&str>) -> FlomResult<&str> {
+        match target {
+            None => Ok(&self.url),
+            Some(target) => self.links.get(target).map(String::as_str).ok_or_else(|| {
+                FlomError::UnsupportedInput(format!("favorite #{} has no {target} link", self.id))
+            }),
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Favorites {
+    #[serde(default)]
+    pub entries: Vec<Favorite>,
+}
+
+impl Favorites {
+    pub fn add(&mut self, mut favorite: Favorite) -> u64 {
+        let id = self.entries.iter().map(|f| f.id).max().unwrap_or(0) + 1;
+        favorite.id = id;
+        self.entries.push(favorite);
+        id
+    }
+
+    pub fn get(&self, id: u64) -> FlomResult<&Favorite> {
+        self.entries
+            .iter()
+            .find(|f| f.id == id)
+            .ok_or_else(|| FlomError::InvalidInput(format!("no favorite with id {id}")))
+    }
+
+    pub fn contains_url(&self, url: &str) -> bool {
+        self.entries.iter().any(|f| f.url == url)
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<Favorite> {
+        let index = self.entries.iter().position(|f| f.id == id)?;
+        Some(self.entries.remove(index))
+    }
+
+    pub fn filtered<'a>(&'a self, tag: Option<&'a str>) -> impl Iterator<Item = &'a Favorite> {
+        self.entries
+            .iter()
+            .filter(move |f| tag.is_none_or(|tag| f.has_tag(tag)))
+    }
+}
+
+pub fn favorites_path() -> FlomResult<PathBuf> {
+    Ok(flom_dir()?.join("favorites.json"))
+}
+
+pub fn load() -> FlomResult<Favorites> {
+    let path = favorites_path()?;
+    if !path.exists() {
+        return Ok(Favorites::default());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|err| FlomError::Config(format!("failed to read favorites: {err}")))?;
+    serde_json::from_str(&content)
+        .map_err(|err| FlomError::Parse(format!("failed to parse favorites: {err}")))
+}
+
+pub fn save(favorites: &Favorites) -> FlomResult<()> {
+    let path = favorites_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| FlomError::Config(format!("failed to create favorites dir: {err}")))?;
+    }
+    let content = serde_json::to_string_pretty(favorites)
+        .map_err(|err| FlomError::Parse(format!("failed to serialize favorites: {err}")))?;
+    fs::write(&path, content)
+        .map_err(|err| FlomError::Config(format!("failed to write favorites: {err}")))
+}
+
+/// Renders favorites as CSV rows with the link for `target` (or the original URL).
+pub fn export_csv<'a>(
+    favorites: impl Iterator<Item = &'a Favorite>,
+    target: Option<&str>,
+) -> String {
+    let mut out = String::from("title,artist,album,url,tags\n");
+    for favorite in favorites {
+        let url = target
+            .and_then(|target| favorite.links.get(target))
+            .unwrap_or(&favorite.url);
+        let row = [
+            favorite.title.as_deref().unwrap_or(""),
+            favorite.artist.as_deref().unwrap_or(""),
+            favorite.album.as_deref().unwrap_or(""),
+            url,
+            &favorite.tags.join(";"),
+        ]
+        .map(csv_field)
+        .join(",");
+        out.push_str(&row);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn favorite(title: &str, tags: &[&str]) -> Favorite {
+        Favorite {
+            id: 0,
+            url: "https://open.spotify.com/track/abc".to_string(),
+            title: Some(title.to_string()),
+            artist: Some("Artist".to_string()),
+            album: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            links: BTreeMap::from([(
+                "appleMusic".to_string(),
+                "https://music.apple.com/x".to_string(),
+            )]),
+            added_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn add_assigns_increasing_ids_and_remove() {
+        let mut favorites = Favorites::default();
+        assert_eq!(favorites.add(favorite("One", &[])), 1);
+        assert_eq!(favorites.add(favorite("Two", &[])), 2);
+        assert!(favorites.remove(1).is_some());
+        assert_eq!(favorites.add(favorite("Three", &[])), 3);
+        assert!(favorites.remove(42).is_none());
+    }
+
+    #[test]
+    fn link_picks_the_target_platform() {
+        let mut favorites = Favorites::default();
+        let id = favorites.add(favorite("One", &[]));
+        let favorite = favorites.get(id).unwrap();
+        assert_eq!(
+            favorite.link(None).unwrap(),
+            "https://open.spotify.com/track/abc"
+        );
+        assert_eq!(
+            favorite.link(Some("appleMusic")).unwrap(),
+            "https://music.apple.com/x"
+        );
+        assert!(favorite.link(Some("tidal")).is_err());
+        assert!(favorites.get(id + 1).is_err());
+        assert!(favorites.contains_url("https://open.spotify.com/track/abc"));
+    }
+
+    #[test]
+    fn filtered_by_tag_is_case_insensitive() {
+        let mut favorites = Favorites::default();
+        favorites.add(favorite("One", &["Chill"]));
+        favorites.add(favorite("Two", &["party"]));
+        let titles: Vec<_> = favorites
+            .filtered(Some("chill"))
+            .map(|f| f.title.clone().unwrap())
+            .collect();
+        assert_eq!(titles, vec!["One"]);
+        assert_eq!(favorites.filtered(None).count(), 2);
+    }
+
+    #[test]
+    fn csv_snapshot() {
+        let favorites: Vec<_> = crate::fixtures::results()
+            .into_iter()
+            .map(|result| {
+                let info = result.source_info.unwrap_or_default();
+                Favorite {
+                    id: 0,
+                    url: result.source_url,
+                    title: info.title,
+                    artist: info.artist,
+                    album: info.album,
+                    tags: vec!["fixture".to_string()],
+                    links: result
+                        .target_platform
+                        .zip(result.target_url)
+                        .into_iter()
+                        .collect(),
+                    added_at: Utc::now(),
+                }
+            })
+            .collect();
+        insta::assert_snapshot!(export_csv(favorites.iter(), Some("spotify")));
+    }
+
+    #[test]
+    fn export_csv_uses_target_link_and_escapes() {
+        let favorites = [favorite("Hello, World", &["a", "b"])];
+        let csv = export_csv(favorites.iter(), Some("appleMusic"));
+        assert_eq!(
+            csv,
+            "title,artist,album,url,tags\n\"Hello, World\",Artist,,https://music.apple.com/x,a;b\n"
+        );
+
+        let csv = export_csv(favorites.iter(), Some("tidal"));
+        assert!(csv.contains("https://open.spotify.com/track/abc"));
+    }
+}
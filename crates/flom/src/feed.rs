@@ -0,0 +1,205 @@
+use flom_core::{ConversionResult, FlomError, FlomResult};
+use flom_music::extract::extract_music_urls;
+use regex::Regex;
+use serde::Serialize;
+
+/// An RSS `<item>` or Atom `<entry>` that links to music.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedEntry {
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub urls: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DigestEntry {
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub results: Vec<ConversionResult>,
+    pub failures: Vec<DigestFailure>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DigestFailure {
+    pub url: String,
+    pub error: String,
+}
+
+impl DigestEntry {
+    pub fn new(entry: &FeedEntry) -> Self {
+        Self {
+            title: entry.title.clone(),
+            link: entry.link.clone(),
+            results: Vec::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, url: &str, outcome: FlomResult<Vec<ConversionResult>>) {
+        match outcome {
+            Ok(results) => self.results.extend(results),
+            Err(err) => self.failures.push(DigestFailure {
+                url: url.to_string(),
+                error: err.to_string(),
+            }),
+        }
+    }
+}
+
+/// Extracts entries with music links from an RSS or Atom document. This is a
+/// lenient scan rather than a full XML parse; feeds in the wild are rarely valid.
+pub fn parse_feed(xml: &str) -> FlomResult<Vec<FeedEntry>> {
+    let item_re =
+        Regex::new(r"(?s)<(item|entry)\b[^>]*>(.*?)</(?:item|entry)>").expect("valid item regex");
+    let title_re = Regex::new(r"(?s)<title\b[^>]*>(.*?)</title>").expect("valid title regex");
+    let rss_link_re = Regex::new(r"(?s)<link>(.*?)</link>").expect("valid link regex");
+    let atom_link_re =
+        Regex::new(r#"<link\b[^>]*\bhref="([^"]*)""#).expect("valid atom link regex");
+
+    if !xml.contains("<rss") && !xml.contains("<feed") && !xml.contains("<rdf:RDF") {
+        return Err(FlomError::Parse("not an RSS or Atom feed".to_string()));
+    }
+
+    let entries = item_re
+        .captures_iter(xml)
+        .filter_map(|caps| {
+            let body = &caps[2];
+            let urls = extract_music_urls(&unescape(body));
+            if urls.is_empty() {
+                return None;
+            }
+            let title = title_re
+                .captures(body)
+                .map(|caps| unescape(caps[1].trim()).trim().to_string());
+            let link = rss_link_re
+                .captures(body)
+                .or_else(|| atom_link_re.captures(body))
+                .map(|caps| unescape(caps[1].trim()).trim().to_string());
+            Some(FeedEntry { title, link, urls })
+        })
+        .collect();
+    Ok(entries)
+}
+
+/// Removes CDATA wrappers and decodes the entities that matter for links; entry
+/// bodies are often HTML escaped once more inside the XML.
+fn unescape(text: &str) -> String {
+    let mut text = text.replace("<![CDATA[", "").replace("]]>", "");
+    for _ in 0..2 {
+        text = text
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&#39;", "'")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&");
+    }
+    text
+}
+
+pub fn render_markdown(feed_url: &str, digest: &[DigestEntry]) -> String {
+    let mut out = format!("# Music from {feed_url}\n");
+    for entry in digest {
+        let title = entry.title.as_deref().unwrap_or("Untitled");
+        match &entry.link {
+            Some(link) => out.push_str(&format!("\n## [{title}]({link})\n\n")),
+            None => out.push_str(&format!("\n## {title}\n\n")),
+        }
+        for result in &entry.results {
+            let info = result.source_info.as_ref();
+            let name = match (
+                info.and_then(|info| info.title.as_deref()),
+                info.and_then(|info| info.artist.as_deref()),
+            ) {
+                (Some(title), Some(artist)) => format!("{title} – {artist}"),
+                (Some(title), None) => title.to_string(),
+                _ => result.source_url.clone(),
+            };
+            match &result.target_url {
+                Some(url) => out.push_str(&format!("- [{name}]({url})\n")),
+                None => out.push_str(&format!("- {name}: no target link\n")),
+            }
+        }
+        for failure in &entry.failures {
+            out.push_str(&format!("- {}: failed ({})\n", failure.url, failure.error));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rss_items_with_escaped_html() {
+        let xml = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Blog</title>
+<item>
+  <title>Friday picks &amp; more</title>
+  <link>https://blog.example/friday</link>
+  <description>&lt;p&gt;Listen: &lt;a href="https://open.spotify.com/track/abc?si=1&amp;amp;x=2"&gt;here&lt;/a&gt;&lt;/p&gt;</description>
+</item>
+<item><title>No music</title><link>https://blog.example/news</link></item>
+</channel></rss>"#;
+        let entries = parse_feed(xml).unwrap();
+        assert_eq!(
+            entries,
+            vec![FeedEntry {
+                title: Some("Friday picks & more".to_string()),
+                link: Some("https://blog.example/friday".to_string()),
+                urls: vec!["https://open.spotify.com/track/abc?si=1&x=2".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_atom_entries() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+<entry>
+  <title type="html"><![CDATA[New single]]></title>
+  <link rel="alternate" href="https://blog.example/single"/>
+  <content type="html"><![CDATA[<a href="https://youtu.be/xyz">Video</a>]]></content>
+</entry>
+</feed>"#;
+        let entries = parse_feed(xml).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title.as_deref(), Some("New single"));
+        assert_eq!(
+            entries[0].link.as_deref(),
+            Some("https://blog.example/single")
+        );
+        assert_eq!(entries[0].urls, vec!["https://youtu.be/xyz"]);
+    }
+
+    #[test]
+    fn markdown_snapshot() {
+        let mut entry = DigestEntry::new(&FeedEntry {
+            title: Some("Friday picks".to_string()),
+            link: Some("https://blog.example/friday".to_string()),
+            urls: Vec::new(),
+        });
+        entry.push("", Ok(crate::fixtures::results()));
+        entry.push(
+            "https://youtu.be/gone",
+            Err(FlomError::Api("status=404".to_string())),
+        );
+        let untitled = DigestEntry::new(&FeedEntry {
+            title: None,
+            link: None,
+            urls: Vec::new(),
+        });
+        insta::assert_snapshot!(render_markdown(
+            "https://blog.example/feed.xml",
+            &[entry, untitled]
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_non_feed() {
+        assert!(matches!(
+            parse_feed("<html><body></body></html>"),
+            Err(FlomError::Parse(_))
+        ));
+    }
+}
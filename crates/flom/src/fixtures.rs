@@ -0,0 +1,71 @@
+//! Canned results shared by the formatter snapshot tests.
+
+use flom_core::{ConversionResult, MediaInfo, MediaType};
+
+/// Results covering full metadata, a missing target, no metadata at all and
+/// characters that need escaping in some formats.
+pub fn results() -> Vec<ConversionResult> {
+    vec![
+        ConversionResult {
+            source_url: "https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT".to_string(),
+            target_url: Some("https://music.apple.com/us/album/x/1440?i=1441".to_string()),
+            source_platform: Some("spotify".to_string()),
+            target_platform: Some("appleMusic".to_string()),
+            source_id: Some("4cOdK2wGLETKBW3PvgPWqT".to_string()),
+            target_id: Some("1441".to_string()),
+            source_info: Some(MediaInfo {
+                title: Some("Never Gonna Give You Up".to_string()),
+                artist: Some("Rick Astley".to_string()),
+                album: Some("Whenever You Need Somebody".to_string()),
+                kind: Some("song".to_string()),
+                duration_ms: Some(213_573),
+                artwork_url: Some("https://i.scdn.co/image/ab67616d0000b273".to_string()),
+                isrc: Some("GBARL9300135".to_string()),
+                release_date: Some("1987-11-12".to_string()),
+                release_year: Some(1987),
+                label: Some("RCA".to_string()),
+            }),
+            target_info: None,
+            page_url: Some("https://song.link/s/4cOdK2wGLETKBW3PvgPWqT".to_string()),
+            media_type: Some(MediaType::Song),
+            warning: None,
+        },
+        ConversionResult {
+            source_url: "https://youtu.be/abc123".to_string(),
+            target_url: None,
+            source_platform: Some("youtube".to_string()),
+            target_platform: Some("tidal".to_string()),
+            source_info: Some(MediaInfo {
+                title: Some("夜に駆ける".to_string()),
+                artist: None,
+                ..Default::default()
+            }),
+            warning: Some("matched by title only".to_string()),
+            ..Default::default()
+        },
+        ConversionResult {
+            source_url: "https://music.apple.com/us/album/y/2?i=3".to_string(),
+            target_url: Some("https://song.link/i/3".to_string()),
+            target_platform: Some("songlink".to_string()),
+            ..Default::default()
+        },
+        ConversionResult {
+            source_url: "https://www.deezer.com/track/42".to_string(),
+            target_url: Some("https://open.spotify.com/track/def".to_string()),
+            source_platform: Some("deezer".to_string()),
+            target_platform: Some("spotify".to_string()),
+            source_info: Some(MediaInfo {
+                title: Some("Hello, \"World\" 100%".to_string()),
+                artist: Some("A & B".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    ]
+}
+
+/// Turns off terminal colours so snapshots don't depend on where tests run.
+pub fn plain_styles() {
+    #[cfg(feature = "color")]
+    console::set_colors_enabled(false);
+}
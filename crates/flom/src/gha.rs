@@ -0,0 +1,86 @@
+//! GitHub Actions workflow commands, see
+//! <https://docs.github.com/actions/reference/workflow-commands-for-github-actions>.
+
+use flom_core::{ConversionResult, FlomError};
+
+/// Position of an input URL in the `--input` file, used to annotate diffs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location<'a> {
+    pub file: &'a str,
+    pub line: usize,
+}
+
+pub fn result_command(result: &ConversionResult, location: Option<Location>) -> String {
+    match (&result.target_url, &result.warning) {
+        (Some(target_url), None) => command(
+            "notice",
+            "Converted",
+            location,
+            &format!("{} -> {target_url}", result.source_url),
+        ),
+        (Some(target_url), Some(warning)) => command(
+            "warning",
+            "Converted with warning",
+            location,
+            &format!("{} -> {target_url}: {warning}", result.source_url),
+        ),
+        (None, _) => command(
+            "warning",
+            "No target link",
+            location,
+            &format!("{}: no target url", result.source_url),
+        ),
+    }
+}
+
+pub fn failure_command(url: &str, err: &FlomError, location: Option<Location>) -> String {
+    command(
+        "error",
+        "Conversion failed",
+        location,
+        &format!("{url}: {err}"),
+    )
+}
+
+fn command(level: &str, title: &str, location: Option<Location>, message: &str) -> String {
+    let mut properties = Vec::new();
+    if let Some(location) = location {
+        properties.push(format!("file={}", escape_property(location.file)));
+        properties.push(format!("line={}", location.line));
+    }
+    properties.push(format!("title={}", escape_property(title)));
+    format!(
+        "::{level} {}::{}",
+        properties.join(","),
+        escape_data(message)
+    )
+}
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failure_command_with_location_escapes() {
+        let err = FlomError::Api("odesli error: status=404\nnot found".to_string());
+        let location = Location {
+            file: "docs/links,v2.md",
+            line: 7,
+        };
+        assert_eq!(
+            failure_command("https://x", &err, Some(location)),
+            "::error file=docs/links%2Cv2.md,line=7,title=Conversion failed::https://x: api error: odesli error: status=404%0Anot found"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use flom_config::flom_dir;
+use flom_core::{ConversionResult, FlomError, FlomResult};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// 1-based position in the history file; assigned on load.
+    #[serde(skip)]
+    pub id: usize,
+    pub recorded_at: DateTime<Utc>,
+    /// Batch label given with `--label`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub result: ConversionResult,
+}
+
+impl HistoryEntry {
+    pub fn summary(&self) -> String {
+        let info = self.result.source_info.as_ref();
+        let title = info
+            .and_then(|info| info.title.as_deref())
+            .unwrap_or("Unknown title");
+        let artist = info
+            .and_then(|info| info.artist.as_deref())
+            .unwrap_or("Unknown artist");
+        let target = self.result.target_platform.as_deref().unwrap_or("unknown");
+        format!("{title} / {artist} -> {target}")
+    }
+}
+
+pub fn history_path() -> FlomResult<PathBuf> {
+    Ok(flom_dir()?.join("history.jsonl"))
+}
+
+pub fn append(results: &[ConversionResult], label: Option<&str>) -> FlomResult<()> {
+    if results.is_empty() {
+        return Ok(());
+    }
+    let path = history_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| FlomError::Config(format!("failed to create history dir: {err}")))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|err| FlomError::Config(format!("failed to open history: {err}")))?;
+
+    let recorded_at = Utc::now();
+    for result in results {
+        let entry = HistoryEntry {
+            id: 0,
+            recorded_at,
+            label: label.map(str::to_string),
+            result: result.clone(),
+        };
+        let line = serde_json::to_string(&entry)
+            .map_err(|err| FlomError::Parse(format!("failed to serialize history: {err}")))?;
+        writeln!(file, "{line}")
+            .map_err(|err| FlomError::Config(format!("failed to write history: {err}")))?;
+    }
+    Ok(())
+}
+
+pub fn load() -> FlomResult<Vec<HistoryEntry>> {
+    let path = history_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|err| FlomError::Config(format!("failed to read history: {err}")))?;
+    Ok(parse_entries(&content))
+}
+
+pub fn filter_by_label<'a>(
+    entries: &'a [HistoryEntry],
+    label: Option<&'a str>,
+) -> impl Iterator<Item = &'a HistoryEntry> {
+    entries
+        .iter()
+        .filter(move |entry| label.is_none_or(|label| entry.label.as_deref() == Some(label)))
+}
+
+/// Entries whose title, artist or album contains every word of `query`,
+/// ignoring case.
+pub fn search<'a>(
+    entries: &'a [HistoryEntry],
+    query: &str,
+) -> impl Iterator<Item = &'a HistoryEntry> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    entries.iter().filter(move |entry| {
+        let text = searchable_text(&entry.result);
+        words.iter().all(|word| text.contains(word.as_str()))
+    })
+}
+
+pub fn find(entries: &[HistoryEntry], id: usize) -> FlomResult<&HistoryEntry> {
+    entries
+        .iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| FlomError::InvalidInput(format!("no history entry #{id}")))
+}
+
+fn searchable_text(result: &ConversionResult) -> String {
+    [&result.source_info, &result.target_info]
+        .into_iter()
+        .flatten()
+        .flat_map(|info| [&info.title, &info.artist, &info.album])
+        .flatten()
+        .map(|text| text.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parses history lines, skipping any that are malformed.
+fn parse_entries(content: &str) -> Vec<HistoryEntry> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let mut entry = serde_json::from_str::<HistoryEntry>(line).ok()?;
+            entry.id = index + 1;
+            Some(entry)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_entries_assigns_line_ids_and_skips_garbage() {
+        let content = concat!(
+            r#"{"recorded_at":"2024-05-01T12:00:00Z","source_url":"https://a","target_url":"https://b","source_platform":"spotify","target_platform":"appleMusic","source_id":null,"target_id":null,"source_info":{"title":"Song","artist":"Artist","album":null},"target_info":null,"warning":null}"#,
+            "\n",
+            "not json\n",
+            r#"{"recorded_at":"2024-05-02T12:00:00Z","label":"road-trip","source_url":"https://c","target_url":null,"source_platform":null,"target_platform":"songlink","source_id":null,"target_id":null,"source_info":null,"target_info":null,"warning":null}"#,
+            "\n",
+        );
+        let entries = parse_entries(content);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, 1);
+        assert_eq!(entries[0].summary(), "Song / Artist -> appleMusic");
+        assert_eq!(entries[1].id, 3);
+        assert_eq!(entries[1].result.source_url, "https://c");
+        assert_eq!(entries[1].label.as_deref(), Some("road-trip"));
+    }
+
+    #[test]
+    fn filter_by_label_matches_exactly() {
+        let content = concat!(
+            r#"{"recorded_at":"2024-05-01T12:00:00Z","label":"a","source_url":"https://a","target_url":null,"source_platform":null,"target_platform":null,"source_id":null,"target_id":null,"source_info":null,"target_info":null,"warning":null}"#,
+            "\n",
+            r#"{"recorded_at":"2024-05-01T12:00:00Z","source_url":"https://b","target_url":null,"source_platform":null,"target_platform":null,"source_id":null,"target_id":null,"source_info":null,"target_info":null,"warning":null}"#,
+            "\n",
+        );
+        let entries = parse_entries(content);
+        let labeled: Vec<_> = filter_by_label(&entries, Some("a")).collect();
+        assert_eq!(labeled.len(), 1);
+        assert_eq!(labeled[0].result.source_url, "https://a");
+        assert_eq!(filter_by_label(&entries, None).count(), 2);
+    }
+
+    #[test]
+    fn search_matches_every_word_in_any_order() {
+        let content = concat!(
+            r#"{"recorded_at":"2024-05-01T12:00:00Z","source_url":"https://a","target_url":null,"source_platform":null,"target_platform":null,"source_id":null,"target_id":null,"source_info":{"title":"One More Time","artist":"Daft Punk","album":"Discovery"},"target_info":null,"warning":null}"#,
+            "\n",
+            r#"{"recorded_at":"2024-05-01T12:00:00Z","source_url":"https://b","target_url":null,"source_platform":null,"target_platform":null,"source_id":null,"target_id":null,"source_info":{"title":"Time","artist":"Pink Floyd","album":null},"target_info":null,"warning":null}"#,
+            "\n",
+        );
+        let entries = parse_entries(content);
+        let urls = |query: &str| {
+            search(&entries, query)
+                .map(|entry| entry.result.source_url.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(urls("time"), ["https://a", "https://b"]);
+        assert_eq!(urls("punk TIME"), ["https://a"]);
+        assert_eq!(urls("discovery"), ["https://a"]);
+        assert!(urls("floyd punk").is_empty());
+        assert_eq!(find(&entries, 2).unwrap().result.source_url, "https://b");
+        assert!(find(&entries, 3).is_err());
+    }
+}
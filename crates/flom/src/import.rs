@@ -0,0 +1,95 @@
+//! Track lists exported from recognition apps (Shazam, SoundHound), which name
+//! tracks by title and artist rather than by a streaming link.
+
+use flom_core::{FlomError, FlomResult, validate_music_url};
+use serde_json::Value;
+
+use crate::dupes::{Track, parse_tracks};
+
+/// Field names holding the artist; Shazam's own JSON calls it the subtitle.
+const ARTIST_FIELDS: [&str; 3] = ["artist", "subtitle", "artistName"];
+
+/// Reads a CSV or JSON export. JSON may nest the tracks anywhere, as long as
+/// each is an object with a `title` and an artist field; `line` then counts
+/// tracks rather than lines.
+pub fn parse_export(content: &str) -> FlomResult<Vec<Track>> {
+    let trimmed = content.trim_start();
+    if !trimmed.starts_with(['{', '[']) {
+        return Ok(parse_tracks(content));
+    }
+    let value: Value = serde_json::from_str(trimmed)
+        .map_err(|err| FlomError::Parse(format!("failed to parse export: {err}")))?;
+    let mut tracks = Vec::new();
+    collect_tracks(&value, &mut tracks);
+    Ok(tracks)
+}
+
+/// A streaming link for the track, if the export already has one (Shazam's
+/// own track pages are not).
+pub fn music_url(track: &Track) -> Option<&str> {
+    track
+        .url
+        .as_deref()
+        .filter(|url| validate_music_url(url).is_ok())
+}
+
+fn collect_tracks(value: &Value, tracks: &mut Vec<Track>) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_tracks(item, tracks)),
+        Value::Object(map) => {
+            let text = |key: &str| {
+                map.get(key)
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+            };
+            let title = text("title");
+            let artist = ARTIST_FIELDS.iter().find_map(|key| text(key));
+            if title.is_some() && artist.is_some() {
+                tracks.push(Track {
+                    line: tracks.len() + 1,
+                    url: text("url"),
+                    isrc: text("isrc"),
+                    title,
+                    artist,
+                    ..Track::default()
+                });
+                return;
+            }
+            map.values().for_each(|value| collect_tracks(value, tracks));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_nested_shazam_json() {
+        let json = r#"{"tags": [
+            {"timestamp": 1, "track": {"title": "Mr. Brightside", "subtitle": "The Killers",
+              "url": "https://www.shazam.com/track/123"}},
+            {"timestamp": 2, "track": {"title": "Untitled"}},
+            {"title": "Hello", "artist": "Adele", "url": "https://open.spotify.com/track/abc"}
+        ]}"#;
+        let tracks = parse_export(json).unwrap();
+        let names: Vec<_> = tracks.iter().map(Track::summary).collect();
+        assert_eq!(names, vec!["Mr. Brightside / The Killers", "Hello / Adele"]);
+        assert_eq!(tracks[1].line, 2);
+        assert_eq!(music_url(&tracks[0]), None);
+        assert_eq!(
+            music_url(&tracks[1]),
+            Some("https://open.spotify.com/track/abc")
+        );
+    }
+
+    #[test]
+    fn reads_csv_exports_and_rejects_broken_json() {
+        let tracks = parse_export("Title,Artist\nHello,Adele\n").unwrap();
+        assert_eq!(tracks[0].summary(), "Hello / Adele");
+        assert!(matches!(parse_export("[{"), Err(FlomError::Parse(_))));
+    }
+}
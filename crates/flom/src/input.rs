@@ -0,0 +1,257 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Cursor};
+
+use flom_core::{FlomError, FlomResult, validate_url};
+use reqwest::Client;
+use url::Url;
+
+/// Reads an `--input` source, which is either a local path or an http(s) URL.
+pub async fn read_source(source: &str) -> FlomResult<String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        return fetch(source).await;
+    }
+    fs::read_to_string(source)
+        .map_err(|err| FlomError::InvalidInput(format!("failed to read input file: {err}")))
+}
+
+/// One non-empty, trimmed line of input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputLine {
+    pub text: String,
+    /// 1-based line in the `--input` file, when the text came from one.
+    pub line: Option<usize>,
+}
+
+impl InputLine {
+    pub fn new(text: String) -> Self {
+        Self { text, line: None }
+    }
+}
+
+/// Reads input one line at a time, so huge link dumps never sit in memory whole.
+pub struct Lines {
+    reader: Box<dyn BufRead + Send>,
+    numbered: bool,
+    line: usize,
+    buffer: String,
+    /// Number of non-empty lines, when it can be known up front.
+    total: Option<usize>,
+}
+
+impl Lines {
+    fn new(reader: impl BufRead + Send + 'static, numbered: bool) -> Self {
+        Self {
+            reader: Box::new(reader),
+            numbered,
+            line: 0,
+            buffer: String::new(),
+            total: None,
+        }
+    }
+
+    /// Streams an `--input` source. Local files are read lazily; remote sources
+    /// are small documents and are fetched in one go.
+    pub async fn open(source: &str) -> FlomResult<Self> {
+        if source.starts_with("http://") || source.starts_with("https://") {
+            let content = fetch(source).await?;
+            let total = count_lines(Cursor::new(content.as_bytes()));
+            let mut lines = Self::new(Cursor::new(content.into_bytes()), true);
+            lines.total = total;
+            return Ok(lines);
+        }
+        let file = File::open(source)
+            .map_err(|err| FlomError::InvalidInput(format!("failed to read input file: {err}")))?;
+        // Counting takes a second pass, which only a regular file allows: a
+        // FIFO or `<(cmd)` would be drained by it.
+        let is_file = file.metadata().is_ok_and(|metadata| metadata.is_file());
+        let total = if is_file {
+            File::open(source)
+                .ok()
+                .and_then(|count| count_lines(BufReader::new(count)))
+        } else {
+            None
+        };
+        let mut lines = Self::new(BufReader::new(file), true);
+        lines.total = total;
+        Ok(lines)
+    }
+
+    /// How many lines this yields, for a progress display; `None` for stdin.
+    pub fn total(&self) -> Option<usize> {
+        self.total
+    }
+
+    pub fn stdin() -> Self {
+        Self::new(BufReader::new(io::stdin()), false)
+    }
+}
+
+impl Iterator for Lines {
+    type Item = FlomResult<InputLine>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => self.line += 1,
+                Err(err) => {
+                    return Some(Err(FlomError::InvalidInput(format!(
+                        "failed to read input: {err}"
+                    ))));
+                }
+            }
+            let text = self.buffer.trim();
+            if !text.is_empty() {
+                return Some(Ok(InputLine {
+                    text: text.to_string(),
+                    line: self.numbered.then_some(self.line),
+                }));
+            }
+        }
+    }
+}
+
+fn count_lines(reader: impl BufRead) -> Option<usize> {
+    let mut count = 0;
+    for line in reader.lines() {
+        if !line.ok()?.trim().is_empty() {
+            count += 1;
+        }
+    }
+    Some(count)
+}
+
+async fn fetch(source: &str) -> FlomResult<String> {
+    validate_url(source)?;
+    let url = raw_url(source);
+    let client = Client::builder()
+        .user_agent("flom/0.1")
+        .build()
+        .map_err(|err| FlomError::Network(format!("failed to build http client: {err}")))?;
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|err| FlomError::Network(format!("failed to fetch input: {err}")))?;
+    if !response.status().is_success() {
+        return Err(FlomError::Network(format!(
+            "failed to fetch input: status={} url={url}",
+            response.status()
+        )));
+    }
+    response
+        .text()
+        .await
+        .map_err(|err| FlomError::Network(format!("failed to read input: {err}")))
+}
+
+/// Rewrites gist and GitHub file pages to their raw content.
+fn raw_url(source: &str) -> String {
+    let Ok(url) = Url::parse(source) else {
+        return source.to_string();
+    };
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+    match (url.host_str(), segments.as_slice()) {
+        (Some("gist.github.com"), [user, id]) => {
+            format!("https://gist.githubusercontent.com/{user}/{id}/raw")
+        }
+        (Some("github.com"), [owner, repo, "blob", rest @ ..]) if !rest.is_empty() => {
+            format!(
+                "https://raw.githubusercontent.com/{owner}/{repo}/{}",
+                rest.join("/")
+            )
+        }
+        _ => source.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_skip_blanks_and_keep_numbers() {
+        let lines: Vec<_> = Lines::new(Cursor::new("  https://a \n\n\r\nhttps://b"), true)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                InputLine {
+                    text: "https://a".to_string(),
+                    line: Some(1)
+                },
+                InputLine {
+                    text: "https://b".to_string(),
+                    line: Some(4)
+                },
+            ]
+        );
+
+        let mut unnumbered = Lines::new(Cursor::new("\nhttps://c\n"), false);
+        assert_eq!(unnumbered.next().unwrap().unwrap().line, None);
+        assert!(unnumbered.next().is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn pipes_are_read_once() {
+        let dir = std::env::temp_dir().join(format!("flom-test-fifo-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let fifo = dir.join("links");
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let writer = {
+            let fifo = fifo.clone();
+            std::thread::spawn(move || fs::write(fifo, "https://a\nhttps://b\n").unwrap())
+        };
+
+        let lines = Lines::open(fifo.to_str().unwrap()).await.unwrap();
+        assert_eq!(lines.total(), None);
+        assert_eq!(lines.count(), 2);
+        writer.join().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn regular_files_are_counted() {
+        let path = std::env::temp_dir().join(format!("flom-test-count-{}", std::process::id()));
+        fs::write(&path, "https://a\n\nhttps://b\n").unwrap();
+        let lines = Lines::open(path.to_str().unwrap()).await.unwrap();
+        assert_eq!(lines.total(), Some(2));
+        assert_eq!(lines.count(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn count_lines_skips_blanks() {
+        assert_eq!(
+            count_lines(Cursor::new("  https://a \n\n\r\nhttps://b")),
+            Some(2)
+        );
+        assert_eq!(count_lines(Cursor::new("")), Some(0));
+    }
+
+    #[test]
+    fn raw_url_rewrites_github_pages() {
+        assert_eq!(
+            raw_url("https://gist.github.com/alice/0123abcd"),
+            "https://gist.githubusercontent.com/alice/0123abcd/raw"
+        );
+        assert_eq!(
+            raw_url("https://github.com/alice/links/blob/main/docs/links.txt"),
+            "https://raw.githubusercontent.com/alice/links/main/docs/links.txt"
+        );
+        assert_eq!(
+            raw_url("https://example.com/links.txt"),
+            "https://example.com/links.txt"
+        );
+    }
+}
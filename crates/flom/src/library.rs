@@ -0,0 +1,429 @@
+//! The `Library.xml` export of iTunes and the Music app, and the progress log
+//! that lets `flom library` resume an interrupted run.
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use flom_config::flom_dir;
+use flom_core::{FlomError, FlomResult};
+use flom_music::matching::TrackRef;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::outfile::sanitize_component;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibraryTrack {
+    /// The persistent ID, which survives re-exports; the track ID otherwise.
+    pub key: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub duration_ms: Option<u64>,
+}
+
+impl LibraryTrack {
+    pub fn as_ref(&self) -> Option<TrackRef<'_>> {
+        Some(TrackRef {
+            title: self.title.as_deref()?,
+            artist: self.artist.as_deref()?,
+            duration: self.duration_ms.map(Duration::from_millis),
+        })
+    }
+
+    pub fn summary(&self) -> String {
+        let title = self.title.as_deref().unwrap_or("Unknown title");
+        let artist = self.artist.as_deref().unwrap_or("Unknown artist");
+        format!("{title} / {artist}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Playlist {
+    pub name: String,
+    /// Indices into [`Library::tracks`], in playlist order.
+    pub tracks: Vec<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct Library {
+    pub tracks: Vec<LibraryTrack>,
+    /// User playlists; the whole-library and built-in ones are left out.
+    pub playlists: Vec<Playlist>,
+}
+
+impl Library {
+    pub fn playlist(&self, name: &str) -> Option<&Playlist> {
+        let name = name.trim();
+        self.playlists
+            .iter()
+            .find(|playlist| playlist.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// A plist value; only what a library export uses.
+#[derive(Debug, Clone, PartialEq)]
+enum Plist {
+    Dict(Vec<(String, Plist)>),
+    Array(Vec<Plist>),
+    Text(String),
+    Bool(bool),
+}
+
+impl Plist {
+    fn get(&self, key: &str) -> Option<&Plist> {
+        match self {
+            Plist::Dict(entries) => entries
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn text(&self, key: &str) -> Option<String> {
+        match self.get(key)? {
+            Plist::Text(text) if !text.trim().is_empty() => Some(text.trim().to_string()),
+            _ => None,
+        }
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        matches!(self.get(key), Some(Plist::Bool(true)))
+    }
+}
+
+enum Token {
+    Open(bool),
+    Close,
+    Key(String),
+    Value(Plist),
+}
+
+pub fn parse_library(xml: &str) -> FlomResult<Library> {
+    if !xml.contains("<plist") {
+        return Err(FlomError::Parse("not a Library.xml export".to_string()));
+    }
+    let mut tokens = tokenize(xml).into_iter();
+    let root = match tokens.next() {
+        Some(Token::Open(is_dict)) => parse_container(is_dict, &mut tokens)?,
+        _ => {
+            return Err(FlomError::Parse(
+                "library has no top-level dict".to_string(),
+            ));
+        }
+    };
+
+    let mut library = Library::default();
+    let mut by_id = HashMap::new();
+    if let Some(Plist::Dict(entries)) = root.get("Tracks") {
+        for (id, track) in entries {
+            by_id.insert(id.as_str(), library.tracks.len());
+            library.tracks.push(LibraryTrack {
+                key: track.text("Persistent ID").unwrap_or_else(|| id.clone()),
+                title: track.text("Name"),
+                artist: track.text("Artist"),
+                duration_ms: track.text("Total Time").and_then(|ms| ms.parse().ok()),
+            });
+        }
+    }
+    if let Some(Plist::Array(playlists)) = root.get("Playlists") {
+        for playlist in playlists {
+            if playlist.flag("Master")
+                || playlist.flag("Folder")
+                || playlist.get("Distinguished Kind").is_some()
+            {
+                continue;
+            }
+            let tracks = match playlist.get("Playlist Items") {
+                Some(Plist::Array(items)) => items
+                    .iter()
+                    .filter_map(|item| by_id.get(item.text("Track ID")?.as_str()).copied())
+                    .collect(),
+                _ => Vec::new(),
+            };
+            library.playlists.push(Playlist {
+                name: playlist
+                    .text("Name")
+                    .unwrap_or_else(|| "Untitled".to_string()),
+                tracks,
+            });
+        }
+    }
+    Ok(library)
+}
+
+/// Splits a plist into tokens. Like the feed parser this is a lenient scan,
+/// but library exports are machine-written and regular.
+fn tokenize(xml: &str) -> Vec<Token> {
+    let token_re = Regex::new(
+        r"(?s)<(key|string|integer|real|date|data)>(.*?)</(?:key|string|integer|real|date|data)>|<(dict|array)>|</(?:dict|array)>|<(dict|array|string|true|false)\s*/>",
+    )
+    .expect("valid plist regex");
+    token_re
+        .captures_iter(xml)
+        .map(|caps| {
+            if let (Some(tag), Some(text)) = (caps.get(1), caps.get(2)) {
+                let text = unescape(text.as_str());
+                return match tag.as_str() {
+                    "key" => Token::Key(text),
+                    _ => Token::Value(Plist::Text(text)),
+                };
+            }
+            if let Some(tag) = caps.get(3) {
+                return Token::Open(tag.as_str() == "dict");
+            }
+            match caps.get(4).map(|tag| tag.as_str()) {
+                Some("dict") => Token::Value(Plist::Dict(Vec::new())),
+                Some("array") => Token::Value(Plist::Array(Vec::new())),
+                Some("string") => Token::Value(Plist::Text(String::new())),
+                Some(flag) => Token::Value(Plist::Bool(flag == "true")),
+                None => Token::Close,
+            }
+        })
+        .collect()
+}
+
+fn parse_container(is_dict: bool, tokens: &mut impl Iterator<Item = Token>) -> FlomResult<Plist> {
+    let mut entries = Vec::new();
+    let mut items = Vec::new();
+    let mut key = None;
+    loop {
+        let value = match tokens.next() {
+            Some(Token::Close) => break,
+            Some(Token::Key(name)) => {
+                key = Some(name);
+                continue;
+            }
+            Some(Token::Open(nested)) => parse_container(nested, tokens)?,
+            Some(Token::Value(value)) => value,
+            None => return Err(FlomError::Parse("library export is truncated".to_string())),
+        };
+        if !is_dict {
+            items.push(value);
+        } else if let Some(key) = key.take() {
+            entries.push((key, value));
+        }
+    }
+    Ok(if is_dict {
+        Plist::Dict(entries)
+    } else {
+        Plist::Array(items)
+    })
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Writes `playlist` as an extended M3U file of the converted links in `dir`,
+/// leaving out tracks that could not be converted.
+pub fn write_playlist(
+    dir: &Path,
+    library: &Library,
+    playlist: &Playlist,
+    links: &HashMap<usize, String>,
+) -> FlomResult<PathBuf> {
+    fs::create_dir_all(dir)
+        .map_err(|err| FlomError::Config(format!("failed to create playlist dir: {err}")))?;
+    let mut content = String::from("#EXTM3U\n");
+    for &index in &playlist.tracks {
+        let Some(url) = links.get(&index) else {
+            continue;
+        };
+        let track = &library.tracks[index];
+        let seconds = track.duration_ms.map_or(-1, |ms| (ms / 1000) as i64);
+        let name = match (&track.artist, &track.title) {
+            (Some(artist), Some(title)) => format!("{artist} - {title}"),
+            _ => track.summary(),
+        };
+        content.push_str(&format!("#EXTINF:{seconds},{name}\n{url}\n"));
+    }
+    let path = dir.join(format!("{}.m3u", sanitize_component(&playlist.name)));
+    fs::write(&path, content)
+        .map_err(|err| FlomError::Config(format!("failed to write {}: {err}", path.display())))?;
+    Ok(path)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ProgressEntry {
+    target: String,
+    track: String,
+    url: String,
+}
+
+/// Links already found for library tracks, so a rerun after an interruption
+/// skips them. Kept as JSON lines and appended to as each track converts.
+#[derive(Debug)]
+pub struct Progress {
+    path: PathBuf,
+    target: String,
+    done: HashMap<String, String>,
+}
+
+pub fn progress_path() -> FlomResult<PathBuf> {
+    Ok(flom_dir()?.join("library_progress.jsonl"))
+}
+
+impl Progress {
+    /// Loads the links found for `target`; a missing file or damaged lines
+    /// just mean those tracks are converted again.
+    pub fn load(path: PathBuf, target: &str) -> Self {
+        let done = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str::<ProgressEntry>(line).ok())
+            .filter(|entry| entry.target == target)
+            .map(|entry| (entry.track, entry.url))
+            .collect();
+        Self {
+            path,
+            target: target.to_string(),
+            done,
+        }
+    }
+
+    /// Forgets what earlier runs found; new links are still recorded.
+    pub fn clear(&mut self) {
+        self.done.clear();
+    }
+
+    pub fn get(&self, track: &LibraryTrack) -> Option<&str> {
+        self.done.get(&track.key).map(String::as_str)
+    }
+
+    pub fn record(&mut self, track: &LibraryTrack, url: &str) -> FlomResult<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|err| {
+                FlomError::Config(format!("failed to create progress dir: {err}"))
+            })?;
+        }
+        let entry = ProgressEntry {
+            target: self.target.clone(),
+            track: track.key.clone(),
+            url: url.to_string(),
+        };
+        let line = serde_json::to_string(&entry)
+            .map_err(|err| FlomError::Parse(format!("failed to serialize progress: {err}")))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|err| FlomError::Config(format!("failed to open progress: {err}")))?;
+        writeln!(file, "{line}")
+            .map_err(|err| FlomError::Config(format!("failed to write progress: {err}")))?;
+        self.done.insert(entry.track, entry.url);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIBRARY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple Computer//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>Major Version</key><integer>1</integer>
+	<key>Tracks</key>
+	<dict>
+		<key>101</key>
+		<dict>
+			<key>Track ID</key><integer>101</integer>
+			<key>Name</key><string>Mr. Brightside</string>
+			<key>Artist</key><string>The Killers</string>
+			<key>Total Time</key><integer>222075</integer>
+			<key>Explicit</key><true/>
+			<key>Persistent ID</key><string>AB12</string>
+		</dict>
+		<key>102</key>
+		<dict>
+			<key>Track ID</key><integer>102</integer>
+			<key>Name</key><string>Rock &amp; Roll</string>
+			<key>Artist</key><string>Led Zeppelin</string>
+		</dict>
+	</dict>
+	<key>Playlists</key>
+	<array>
+		<dict>
+			<key>Name</key><string>Library</string>
+			<key>Master</key><true/>
+			<key>Playlist Items</key>
+			<array>
+				<dict><key>Track ID</key><integer>101</integer></dict>
+				<dict><key>Track ID</key><integer>102</integer></dict>
+			</array>
+		</dict>
+		<dict>
+			<key>Name</key><string>Road Trip</string>
+			<key>Description</key><string/>
+			<key>Playlist Items</key>
+			<array>
+				<dict><key>Track ID</key><integer>102</integer></dict>
+				<dict><key>Track ID</key><integer>999</integer></dict>
+				<dict><key>Track ID</key><integer>101</integer></dict>
+			</array>
+		</dict>
+		<dict>
+			<key>Name</key><string>Empty</string>
+			<key>Playlist Items</key><array/>
+		</dict>
+	</array>
+</dict>
+</plist>
+"#;
+
+    #[test]
+    fn parses_tracks_and_user_playlists() {
+        let library = parse_library(LIBRARY).unwrap();
+        assert_eq!(library.tracks.len(), 2);
+        assert_eq!(library.tracks[0].key, "AB12");
+        assert_eq!(library.tracks[0].duration_ms, Some(222_075));
+        assert_eq!(library.tracks[1].key, "102");
+        assert_eq!(library.tracks[1].summary(), "Rock & Roll / Led Zeppelin");
+
+        let names: Vec<_> = library.playlists.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Road Trip", "Empty"]);
+        assert_eq!(library.playlist("road trip").unwrap().tracks, vec![1, 0]);
+        assert!(parse_library("<rss></rss>").is_err());
+    }
+
+    #[test]
+    fn progress_survives_a_restart_and_playlists_skip_misses() {
+        let dir = std::env::temp_dir().join("flom-test-library");
+        let _ = fs::remove_dir_all(&dir);
+        let library = parse_library(LIBRARY).unwrap();
+        let track = &library.tracks[0];
+
+        let mut progress = Progress::load(dir.join("progress.jsonl"), "spotify");
+        progress
+            .record(track, "https://open.spotify.com/track/a")
+            .unwrap();
+        let reloaded = Progress::load(dir.join("progress.jsonl"), "spotify");
+        assert_eq!(
+            reloaded.get(track),
+            Some("https://open.spotify.com/track/a")
+        );
+        assert_eq!(
+            Progress::load(dir.join("progress.jsonl"), "deezer").get(track),
+            None
+        );
+
+        let links = HashMap::from([(0, "https://open.spotify.com/track/a".to_string())]);
+        let path = write_playlist(&dir, &library, &library.playlists[0], &links).unwrap();
+        assert_eq!(path.file_name().unwrap(), "Road Trip.m3u");
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "#EXTM3U\n#EXTINF:222,The Killers - Mr. Brightside\nhttps://open.spotify.com/track/a\n"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
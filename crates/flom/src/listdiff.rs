@@ -0,0 +1,173 @@
+//! `flom diff`: what changed between two exports of the same link list.
+
+use std::collections::{HashMap, HashSet};
+
+use flom_core::ConversionResult;
+use flom_music::metadata::{artist_key, title_key};
+use serde::Serialize;
+
+/// A converted line of one of the lists.
+#[derive(Debug, Clone, Serialize)]
+pub struct ListEntry {
+    /// 1-based line in its list.
+    pub line: usize,
+    pub url: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub target_url: Option<String>,
+    /// What identifies the track on the target platform.
+    #[serde(skip)]
+    pub key: String,
+}
+
+impl ListEntry {
+    pub fn new(line: usize, result: &ConversionResult) -> Self {
+        let info = result.target_info.as_ref().or(result.source_info.as_ref());
+        let key = result
+            .target_id
+            .clone()
+            .or_else(|| result.target_url.clone())
+            .unwrap_or_else(|| result.source_url.clone());
+        Self {
+            line,
+            url: result.source_url.clone(),
+            title: info.and_then(|info| info.title.clone()),
+            artist: info.and_then(|info| info.artist.clone()),
+            target_url: result.target_url.clone(),
+            key,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let title = self.title.as_deref().unwrap_or("Unknown title");
+        let artist = self.artist.as_deref().unwrap_or("Unknown artist");
+        format!("{title} / {artist}")
+    }
+
+    /// Normalized title and artist, for spotting another version of a track.
+    fn song_key(&self) -> Option<(String, String)> {
+        Some((
+            title_key(self.title.as_deref()?),
+            artist_key(self.artist.as_deref()?),
+        ))
+    }
+}
+
+/// A track whose entity changed between the lists while its title and
+/// artist stayed, e.g. a remaster swapped in for the original.
+#[derive(Debug, Clone, Serialize)]
+pub struct Changed {
+    pub old: ListEntry,
+    pub new: ListEntry,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ListDiff {
+    pub added: Vec<ListEntry>,
+    pub removed: Vec<ListEntry>,
+    pub changed: Vec<Changed>,
+    pub unchanged: usize,
+}
+
+/// Matches `old` and `new` by target entity; entries left over on both sides
+/// with the same title and artist count as changed rather than removed and
+/// added. A track listed twice needs two matches.
+pub fn diff_lists(old: Vec<ListEntry>, new: Vec<ListEntry>) -> ListDiff {
+    let mut diff = ListDiff::default();
+    let mut remaining: HashMap<String, usize> = HashMap::new();
+    for entry in &old {
+        *remaining.entry(entry.key.clone()).or_insert(0) += 1;
+    }
+    let mut matched: HashMap<String, usize> = HashMap::new();
+    let mut added = Vec::new();
+    for entry in new {
+        match remaining.get_mut(&entry.key) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                *matched.entry(entry.key).or_insert(0) += 1;
+                diff.unchanged += 1;
+            }
+            _ => added.push(entry),
+        }
+    }
+    let mut removed = Vec::new();
+    for entry in old {
+        match matched.get_mut(&entry.key) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => removed.push(entry),
+        }
+    }
+
+    let mut paired = HashSet::new();
+    for entry in removed {
+        let partner = entry.song_key().and_then(|key| {
+            added.iter().enumerate().position(|(index, candidate)| {
+                !paired.contains(&index) && candidate.song_key().as_ref() == Some(&key)
+            })
+        });
+        match partner {
+            Some(index) => {
+                paired.insert(index);
+                diff.changed.push(Changed {
+                    old: entry,
+                    new: added[index].clone(),
+                });
+            }
+            None => diff.removed.push(entry),
+        }
+    }
+    diff.added = added
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| !paired.contains(index))
+        .map(|(_, entry)| entry)
+        .collect();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(line: usize, key: &str, title: &str) -> ListEntry {
+        ListEntry {
+            line,
+            url: format!("https://example.com/{key}"),
+            title: Some(title.to_string()),
+            artist: Some("Artist".to_string()),
+            target_url: Some(format!("https://open.spotify.com/track/{key}")),
+            key: key.to_string(),
+        }
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed() {
+        let old = vec![
+            entry(1, "a", "Intro"),
+            entry(2, "b", "Song"),
+            entry(3, "c", "Gone"),
+            entry(4, "d", "Twice"),
+            entry(5, "d", "Twice"),
+        ];
+        let new = vec![
+            entry(1, "d", "Twice"),
+            entry(2, "a", "Intro"),
+            entry(3, "b2", "Song - 2011 Remaster"),
+            entry(4, "e", "Fresh"),
+        ];
+        let diff = diff_lists(old, new);
+
+        assert_eq!(diff.unchanged, 2);
+        let lines = |entries: &[ListEntry]| entries.iter().map(|e| e.line).collect::<Vec<_>>();
+        assert_eq!(lines(&diff.added), [4]);
+        assert_eq!(lines(&diff.removed), [3, 5]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(
+            (
+                diff.changed[0].old.key.as_str(),
+                diff.changed[0].new.key.as_str()
+            ),
+            ("b", "b2")
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use chrono::Utc;
+use flom_config::{FlomConfigData, flom_dir};
+use flom_core::FlomResult;
+
+const DEFAULT_MAX_SIZE: u64 = 1024 * 1024;
+const DEFAULT_KEEP: usize = 3;
+
+static LOG_FILE: OnceLock<LogFile> = OnceLock::new();
+
+/// Size-bounded log file rotated to `<path>.1` .. `<path>.<keep>`.
+#[derive(Debug, Clone)]
+pub struct LogFile {
+    path: PathBuf,
+    max_size: u64,
+    keep: usize,
+}
+
+impl LogFile {
+    pub fn new(path: PathBuf, max_size: u64, keep: usize) -> Self {
+        Self {
+            path,
+            max_size,
+            keep,
+        }
+    }
+
+    pub fn from_config(config: &FlomConfigData) -> FlomResult<Option<Self>> {
+        if !config.log.enabled.unwrap_or(false) {
+            return Ok(None);
+        }
+        let path = match &config.log.path {
+            Some(path) => PathBuf::from(path),
+            None => flom_dir()?.join("logs").join("flom.log"),
+        };
+        Ok(Some(Self::new(
+            path,
+            config.log.max_size.unwrap_or(DEFAULT_MAX_SIZE),
+            config.log.keep.unwrap_or(DEFAULT_KEEP),
+        )))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write(&self, level: &str, message: &str) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let line = format!("{} {level} {message}\n", Utc::now().to_rfc3339());
+        let size = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())
+    }
+
+    fn rotate(&self) -> std::io::Result<()> {
+        if self.keep == 0 {
+            return fs::remove_file(&self.path);
+        }
+        let oldest = rotated_path(&self.path, self.keep);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for index in (1..self.keep).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, index + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
+
+pub fn init(config: &FlomConfigData) -> FlomResult<()> {
+    if let Some(log) = LogFile::from_config(config)? {
+        let _ = LOG_FILE.set(log);
+    }
+    Ok(())
+}
+
+pub fn warn(message: &str) {
+    write("WARN", message);
+}
+
+pub fn error(message: &str) {
+    write("ERROR", message);
+}
+
+fn write(level: &str, message: &str) {
+    if let Some(log) = LOG_FILE.get() {
+        // Logging must never turn a warning into a failure.
+        let _ = log.write(level, message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_when_max_size_exceeded() {
+        let dir = std::env::temp_dir().join("flom-test-logfile");
+        let _ = fs::remove_dir_all(&dir);
+        let log = LogFile::new(dir.join("flom.log"), 80, 2);
+
+        for index in 0..6 {
+            log.write("ERROR", &format!("message number {index}"))
+                .unwrap();
+        }
+
+        assert!(dir.join("flom.log").exists());
+        assert!(dir.join("flom.log.1").exists());
+        assert!(dir.join("flom.log.2").exists());
+        assert!(!dir.join("flom.log.3").exists());
+        let current = fs::read_to_string(dir.join("flom.log")).unwrap();
+        assert!(current.contains("message number 5"));
+        assert!(current.len() as u64 <= 80);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,24 +1,379 @@
-use std::fs;
-use std::io::{self, IsTerminal, Read};
+mod clipboard;
+mod config_ui;
+mod dupes;
+mod environment;
+mod favorites;
+mod feed;
+#[cfg(test)]
+mod fixtures;
+mod gha;
+mod history;
+mod import;
+mod input;
+mod library;
+mod listdiff;
+mod logfile;
+mod notify;
+mod outfile;
+mod output;
+mod post;
+mod progress;
+mod prompt;
+mod romanize;
+mod scheduler;
+#[cfg(feature = "signing")]
+mod signing;
+mod stats;
+mod template;
+mod trackwatch;
+mod ui;
+mod version;
+#[cfg(feature = "watch")]
+mod watch;
 
-use clap::{Parser, Subcommand};
-use console::style;
-use dialoguer::{Input, Select, theme::ColorfulTheme};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, IsTerminal};
+use std::iter::Peekable;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::{Parser, Subcommand, ValueEnum};
 use flom_config::{
-    config_exists, load_config, open_in_editor, resolve_default_target, resolve_simple_output,
-    save_config, set_config_value,
+    ConfigKey, config_exists, decrypt_config, encrypt_config, is_config_encrypted, load_config,
+    load_env_files, open_in_editor, resolve_cache_ttl, resolve_clipboard_fallback,
+    resolve_config_key, resolve_default_target, resolve_entity_db_enabled, resolve_history_enabled,
+    resolve_hyperlinks, resolve_icons, resolve_locale, resolve_output_format,
+    resolve_output_template, resolve_romanize, resolve_simple_output, resolve_song_if_single,
+    resolve_spotify_client_id, resolve_spotify_client_secret, resolve_spotify_refresh_token,
+    resolve_strict, resolve_user_country, set_config_key, set_config_overrides, set_config_value,
+    update_config_values,
 };
-use flom_core::{ConversionResult, FlomError, FlomResult};
-use flom_music::MusicConverter;
+use flom_core::{ConversionResult, FlomError, FlomResult, normalize_isrc, validate_country_code};
+use flom_music::api::odesli::{EntityQuery, EntityType, OdesliOptions};
+use flom_music::api::spotify::{self, SpotifyClient, SpotifyCredentials};
+use flom_music::artist::{ArtistLink, artist_releases};
+use flom_music::cache::ResponseCache;
+use flom_music::extract::extract_music_urls;
+use flom_music::hints;
+use flom_music::matching::MatchThresholds;
+use flom_music::playlist::{PlaylistUrl, playlist_tracks};
+use flom_music::podcast::{EpisodeLink, PodcastConverter, PodcastPlatform};
+use flom_music::{CacheMode, MusicConverter, Platform, TargetOption, build_radio_url};
 use flom_shorten::ShortenClient;
+use scheduler::{Attempted, Priority, Scheduler};
+use serde::Serialize;
+use ui::style;
 
 #[derive(Subcommand, Debug)]
 enum Commands {
+    /// Convert music links (same as passing URLs without a subcommand)
+    #[command(visible_alias = "c")]
+    Convert {
+        #[arg(long)]
+        to: Option<String>,
+        #[arg(value_name = "URL")]
+        urls: Vec<String>,
+    },
+    /// Convert music links to a platform: `flom to spotify <URL>...`
+    To {
+        target: String,
+        #[arg(value_name = "URL")]
+        urls: Vec<String>,
+    },
+    /// Shorten URLs (same as --shorten)
+    #[command(visible_alias = "s")]
+    Shorten {
+        /// Check that each short link redirects straight to the original URL
+        #[arg(long)]
+        verify: bool,
+        #[arg(value_name = "URL")]
+        urls: Vec<String>,
+    },
     /// Manage configuration
+    #[command(visible_alias = "cfg")]
     Config {
         #[command(subcommand)]
         action: ConfigAction,
     },
+    /// Browse past conversions
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+    /// Inspect or empty the cache of Odesli responses
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Print paths, terminal and proxy details and where each config value comes from
+    Env,
+    /// Summarize the history: platforms, success rate and busiest days
+    Stats {
+        /// Only count conversions from runs with this label
+        #[arg(long)]
+        label: Option<String>,
+        /// Number of busiest days to show
+        #[arg(long, default_value_t = 5)]
+        top: usize,
+    },
+    /// Manage bookmarked tracks
+    Fav {
+        #[command(subcommand)]
+        action: FavAction,
+    },
+    /// Convert link files dropped into a directory
+    #[cfg(feature = "watch")]
+    WatchDir { dir: PathBuf },
+    /// Convert music links found in an RSS/Atom feed into a digest
+    Feed {
+        url: String,
+        #[arg(long)]
+        to: Option<String>,
+        #[arg(long, value_enum, default_value_t = DigestFormat::Markdown)]
+        format: DigestFormat,
+        /// Keep running and check the feed again every N minutes
+        #[arg(long, value_name = "MINUTES")]
+        every: Option<u64>,
+    },
+    /// Print a ready-to-paste share message with links for several platforms
+    Post {
+        url: String,
+        /// Message template (defaults to `post.template` in the config)
+        #[arg(long)]
+        template: Option<String>,
+    },
+    /// Print "song radio" and mix links seeded with a track, for discovery
+    Similar {
+        url: String,
+        /// Only this platform (spotify, appleMusic, youtube or youtubeMusic)
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Wait for a track to become available on a platform
+    TrackWatch {
+        url: String,
+        #[arg(long)]
+        to: Option<String>,
+        /// Send a desktop notification when the track shows up
+        #[arg(long)]
+        notify: bool,
+    },
+    /// Re-check pending track watches (run from cron or a scheduler)
+    WatchRun,
+    /// Report probable duplicates in a track list (CSV export or list of links)
+    Dupes {
+        /// File or http(s) URL with the track list
+        #[arg(long)]
+        input: String,
+    },
+    /// Compare two link lists, e.g. exports of a shared playlist, track by track
+    Diff {
+        /// File or http(s) URL with the earlier list
+        old: String,
+        /// File or http(s) URL with the later list
+        new: String,
+        /// Platform the tracks are matched on
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Convert the top releases of a Spotify, Apple Music or Deezer artist
+    Artist {
+        url: String,
+        #[arg(long)]
+        to: Option<String>,
+        /// How many releases to convert
+        #[arg(long, default_value_t = 10)]
+        limit: u32,
+    },
+    /// Convert a podcast episode between Apple Podcasts, Spotify and Pocket Casts
+    Podcast {
+        url: String,
+        /// applePodcasts, spotify or pocketCasts
+        #[arg(long)]
+        to: String,
+    },
+    /// Find a track by name, e.g. "Daft Punk - One More Time", and convert it
+    Search {
+        /// `artist - title`, or any text to search for
+        query: String,
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Convert every track of a Spotify, Apple Music or YouTube playlist
+    Playlist {
+        url: String,
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Convert the tracks of a Shazam or SoundHound export (CSV or JSON)
+    Import {
+        /// File or http(s) URL with the export
+        #[arg(long)]
+        input: String,
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Convert the tracks and playlists of an iTunes/Music `Library.xml` export
+    Library {
+        /// File or http(s) URL with the export
+        #[arg(long)]
+        input: String,
+        #[arg(long)]
+        to: Option<String>,
+        /// Only convert this playlist
+        #[arg(long)]
+        playlist: Option<String>,
+        /// Write each playlist as an .m3u file of converted links into this directory
+        #[arg(long, value_name = "DIR")]
+        playlists: Option<PathBuf>,
+        /// Convert every track again instead of resuming an earlier run
+        #[arg(long)]
+        restart: bool,
+    },
+    /// Write a detached signature for a JSON report to `<FILE>.sig`
+    #[cfg(feature = "signing")]
+    Sign { file: PathBuf },
+    /// Check a JSON report against its detached signature
+    #[cfg(feature = "signing")]
+    Verify {
+        file: PathBuf,
+        /// Signature file (defaults to `<FILE>.sig`)
+        #[arg(long)]
+        signature: Option<PathBuf>,
+        /// Base64 public key the report must be signed with (defaults to
+        /// the public key of the configured signing.key)
+        #[arg(long, value_name = "KEY")]
+        public_key: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum FavAction {
+    /// Bookmark a track by URL
+    Add {
+        url: String,
+        /// Tag to attach (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// List bookmarked tracks
+    List {
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// Remove a bookmark by ID
+    Remove { id: u64 },
+    /// Copy a bookmarked link to the clipboard
+    Copy {
+        id: u64,
+        /// Copy the link on this platform instead of the bookmarked URL
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Export bookmarks for sharing
+    Export {
+        /// Target platform whose links are exported
+        #[arg(long)]
+        to: Option<String>,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        #[arg(long)]
+        tag: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Styled text for terminals
+    Human,
+    /// One JSON object per line
+    Json,
+    /// GitHub Actions workflow commands (annotations)
+    Gha,
+    /// CSV rows with a header: URLs, platforms, title, artist and album
+    Csv,
+    /// Markdown links, with a table per link that yields several results
+    Markdown,
+}
+
+/// Order of the results one link expands to, e.g. with `--to all`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SortOrder {
+    /// By target platform key
+    Platform,
+    /// By target title, then platform; results without a title come last
+    Title,
+    /// Unsorted; the order may differ between runs
+    None,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DigestFormat {
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum IdType {
+    Song,
+    Album,
+}
+
+impl From<IdType> for EntityType {
+    fn from(value: IdType) -> Self {
+        match value {
+            IdType::Song => EntityType::Song,
+            IdType::Album => EntityType::Album,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum HistoryAction {
+    /// Fuzzy-search past conversions and print the chosen target URL
+    Pick,
+    /// List past conversions
+    List {
+        /// Only show conversions from runs with this label
+        #[arg(long)]
+        label: Option<String>,
+        /// Show at most this many of the most recent entries
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// List past conversions whose title, artist or album contains every word
+    Search {
+        #[arg(required = true)]
+        words: Vec<String>,
+        /// Show at most this many of the most recent matches
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Convert the source link of a past conversion again
+    Rerun {
+        /// Entry number, as shown by `flom history list`
+        id: usize,
+        /// Target platform (defaults to the entry's)
+        #[arg(long)]
+        to: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    /// Show the number of cached responses and their size on disk
+    Stats,
+    /// Remove every cached response
+    Clear,
+    /// Remove cached responses older than `odesli.cache_ttl_secs`
+    Prune,
 }
 
 #[derive(Subcommand, Debug)]
@@ -31,29 +386,242 @@ enum ConfigAction {
     List,
     /// Open config file in editor
     Edit,
+    /// Edit every known key in an interactive form
+    Ui,
+    /// Encrypt the config file with a passphrase or age identity file
+    Encrypt {
+        #[arg(long, value_name = "PATH")]
+        identity: Option<PathBuf>,
+    },
+    /// Decrypt the config file back to plain TOML
+    Decrypt {
+        #[arg(long, value_name = "PATH")]
+        identity: Option<PathBuf>,
+    },
 }
 
 #[derive(Debug, Parser)]
 #[command(name = "flom")]
-#[command(version, about = "Universal converter", long_about = None)]
+#[command(about = "Universal converter", long_about = None)]
+#[command(disable_version_flag = true)]
 struct Cli {
+    /// Print version information
+    #[arg(short = 'V', long)]
+    version: bool,
+    /// Output format (defaults to `output.format` in the config, then human)
+    #[arg(long, visible_alias = "format", value_enum)]
+    output: Option<OutputFormat>,
+    /// Shorthand for `--output json`
+    #[arg(long, conflicts_with = "output")]
+    json: bool,
+    /// Override a config key for this run only, e.g. `--set output.simple=true` (repeatable)
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    overrides: Vec<String>,
+    /// Target platform, a comma-separated list of them, `all` or `songlink`
     #[arg(long)]
     to: Option<String>,
+    /// Read URLs from a file or an http(s) URL (gists and GitHub file pages work too)
     #[arg(long)]
     input: Option<String>,
     #[arg(long)]
     shorten: bool,
+    /// Check that each short link redirects straight to the original URL
+    #[arg(long, requires = "shorten")]
+    verify: bool,
     #[arg(long)]
     simple: bool,
+    /// Print each result as this line, e.g. "{title} — {artist}: {target_url}"
+    /// (fields: title, artist, album, source_url, target_url, source_platform,
+    /// target_platform, source_id, target_id)
+    #[arg(long)]
+    template: Option<String>,
+    /// Order of results when one link yields several (e.g. `--to all`)
+    #[arg(long, value_enum, default_value_t = SortOrder::Platform)]
+    sort: SortOrder,
+    /// Also add the converted tracks to a new private Spotify playlist (needs --to spotify
+    /// and `spotify.*` credentials)
+    #[arg(long, value_name = "NAME", conflicts_with = "shorten")]
+    create_playlist: Option<String>,
+    /// Label recorded with this run's history entries
+    #[arg(long)]
+    label: Option<String>,
+    /// Resolve single-track albums to the track instead of the album
+    #[arg(long)]
+    song_if_single: bool,
+    /// Fail each conversion that comes with a warning instead of printing it
+    #[arg(long)]
+    strict: bool,
+    /// Ask for the target of every link of a batch instead of once for all
+    #[arg(long)]
+    prompt_each: bool,
+    /// Print Odesli's unmodified JSON response for each link instead of converting it
+    #[arg(long, conflicts_with = "shorten")]
+    raw: bool,
+    /// Don't read or write the response cache and entity database
+    #[arg(long, conflicts_with = "refresh")]
+    no_cache: bool,
+    /// Ask Odesli again instead of using cached answers, and store the fresh ones
+    #[arg(long)]
+    refresh: bool,
+    /// Answer only from the response cache and entity database; fail for anything else
+    #[arg(long, conflicts_with_all = ["no_cache", "refresh", "raw"])]
+    offline: bool,
+    /// Convert each track of an album link instead of the album itself
+    #[arg(long)]
+    expand_tracks: bool,
+    /// Drop results whose target URL a previous link of the batch already produced
+    #[arg(long)]
+    unique_targets: bool,
+    /// Print a placeholder record for each link that fails, so output records
+    /// line up with input links; needs a single target
+    #[arg(long, conflicts_with_all = ["unique_targets", "expand_tracks"])]
+    ordered: bool,
+    /// Bookmark every converted link, as `flom fav add` does
+    #[arg(long)]
+    fav: bool,
+    /// Lookups of a batch running at the same time, across all sites (default: 4)
+    #[arg(long, value_name = "N")]
+    concurrency: Option<NonZeroUsize>,
+    /// Transliterate titles and artists to ASCII (e.g. for CJK scripts)
+    #[arg(long)]
+    romanize: bool,
+    /// Don't show the progress line large batches keep on stderr
+    #[arg(long)]
+    no_progress: bool,
+    /// Look up release date, year and label on Deezer or the iTunes store
+    /// (up to two more requests per link)
+    #[arg(long)]
+    release_info: bool,
+    /// Also write each result as JSON into this directory
+    #[arg(long, value_name = "DIR")]
+    out_dir: Option<PathBuf>,
+    /// File name template for --out-dir, e.g. "{artist}-{title}.json"
+    #[arg(long, requires = "out_dir", default_value = outfile::DEFAULT_TEMPLATE)]
+    out_template: String,
+    /// Look up a track or album by platform-native ID instead of a URL
+    #[arg(long, requires = "platform", conflicts_with = "shorten")]
+    id: Option<String>,
+    /// Look up a track by ISRC (needs Spotify app credentials unless seen before)
+    #[arg(long, value_name = "CODE", conflicts_with_all = ["id", "shorten"])]
+    isrc: Option<String>,
+    /// Platform the --id belongs to, e.g. spotify or appleMusic
+    #[arg(long, requires = "id")]
+    platform: Option<String>,
+    /// Kind of entity the --id refers to
+    #[arg(long = "type", value_enum, default_value_t = IdType::Song)]
+    id_type: IdType,
     #[arg(value_name = "URL")]
     urls: Vec<String>,
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
-#[tokio::main]
-async fn main() {
-    let cli = Cli::parse();
+impl Cli {
+    /// Folds the `convert`, `to` and `shorten` subcommands into the equivalent
+    /// top-level flags, so the rest of `main` only handles one form.
+    fn expand_shortcuts(&mut self) {
+        match self.command.take() {
+            Some(Commands::Convert { to, urls }) => {
+                self.to = to.or(self.to.take());
+                self.urls.extend(urls);
+            }
+            Some(Commands::To { target, urls }) => {
+                self.to = Some(target);
+                self.urls.extend(urls);
+            }
+            Some(Commands::Shorten { verify, urls }) => {
+                self.shorten = true;
+                self.verify |= verify;
+                self.urls.extend(urls);
+            }
+            command => self.command = command,
+        }
+    }
+
+    /// Converter settings given as flags.
+    fn converter_flags(&self) -> ConverterFlags {
+        let cache = if self.offline {
+            CacheMode::Offline
+        } else if self.no_cache {
+            CacheMode::Off
+        } else if self.refresh {
+            CacheMode::Refresh
+        } else {
+            CacheMode::Use
+        };
+        ConverterFlags {
+            song_if_single: self.song_if_single,
+            strict: self.strict,
+            cache,
+        }
+    }
+
+    /// Whether the track comes from `--id` or `--isrc` rather than a link.
+    fn has_id_input(&self) -> bool {
+        self.id.is_some() || self.isrc.is_some()
+    }
+
+    /// The format asked for on the command line, if any.
+    fn requested_output(&self) -> Option<OutputFormat> {
+        if self.json {
+            Some(OutputFormat::Json)
+        } else {
+            self.output
+        }
+    }
+
+    /// `--output` or `--json`, else `output.format` from the config, else human.
+    fn output_format(&self, config: &flom_config::FlomConfigData) -> FlomResult<OutputFormat> {
+        if let Some(format) = self.requested_output() {
+            return Ok(format);
+        }
+        let Some(value) = resolve_output_format(config) else {
+            return Ok(OutputFormat::Human);
+        };
+        OutputFormat::from_str(&value, true)
+            .map_err(|_| FlomError::Config(format!("unknown output format: {value}")))
+    }
+}
+
+/// Conversions handed to the scheduler ahead of the one being printed. Inputs
+/// are read only as these complete, so huge link dumps stream through.
+const MAX_QUEUED_JOBS: usize = 64;
+
+fn main() {
+    // Sets environment variables, so it has to run before the runtime starts
+    // its worker threads.
+    if let Err(err) = load_env_files() {
+        report_warning(&err);
+    }
+    let runtime = tokio::runtime::Runtime::new().unwrap_or_else(|err| {
+        report_error(&err);
+        std::process::exit(1);
+    });
+    runtime.block_on(run());
+}
+
+async fn run() {
+    let mut cli = Cli::parse();
+    cli.expand_shortcuts();
+
+    if let Some(limit) = cli.concurrency {
+        scheduler::set_host_limit(limit.get());
+    }
+
+    if cli.version {
+        print_version(cli.requested_output().unwrap_or(OutputFormat::Human));
+        return;
+    }
+
+    if let Err(err) = set_config_overrides(&cli.overrides) {
+        report_error(&err);
+        std::process::exit(1);
+    }
+
+    if let Err(err) = unlock_config_if_needed(&cli) {
+        eprintln!("{} {err}", style("Error:").red());
+        std::process::exit(1);
+    }
 
     // Handle config commands first
     if let Some(Commands::Config { action }) = cli.command {
@@ -61,172 +629,2344 @@ async fn main() {
             eprintln!("{} {err}", style("Error:").red());
             std::process::exit(1);
         }
-        return;
+        return;
+    }
+
+    if let Some(Commands::History { action }) = &cli.command
+        && !matches!(action, HistoryAction::Rerun { .. })
+    {
+        if let Err(err) = handle_history_command(action) {
+            eprintln!("{} {err}", style("Error:").red());
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut config = match load_config() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{} {err}", style("Error:").red());
+            std::process::exit(1);
+        }
+    };
+
+    let flags = cli.converter_flags();
+
+    if let Some(Commands::Cache { action }) = &cli.command {
+        if let Err(err) = handle_cache_command(action, &config) {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::Stats { label, top }) = &cli.command {
+        let format = cli.output_format(&config).unwrap_or_else(|err| {
+            report_error(&err);
+            std::process::exit(1);
+        });
+        if let Err(err) = print_stats(label.as_deref(), *top, format) {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::Env) = &cli.command {
+        let format = cli.requested_output().unwrap_or(OutputFormat::Human);
+        if let Err(err) = print_env(&config, format) {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Err(err) = logfile::init(&config) {
+        report_warning(&err);
+    }
+
+    let format = cli.output_format(&config).unwrap_or_else(|err| {
+        report_error(&err);
+        std::process::exit(1);
+    });
+    ui::set_hyperlinks(resolve_hyperlinks(&config).unwrap_or_else(ui::hyperlinks_supported));
+    ui::set_icons(resolve_icons(&config).unwrap_or(false));
+    let template = cli
+        .template
+        .clone()
+        .or_else(|| resolve_output_template(&config));
+    if let Some(template) = &template
+        && let Err(err) = template::validate(template)
+    {
+        report_error(&err);
+        std::process::exit(1);
+    }
+    let layout = output::Layout::new(
+        cli.simple || resolve_simple_output(&config).unwrap_or(false),
+        template.as_deref(),
+    );
+
+    // An unknown country makes Odesli return confusing results, so fail early.
+    if let Err(err) = validate_country_code(&resolve_user_country(&config)) {
+        report_error(&err);
+        std::process::exit(1);
+    }
+
+    if let Some(Commands::Fav { action }) = cli.command {
+        if let Err(err) = handle_fav_command(action, &mut config, flags).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::Feed {
+        url,
+        to,
+        format,
+        every,
+    }) = &cli.command
+    {
+        let converter = build_converter(&mut config, flags);
+        let target = to
+            .clone()
+            .or_else(|| cli.to.clone())
+            .or_else(|| resolve_default_target(&config))
+            .unwrap_or_else(|| "songlink".to_string());
+        if let Err(err) = run_feed(&converter, url, &target, *format, *every, cli.sort).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::Post { url, template }) = cli.command {
+        if let Err(err) = handle_post_command(&url, template, &mut config, flags).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::Similar { url, to }) = &cli.command {
+        let converter = build_converter(&mut config, flags);
+        if let Err(err) = run_similar(&converter, url, to.as_deref(), format).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::History {
+        action: HistoryAction::Rerun { id, to },
+    }) = &cli.command
+    {
+        let converter = build_converter(&mut config, flags);
+        let to = to.as_deref().or(cli.to.as_deref());
+        let record = resolve_history_enabled(&config);
+        if let Err(err) = rerun_history(&converter, *id, to, record, layout, format).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::Podcast { url, to }) = &cli.command {
+        if let Err(err) = run_podcast(&config, url, to, flags.strict, layout, format).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::TrackWatch { url, to, notify }) = &cli.command {
+        let target = to
+            .clone()
+            .or_else(|| cli.to.clone())
+            .or_else(|| resolve_default_target(&config));
+        if let Err(err) = handle_track_watch(url, target, *notify, &mut config, flags).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::Dupes { input }) = &cli.command {
+        let converter = build_converter(&mut config, flags);
+        let thresholds = MatchThresholds::from_config(&config);
+        if let Err(err) = run_dupes(&converter, input, &thresholds, format).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::Diff { old, new, to }) = &cli.command {
+        let converter = build_converter(&mut config, flags);
+        let target = to
+            .clone()
+            .or_else(|| cli.to.clone())
+            .or_else(|| resolve_default_target(&config));
+        if let Err(err) = run_diff(&converter, old, new, target, format).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::Artist { url, to, limit }) = &cli.command {
+        let converter = build_converter(&mut config, flags);
+        let target = to
+            .clone()
+            .or_else(|| cli.to.clone())
+            .or_else(|| resolve_default_target(&config));
+        let options = ArtistOptions {
+            limit: *limit,
+            layout,
+            format,
+            order: cli.sort,
+        };
+        if let Err(err) = run_artist(&converter, &config, url, target, &options).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    #[cfg(feature = "signing")]
+    if let Some(Commands::Sign { file }) = &cli.command {
+        match signer(&config).and_then(|signer| signing::sign_file(&signer, file)) {
+            Ok(path) => println!(
+                "{} Signature written to {}",
+                style("✓").green(),
+                path.display()
+            ),
+            Err(err) => {
+                report_error(&err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    #[cfg(feature = "signing")]
+    if let Some(Commands::Verify {
+        file,
+        signature,
+        public_key,
+    }) = &cli.command
+    {
+        let sig_path = signature
+            .clone()
+            .unwrap_or_else(|| signing::signature_path(file));
+        // Only a key the user trusts says anything about who signed, so the
+        // working directory's config and .env don't count.
+        let trusted_key = match public_key {
+            Some(key) => Ok(key.clone()),
+            None => flom_config::trusted_signing_key().and_then(|key| {
+                let key = key.ok_or_else(|| {
+                    FlomError::Config(
+                        "verify needs a trusted key: pass --public-key, or configure \
+                         signing.key in ~/.flom/config.toml (or FLOM_SIGNING_KEY) to check \
+                         against its public key"
+                            .to_string(),
+                    )
+                })?;
+                signing::Signer::from_base64(&key).map(|signer| signer.public_key())
+            }),
+        };
+        match trusted_key
+            .and_then(|trusted_key| signing::verify_file(file, &sig_path, &trusted_key))
+        {
+            Ok(signature) => println!(
+                "{} Signed by {} with {} {} at {}",
+                style("✓").green(),
+                signature.public_key,
+                signature.tool,
+                signature.version,
+                signature.signed_at
+            ),
+            Err(err) => {
+                report_error(&err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(Commands::Search { query, to }) = &cli.command {
+        let converter = build_converter(&mut config, flags);
+        let target = to
+            .clone()
+            .or_else(|| cli.to.clone())
+            .or_else(|| resolve_default_target(&config));
+        let thresholds = MatchThresholds::from_config(&config);
+        if let Err(err) = run_search(
+            &converter,
+            query,
+            target.as_deref(),
+            &thresholds,
+            layout,
+            format,
+            cli.sort,
+        )
+        .await
+        {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::Playlist { url, to }) = &cli.command {
+        let converter = build_converter(&mut config, flags);
+        let target = to
+            .clone()
+            .or_else(|| cli.to.clone())
+            .or_else(|| resolve_default_target(&config));
+        if let Err(err) =
+            run_playlist(&converter, &config, url, target, layout, format, cli.sort).await
+        {
+            report_error(&err);
+            report_hint(url, &err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::Import { input, to }) = &cli.command {
+        let converter = build_converter(&mut config, flags);
+        let thresholds = MatchThresholds::from_config(&config);
+        let target = to
+            .clone()
+            .or_else(|| cli.to.clone())
+            .or_else(|| resolve_default_target(&config));
+        if let Err(err) = run_import(
+            &converter,
+            input,
+            target,
+            &thresholds,
+            layout,
+            format,
+            cli.sort,
+        )
+        .await
+        {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::Library {
+        input,
+        to,
+        playlist,
+        playlists,
+        restart,
+    }) = &cli.command
+    {
+        let converter = build_converter(&mut config, flags);
+        let thresholds = MatchThresholds::from_config(&config);
+        let target = to
+            .clone()
+            .or_else(|| cli.to.clone())
+            .or_else(|| resolve_default_target(&config));
+        let options = LibraryOptions {
+            playlist: playlist.as_deref(),
+            playlists_dir: playlists.as_deref(),
+            restart: *restart,
+            layout,
+            format,
+        };
+        if let Err(err) = run_library(&converter, input, target, &thresholds, options).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::WatchRun) = cli.command {
+        let converter = build_converter(&mut config, flags);
+        if let Err(err) = run_track_watches(&converter).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    #[cfg(feature = "watch")]
+    if let Some(Commands::WatchDir { dir }) = &cli.command {
+        let converter = build_converter(&mut config, flags);
+        // Nobody is around to answer a prompt, so fall back to the Songlink page.
+        let target = cli
+            .to
+            .clone()
+            .or_else(|| resolve_default_target(&config))
+            .unwrap_or_else(|| "songlink".to_string());
+        if let Err(err) = watch::watch_dir(dir, &converter, &target, cli.sort).await {
+            report_error(&err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let input_lines = match &cli.input {
+        Some(source) => match input::Lines::open(source).await {
+            Ok(lines) => Some(lines),
+            Err(err) => {
+                report_error(&err);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let clipboard_fallback = resolve_clipboard_fallback(&config).unwrap_or(false);
+    // Links piped in on stdin, possibly from a pipe that stays open for hours
+    // (`tail -f links.txt | flom`).
+    let streaming = cli.urls.is_empty()
+        && input_lines.is_none()
+        && !cli.has_id_input()
+        && !io::stdin().is_terminal();
+    // Known when every link comes from arguments or an --input file.
+    let input_total = match &input_lines {
+        Some(lines) => lines.total().map(|total| total + cli.urls.len()),
+        None if streaming => None,
+        None => Some(cli.urls.len()),
+    };
+    let mut inputs =
+        gather_inputs(&mut cli, input_lines, clipboard_fallback).unwrap_or_else(|err| {
+            report_error(&err);
+            std::process::exit(1);
+        });
+    // Look ahead just far enough to tell "nothing", "one link" and "a batch"
+    // apart. A pipe counts as a batch, since its second line may never come.
+    let lookahead = if streaming { 1 } else { 2 };
+    let mut pending: VecDeque<_> = inputs.by_ref().take(lookahead).collect();
+
+    if pending.is_empty() && !cli.has_id_input() {
+        report_error(&"no input URLs provided");
+        std::process::exit(1);
+    }
+
+    if cli.shorten {
+        run_shorten(pending.into_iter().chain(inputs), cli.verify, format).await;
+        return;
+    }
+
+    let converter = build_converter(&mut config, flags);
+
+    if let (Some(id), Some(platform)) = (&cli.id, &cli.platform) {
+        match resolve_id_input(&converter, platform, cli.id_type.into(), id).await {
+            Ok(url) => pending.push_front(Ok(input::InputLine::new(url))),
+            Err(err) => {
+                report_error(&err);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(isrc) = &cli.isrc {
+        match resolve_isrc_input(&converter, &config, isrc).await {
+            Ok(url) => pending.push_front(Ok(input::InputLine::new(url))),
+            Err(err) => {
+                report_error(&err);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if cli.raw {
+        run_raw(&converter, pending.into_iter().chain(inputs)).await;
+        return;
+    }
+
+    // Authorize up front so bad credentials fail before any conversion runs.
+    let playlist = match &cli.create_playlist {
+        Some(name) => match spotify_for_playlist(&config, cli.to.as_deref()).await {
+            Ok(client) => Some((name.as_str(), client)),
+            Err(err) => {
+                report_error(&err);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let mut playlist_uris = Vec::new();
+    // Per-result files are signed when a key is configured.
+    #[cfg(feature = "signing")]
+    let out_signer = match (&cli.out_dir, flom_config::resolve_signing_key(&config)) {
+        (Some(_), Some(_)) => match signer(&config) {
+            Ok(signer) => Some(signer),
+            Err(err) => {
+                report_error(&err);
+                std::process::exit(1);
+            }
+        },
+        _ => None,
+    };
+
+    let romanize = cli.romanize || resolve_romanize(&config).unwrap_or(false);
+    let default_target = resolve_default_target(&config);
+    let record_history = resolve_history_enabled(&config);
+    // Annotations point at the line of the --input file each URL came from.
+    let location = |line: Option<usize>| match (&cli.input, line, format) {
+        (Some(file), Some(line), OutputFormat::Gha) => Some(gha::Location { file, line }),
+        _ => None,
+    };
+
+    let is_batch = streaming || pending.len() > 1;
+    // Prompting for every link of a batch is unusable, so ask once up front.
+    let explicit_target = match &cli.to {
+        None if default_target.is_none() && is_batch && !cli.prompt_each => {
+            match prompt_batch_target() {
+                Ok(target) => Some(target),
+                Err(err) => {
+                    report_error(&err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        to => to.clone(),
+    };
+    if cli.ordered
+        && let Err(err) = single_target(explicit_target.as_deref().or(default_target.as_deref()))
+    {
+        report_error(&err);
+        std::process::exit(1);
+    }
+
+    let scheduler = Scheduler::default();
+    // A single link is someone waiting at the terminal, not a bulk job.
+    let priority = if !is_batch {
+        Priority::Interactive
+    } else {
+        Priority::Batch
+    };
+    let spawn_job = |input: input::InputLine| {
+        let scheduler = scheduler.clone();
+        let converter = converter.clone();
+        let explicit_target = explicit_target.clone();
+        let default_target = default_target.clone();
+        let job_url = input.text.clone();
+        let handle = tokio::spawn(async move {
+            Job::Converted(
+                scheduled_process_url(
+                    &scheduler,
+                    priority,
+                    &converter,
+                    &job_url,
+                    explicit_target.as_deref(),
+                    default_target.as_deref(),
+                )
+                .await,
+            )
+        });
+        (input, handle)
+    };
+    // Album lookups are queued like conversions, under the same limits.
+    let spawn_expand = |input: input::InputLine| {
+        let scheduler = scheduler.clone();
+        let converter = converter.clone();
+        let job_url = input.text.clone();
+        let handle = tokio::spawn(async move {
+            Job::Expanded(
+                scheduler
+                    .run(priority, scheduler::lookup_key(&job_url), || {
+                        converter.album_tracks(&job_url)
+                    })
+                    .await,
+            )
+        });
+        (input, handle)
+    };
+    // Target prompts can't interleave, so without a target one URL runs at a time.
+    let max_queued = if explicit_target.is_none() && default_target.is_none() {
+        1
+    } else {
+        MAX_QUEUED_JOBS.max(scheduler::host_limit())
+    };
+
+    print_header(format);
+    // Per-link target prompts would draw over it.
+    let mut progress = if cli.no_progress || format == OutputFormat::Gha || max_queued == 1 {
+        None
+    } else {
+        progress::Progress::start(input_total)
+    };
+    let mut success = 0usize;
+    let mut failures = Vec::new();
+    let mut input_failed = false;
+    let mut seen_targets = HashSet::new();
+    let mut collapsed = 0usize;
+    let mut new_favorites = Vec::new();
+    let mut inputs = read_ahead(pending.into_iter().chain(inputs));
+    let mut input_open = true;
+    let mut jobs = VecDeque::new();
+
+    loop {
+        // Start links as they arrive while waiting on the oldest one, so a
+        // long-lived pipe gets each result as soon as it is ready.
+        let accepting = input_open && !input_failed && jobs.len() < max_queued;
+        let next = match jobs.front_mut() {
+            Some((_, handle)) if accepting => tokio::select! {
+                input = inputs.recv() => BatchEvent::Input(input),
+                outcome = handle => BatchEvent::Finished(outcome),
+            },
+            Some((_, handle)) => BatchEvent::Finished(handle.await),
+            None if accepting => BatchEvent::Input(inputs.recv().await),
+            None => break,
+        };
+        let outcome = match next {
+            BatchEvent::Input(None) => {
+                input_open = false;
+                continue;
+            }
+            BatchEvent::Input(Some(input)) => {
+                match input {
+                    Ok(input) if cli.expand_tracks => jobs.push_back(spawn_expand(input)),
+                    Ok(input) => jobs.push_back(spawn_job(input)),
+                    Err(err) => {
+                        if let Some(progress) = &mut progress {
+                            progress.clear();
+                        }
+                        report_error(&err);
+                        input_failed = true;
+                    }
+                }
+                continue;
+            }
+            BatchEvent::Finished(job) => job,
+        };
+        let Some((input, _)) = jobs.pop_front() else {
+            break;
+        };
+        let outcome = match outcome {
+            Ok(Job::Converted(outcome)) => outcome,
+            Ok(Job::Expanded(expanded)) => match expanded.result {
+                Ok(Some(tracks)) => {
+                    if let Some(progress) = &mut progress {
+                        progress.grow(tracks.len().saturating_sub(1));
+                    }
+                    // The tracks take the album's place in the output.
+                    for text in tracks.into_iter().rev() {
+                        jobs.push_front(spawn_job(input::InputLine {
+                            text,
+                            line: input.line,
+                        }));
+                    }
+                    continue;
+                }
+                Ok(None) => {
+                    jobs.push_front(spawn_job(input));
+                    continue;
+                }
+                Err(err) => Attempted {
+                    result: Err(err),
+                    attempts: expanded.attempts,
+                },
+            },
+            Err(err) => Attempted {
+                result: Err(FlomError::Api(format!("conversion task failed: {err}"))),
+                attempts: 1,
+            },
+        };
+        let url = input.text.as_str();
+        let failed = outcome.result.is_err();
+        if let Some(progress) = &mut progress {
+            progress.clear();
+        }
+        match outcome.result {
+            Ok(mut results) => {
+                output::sort_results(&mut results, cli.sort);
+                if cli.release_info
+                    && let Err(err) = converter.add_release_info(&mut results).await
+                {
+                    report_warning(&err);
+                }
+                if romanize {
+                    results.iter_mut().for_each(romanize::romanize_result);
+                }
+                success += results.len();
+                if cli.unique_targets {
+                    collapsed += drop_seen_targets(&mut results, &mut seen_targets);
+                }
+                if format == OutputFormat::Gha {
+                    for result in &results {
+                        println!("{}", gha::result_command(result, location(input.line)));
+                    }
+                } else if cli.ordered {
+                    match output::render_ordered(&results, layout, format) {
+                        Ok(rendered) => print!("{rendered}"),
+                        Err(err) => report_error(&err),
+                    }
+                } else {
+                    print_results(&results, layout, format);
+                }
+                if let Some(dir) = &cli.out_dir {
+                    for result in &results {
+                        match outfile::write_result(dir, &cli.out_template, result) {
+                            #[cfg(feature = "signing")]
+                            Ok(path) => {
+                                if let Some(signer) = &out_signer
+                                    && let Err(err) = signing::sign_file(signer, &path)
+                                {
+                                    report_warning(&err);
+                                }
+                            }
+                            #[cfg(not(feature = "signing"))]
+                            Ok(_) => {}
+                            Err(err) => report_warning(&err),
+                        }
+                    }
+                }
+                if record_history && let Err(err) = history::append(&results, cli.label.as_deref())
+                {
+                    report_warning(&err);
+                }
+                if playlist.is_some() {
+                    playlist_uris.extend(
+                        results
+                            .iter()
+                            .filter_map(|result| result.target_url.as_deref())
+                            .filter_map(spotify::track_uri),
+                    );
+                }
+                // The lookup is remembered, so this doesn't ask Odesli again.
+                if cli.fav {
+                    match converter.fetch_links(url).await {
+                        Ok(response) => new_favorites.push(favorites::Favorite::from_response(
+                            url,
+                            &response,
+                            Vec::new(),
+                        )),
+                        Err(err) => report_warning(&err),
+                    }
+                }
+            }
+            Err(err) => {
+                if format == OutputFormat::Gha {
+                    println!("{}", gha::failure_command(url, &err, location(input.line)));
+                } else if cli.ordered {
+                    match output::render_failure(url, &err, layout, format) {
+                        Ok(rendered) => print!("{rendered}"),
+                        Err(err) => report_error(&err),
+                    }
+                }
+                // Reported with the summary so errors don't interleave with
+                // results, except from a pipe that may never end.
+                if streaming {
+                    report_final_failure(url, outcome.attempts, &err);
+                }
+                failures.push((input.text, outcome.attempts, err));
+            }
+        }
+        if let Some(progress) = &mut progress {
+            progress.finish_one(failed);
+        }
+    }
+    drop(progress);
+
+    if !streaming {
+        for (url, attempts, err) in &failures {
+            report_final_failure(url, *attempts, err);
+        }
+    }
+    let failed = failures.len();
+    print_summary(success + failed, success, failed, format);
+    if cli.unique_targets {
+        print_note(
+            &format!("Collapsed {collapsed} repeated target URL(s)"),
+            format,
+        );
+    }
+    if cli.fav {
+        match add_favorites(new_favorites) {
+            Ok(added) => print_note(&format!("Added {added} favorite(s)"), format),
+            Err(err) => report_warning(&err),
+        }
+    }
+    if let Some((name, client)) = playlist
+        && let Err(err) = fill_playlist(&client, name, &playlist_uris, format).await
+    {
+        report_error(&err);
+        std::process::exit(1);
+    }
+    if input_failed {
+        std::process::exit(1);
+    }
+}
+
+/// Bookmarks `new` links that aren't favorites yet, and returns how many.
+fn add_favorites(new: Vec<favorites::Favorite>) -> FlomResult<usize> {
+    let mut favs = favorites::load()?;
+    let mut added = 0;
+    for favorite in new {
+        if !favs.contains_url(&favorite.url) {
+            favs.add(favorite);
+            added += 1;
+        }
+    }
+    if added > 0 {
+        favorites::save(&favs)?;
+    }
+    Ok(added)
+}
+
+/// A Spotify client for `--create-playlist`, once the target and credentials check out.
+async fn spotify_for_playlist(
+    config: &flom_config::FlomConfigData,
+    explicit_target: Option<&str>,
+) -> FlomResult<SpotifyClient> {
+    let target = explicit_target
+        .map(str::to_string)
+        .or_else(|| resolve_default_target(config));
+    let is_spotify = target
+        .as_deref()
+        .and_then(MusicConverter::normalize_target)
+        .is_some_and(|key| key == "spotify");
+    if !is_spotify {
+        return Err(FlomError::InvalidInput(
+            "--create-playlist only supports --to spotify".to_string(),
+        ));
+    }
+    let missing = || {
+        FlomError::Config(
+            "--create-playlist needs spotify.client_id, spotify.client_secret and \
+             spotify.refresh_token (or FLOM_SPOTIFY_CLIENT_ID, FLOM_SPOTIFY_CLIENT_SECRET \
+             and FLOM_SPOTIFY_REFRESH_TOKEN)"
+                .to_string(),
+        )
+    };
+    let credentials = SpotifyCredentials {
+        client_id: resolve_spotify_client_id(config).ok_or_else(missing)?,
+        client_secret: resolve_spotify_client_secret(config).ok_or_else(missing)?,
+        refresh_token: resolve_spotify_refresh_token(config).ok_or_else(missing)?,
+    };
+    SpotifyClient::authorize(reqwest::Client::new(), &credentials).await
+}
+
+#[cfg(feature = "signing")]
+fn signer(config: &flom_config::FlomConfigData) -> FlomResult<signing::Signer> {
+    let key = flom_config::resolve_signing_key(config).ok_or_else(|| {
+        FlomError::Config("signing needs signing.key (or FLOM_SIGNING_KEY)".to_string())
+    })?;
+    signing::Signer::from_base64(&key)
+}
+
+/// App credentials are enough for public playlists; a configured refresh
+/// token also opens the user's private ones.
+async fn spotify_for_reading(config: &flom_config::FlomConfigData) -> FlomResult<SpotifyClient> {
+    let missing = || {
+        FlomError::Config(
+            "reading Spotify playlists needs spotify.client_id and spotify.client_secret \
+             (or FLOM_SPOTIFY_CLIENT_ID and FLOM_SPOTIFY_CLIENT_SECRET)"
+                .to_string(),
+        )
+    };
+    let client_id = resolve_spotify_client_id(config).ok_or_else(missing)?;
+    let client_secret = resolve_spotify_client_secret(config).ok_or_else(missing)?;
+    match resolve_spotify_refresh_token(config) {
+        Some(refresh_token) => {
+            let credentials = SpotifyCredentials {
+                client_id,
+                client_secret,
+                refresh_token,
+            };
+            SpotifyClient::authorize(reqwest::Client::new(), &credentials).await
+        }
+        None => {
+            SpotifyClient::authorize_app(reqwest::Client::new(), &client_id, &client_secret).await
+        }
+    }
+}
+
+async fn fill_playlist(
+    client: &SpotifyClient,
+    name: &str,
+    uris: &[String],
+    format: OutputFormat,
+) -> FlomResult<()> {
+    if uris.is_empty() {
+        return Err(FlomError::InvalidInput(
+            "no Spotify tracks to add; playlist not created".to_string(),
+        ));
+    }
+    let playlist = client.create_playlist(name, "Created by flom").await?;
+    client.add_tracks(&playlist.id, uris).await?;
+    print_note(
+        &format!(
+            "{} Created playlist {name} with {} tracks: {}",
+            style("✓").green(),
+            uris.len(),
+            playlist.url
+        ),
+        format,
+    );
+    Ok(())
+}
+
+fn print_version(format: OutputFormat) {
+    let info = version::version_info();
+    match format {
+        OutputFormat::Human | OutputFormat::Gha | OutputFormat::Csv | OutputFormat::Markdown => {
+            println!("flom {}", info.version)
+        }
+        OutputFormat::Json => match serde_json::to_string_pretty(&info) {
+            Ok(json) => println!("{json}"),
+            Err(err) => report_error(&err),
+        },
+    }
+}
+
+fn print_env(config: &flom_config::FlomConfigData, format: OutputFormat) -> FlomResult<()> {
+    let report = environment::env_report(config, flom_config::config_sources()?)?;
+    match format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&report)
+                .map_err(|err| FlomError::Parse(err.to_string()))?;
+            println!("{json}");
+        }
+        _ => print!("{}", environment::render(&report)),
+    }
+    Ok(())
+}
+
+fn print_stats(label: Option<&str>, top: usize, format: OutputFormat) -> FlomResult<()> {
+    let entries = history::load()?;
+    let stats = stats::usage_stats(history::filter_by_label(&entries, label), top);
+    match format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&stats)
+                .map_err(|err| FlomError::Parse(err.to_string()))?;
+            println!("{json}");
+        }
+        _ => print!("{}", stats::render(&stats)),
+    }
+    Ok(())
+}
+
+fn report_error(message: &dyn std::fmt::Display) {
+    eprintln!("{} {message}", style("Error:").red());
+    logfile::error(&message.to_string());
+}
+
+fn report_warning(message: &dyn std::fmt::Display) {
+    eprintln!("{} {message}", style("Warning:").yellow());
+    logfile::warn(&message.to_string());
+}
+
+fn report_failure(url: &str, err: &FlomError) {
+    eprintln!("{} {url}: {err}", style("Failed").red());
+    logfile::error(&format!("{url}: {err}"));
+    report_hint(url, err);
+}
+
+/// Reports a URL that still failed after all its retries.
+fn report_final_failure(url: &str, attempts: u32, err: &FlomError) {
+    let tries = if attempts > 1 {
+        format!(" (after {attempts} attempts)")
+    } else {
+        String::new()
+    };
+    eprintln!("{} {url}: {err}{tries}", style("Failed").red());
+    logfile::error(&format!("{url}: {err}{tries}"));
+    report_hint(url, err);
+}
+
+/// Explains a known kind of failure below its error line.
+fn report_hint(url: &str, err: &FlomError) {
+    if let Some(hint) = hints::hint_for(url, err) {
+        eprintln!("  {} {hint}", style("Hint:").yellow());
+    }
+}
+
+/// Command-line flags that shape every converter of a run.
+#[derive(Debug, Clone, Copy)]
+struct ConverterFlags {
+    song_if_single: bool,
+    strict: bool,
+    cache: CacheMode,
+}
+
+fn build_converter(
+    config: &mut flom_config::FlomConfigData,
+    flags: ConverterFlags,
+) -> MusicConverter {
+    let api_key = resolve_or_prompt_odesli_key(config);
+    let options = OdesliOptions {
+        song_if_single: flags.song_if_single || resolve_song_if_single(config).unwrap_or(false),
+        locale: resolve_locale(config),
+    };
+    let mut converter = MusicConverter::new(api_key, config)
+        .with_options(options)
+        .with_strict(flags.strict || resolve_strict(config))
+        .with_cache_mode(flags.cache);
+    let Ok(dir) = flom_config::flom_dir() else {
+        return converter;
+    };
+    let ttl = resolve_cache_ttl(config);
+    if ttl > 0 {
+        converter = converter.with_response_cache(dir.join(CACHE_DIR), Duration::from_secs(ttl));
+    }
+    if resolve_entity_db_enabled(config) {
+        converter = converter.with_entity_db(dir.join("entities.jsonl"));
+    }
+    converter
+}
+
+/// Input URLs in order; a read error ends the stream.
+type Inputs = Box<dyn Iterator<Item = FlomResult<input::InputLine>> + Send>;
+
+/// What the batch loop waited for.
+enum BatchEvent {
+    /// The next input line, or `None` at the end of the input.
+    Input(Option<FlomResult<input::InputLine>>),
+    /// The oldest queued job finished.
+    Finished(Result<Job, tokio::task::JoinError>),
+}
+
+/// What a queued job of a batch produces.
+enum Job {
+    /// The results for one link.
+    Converted(Attempted<Vec<ConversionResult>>),
+    /// The track links of an album for `--expand-tracks`, or `None` when the
+    /// link is not an album.
+    Expanded(Attempted<Option<Vec<String>>>),
+}
+
+/// Reads `inputs` on a thread of its own, so waiting for the next line of a
+/// pipe never holds up results that are already done.
+fn read_ahead(
+    inputs: impl Iterator<Item = FlomResult<input::InputLine>> + Send + 'static,
+) -> tokio::sync::mpsc::Receiver<FlomResult<input::InputLine>> {
+    let (sender, receiver) = tokio::sync::mpsc::channel(MAX_QUEUED_JOBS);
+    std::thread::spawn(move || {
+        for input in inputs {
+            if sender.blocking_send(input).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Takes the URL arguments out of `cli` rather than copying them.
+fn gather_inputs(
+    cli: &mut Cli,
+    input_lines: Option<input::Lines>,
+    clipboard_fallback: bool,
+) -> Result<Peekable<Inputs>, FlomError> {
+    let args = std::mem::take(&mut cli.urls)
+        .into_iter()
+        .map(|url| Ok(input::InputLine::new(url)));
+    let mut inputs = (Box::new(args.chain(input_lines.into_iter().flatten())) as Inputs).peekable();
+
+    if inputs.peek().is_none() && !cli.has_id_input() && !io::stdin().is_terminal() {
+        inputs = (Box::new(input::Lines::stdin()) as Inputs).peekable();
+    }
+
+    if inputs.peek().is_none()
+        && !cli.has_id_input()
+        && clipboard_fallback
+        && io::stdin().is_terminal()
+        && let Some(url) =
+            clipboard::read_text().and_then(|text| extract_music_urls(&text).into_iter().next())
+        && prompt::confirm(
+            &format!("Convert the link on your clipboard? ({url})"),
+            true,
+        )?
+    {
+        inputs = (Box::new(std::iter::once(Ok(input::InputLine::new(url)))) as Inputs).peekable();
+    }
+
+    Ok(inputs)
+}
+
+/// Resolves an `--id` lookup to the entity's link on its own platform, so it
+/// can go through the usual URL pipeline (answered from the converter's memo).
+async fn resolve_id_input(
+    converter: &MusicConverter,
+    platform: &str,
+    entity_type: EntityType,
+    id: &str,
+) -> FlomResult<String> {
+    let query = EntityQuery::new(platform, entity_type, id)?;
+    let response = converter.fetch_by_id(&query).await?;
+    Ok(response
+        .links_by_platform
+        .get(&query.platform)
+        .map(|link| link.url.clone())
+        .unwrap_or(response.page_url))
+}
+
+/// A link for the track with `isrc`: from the entity database when the ISRC
+/// was seen before, else through Spotify's search, remembering it for next time.
+async fn resolve_isrc_input(
+    converter: &MusicConverter,
+    config: &flom_config::FlomConfigData,
+    isrc: &str,
+) -> FlomResult<String> {
+    let isrc = normalize_isrc(isrc)?;
+    if let Some(response) = converter.lookup_isrc(&isrc) {
+        let links = &response.links_by_platform;
+        return Ok(links
+            .get("spotify")
+            .or_else(|| links.values().next())
+            .map(|link| link.url.clone())
+            .unwrap_or(response.page_url));
+    }
+    let spotify = spotify_for_reading(config).await?;
+    let url = spotify
+        .track_by_isrc(&isrc)
+        .await?
+        .ok_or_else(|| FlomError::UnsupportedInput(format!("no track found for ISRC {isrc}")))?;
+    converter.fetch_links(&url).await?;
+    converter.remember_isrc(&url, &isrc);
+    Ok(url)
+}
+
+fn resolve_or_prompt_odesli_key(config: &mut flom_config::FlomConfigData) -> Option<String> {
+    // Check environment variable first
+    if let Ok(value) = std::env::var("FLOM_ODESLI_KEY")
+        && !value.trim().is_empty() {
+            return Some(value);
+        }
+
+    // If config file exists, use its value (never prompt)
+    if config_exists().unwrap_or(false) {
+        return config.api.odesli_key.clone();
+    }
+
+    // Config file doesn't exist - first time setup
+    println!(
+        "{} Let's configure your flom settings",
+        style("First-time setup:").bold().cyan()
+    );
+
+    let input = prompt::text("Odesli API key (optional, press Enter to skip)").unwrap_or_default();
+    let key = Some(input.trim().to_string()).filter(|key| !key.is_empty());
+    let target = prompt_default_target();
+    let country = prompt_user_country();
+
+    // Only what was asked for goes into the user config; the merged config
+    // also holds system, project and --set values that belong elsewhere.
+    let answers = [
+        ("api.odesli_key", key.as_deref()),
+        ("default.target", target.as_deref()),
+        ("default.user_country", country.as_deref()),
+    ];
+    let changes: Vec<_> = answers
+        .into_iter()
+        .filter(|(_, value)| value.is_some())
+        .collect();
+
+    // Always create config file on first run
+    if let Err(err) = update_config_values(&changes) {
+        report_warning(&err);
+    } else {
+        println!(
+            "{} Config file created at ~/.flom/config.toml",
+            style("✓").green()
+        );
+    }
+
+    if key.is_some() {
+        config.api.odesli_key = key;
+    }
+    if target.is_some() {
+        config.default.target = target;
+    }
+    if country.is_some() {
+        config.default.user_country = country;
+    }
+
+    config.api.odesli_key.clone()
+}
+
+/// Asks for `default.target` during first-time setup; `None` keeps asking per run.
+fn prompt_default_target() -> Option<String> {
+    let mut options = MusicConverter::known_targets();
+    options.sort_by(|a, b| a.label.cmp(&b.label));
+    let mut labels: Vec<String> = options.iter().map(|opt| opt.label.clone()).collect();
+    labels.push("Songlink page".to_string());
+    labels.push("No default (choose on each run)".to_string());
+
+    let selection = prompt::select("Default target platform", &labels).ok()?;
+    match options.get(selection) {
+        Some(option) => Some(option.key.clone()),
+        None if selection == labels.len() - 2 => Some("songlink".to_string()),
+        None => None,
+    }
+}
+
+/// Asks for `default.user_country` during first-time setup until it is a valid
+/// code or skipped. Invalid input is answered with a suggestion.
+fn prompt_user_country() -> Option<String> {
+    loop {
+        let input = prompt::text("Your country, e.g. US or JP (press Enter for US)").ok()?;
+        if input.trim().is_empty() {
+            return None;
+        }
+        match validate_country_code(&input) {
+            Ok(code) => return Some(code),
+            Err(err) => eprintln!("{} {err}", style("Invalid:").red()),
+        }
+    }
+}
+
+fn unlock_config_if_needed(cli: &Cli) -> FlomResult<()> {
+    // encrypt/decrypt ask for their own key
+    if let Some(Commands::Config {
+        action: ConfigAction::Encrypt { .. } | ConfigAction::Decrypt { .. },
+    }) = &cli.command
+    {
+        return Ok(());
+    }
+    if !is_config_encrypted()? || resolve_config_key().is_some() || !io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    let passphrase = prompt::password("Config passphrase", false)?;
+    set_config_key(ConfigKey::Passphrase(passphrase));
+    Ok(())
+}
+
+fn prompt_config_key(identity: Option<PathBuf>, confirm: bool) -> FlomResult<ConfigKey> {
+    if let Some(path) = identity {
+        return Ok(ConfigKey::Identity(path));
+    }
+    if let Some(key) = resolve_config_key() {
+        return Ok(key);
+    }
+
+    let passphrase = prompt::password("Config passphrase", confirm)?;
+    Ok(ConfigKey::Passphrase(passphrase))
+}
+
+fn handle_config_command(action: ConfigAction) -> FlomResult<()> {
+    match action {
+        ConfigAction::Get { key } => {
+            let config = load_config()?;
+            let value = get_nested_config_value(&config, &key);
+            match value {
+                Some(v) => println!("{} = {}", key, v),
+                None => println!("{} = <null>", key),
+            }
+            Ok(())
+        }
+        ConfigAction::Set { key, value } => {
+            set_config_value(&key, &value)?;
+            println!("{} Set {} = {}", style("✓").green(), key, value);
+            Ok(())
+        }
+        ConfigAction::List => {
+            let config = load_config()?;
+            println!("Current configuration:");
+            println!("\n[api]");
+            println!(
+                "odesli_key = {}",
+                config.api.odesli_key.as_deref().unwrap_or("<null>")
+            );
+            println!("\n[default]");
+            println!(
+                "target = {}",
+                config.default.target.as_deref().unwrap_or("<null>")
+            );
+            println!(
+                "user_country = {}",
+                config.default.user_country.as_deref().unwrap_or("<null>")
+            );
+            println!(
+                "locale = {}",
+                config.default.locale.as_deref().unwrap_or("<null>")
+            );
+            println!(
+                "clipboard_fallback = {}",
+                config.default.clipboard_fallback.unwrap_or(false)
+            );
+            println!("\n[output]");
+            println!("simple = {}", config.output.simple.unwrap_or(false));
+            println!("romanize = {}", config.output.romanize.unwrap_or(false));
+            println!(
+                "format = {}",
+                config.output.format.as_deref().unwrap_or("human")
+            );
+            println!(
+                "template = {}",
+                config.output.template.as_deref().unwrap_or("<null>")
+            );
+            println!(
+                "hyperlinks = {}",
+                config
+                    .output
+                    .hyperlinks
+                    .map_or("auto".to_string(), |v| v.to_string())
+            );
+            println!("icons = {}", config.output.icons.unwrap_or(false));
+            println!("\n[odesli]");
+            println!(
+                "song_if_single = {}",
+                config.odesli.song_if_single.unwrap_or(false)
+            );
+            println!("entity_db = {}", config.odesli.entity_db.unwrap_or(true));
+            println!(
+                "cache_ttl_secs = {}",
+                config.odesli.cache_ttl_secs.unwrap_or(86_400)
+            );
+            println!("\n[matching]");
+            println!(
+                "min_score = {}",
+                config
+                    .matching
+                    .min_score
+                    .map_or("<null>".to_string(), |v| v.to_string())
+            );
+            println!(
+                "duration_tolerance_secs = {}",
+                config
+                    .matching
+                    .duration_tolerance_secs
+                    .map_or("<null>".to_string(), |v| v.to_string())
+            );
+            println!("strict = {}", config.matching.strict.unwrap_or(false));
+            println!("\n[history]");
+            println!("enabled = {}", config.history.enabled.unwrap_or(true));
+            println!("\n[log]");
+            println!("enabled = {}", config.log.enabled.unwrap_or(false));
+            println!("path = {}", config.log.path.as_deref().unwrap_or("<null>"));
+            println!(
+                "max_size = {}",
+                config
+                    .log
+                    .max_size
+                    .map_or("<null>".to_string(), |v| v.to_string())
+            );
+            println!(
+                "keep = {}",
+                config
+                    .log
+                    .keep
+                    .map_or("<null>".to_string(), |v| v.to_string())
+            );
+            println!("\n[post]");
+            println!(
+                "template = {}",
+                config.post.template.as_deref().unwrap_or("<null>")
+            );
+            println!("\n[spotify]");
+            println!(
+                "client_id = {}",
+                config.spotify.client_id.as_deref().unwrap_or("<null>")
+            );
+            let secret = |value: &Option<String>| if value.is_some() { "<set>" } else { "<null>" };
+            println!("client_secret = {}", secret(&config.spotify.client_secret));
+            println!("refresh_token = {}", secret(&config.spotify.refresh_token));
+            println!("\n[signing]");
+            println!("key = {}", secret(&config.signing.key));
+            Ok(())
+        }
+        ConfigAction::Edit => {
+            open_in_editor()?;
+            Ok(())
+        }
+        ConfigAction::Ui => config_ui::run(),
+        ConfigAction::Encrypt { identity } => {
+            let key = prompt_config_key(identity, true)?;
+            encrypt_config(&key)?;
+            println!("{} Config file encrypted", style("✓").green());
+            Ok(())
+        }
+        ConfigAction::Decrypt { identity } => {
+            let key = prompt_config_key(identity, false)?;
+            decrypt_config(&key)?;
+            println!("{} Config file decrypted", style("✓").green());
+            Ok(())
+        }
+    }
+}
+
+/// Directory of the response cache under `~/.flom`.
+const CACHE_DIR: &str = "cache";
+
+fn handle_cache_command(
+    action: &CacheAction,
+    config: &flom_config::FlomConfigData,
+) -> FlomResult<()> {
+    let cache = ResponseCache::new(
+        flom_config::flom_dir()?.join(CACHE_DIR),
+        resolve_user_country(config),
+        Duration::from_secs(resolve_cache_ttl(config)),
+    );
+    match action {
+        CacheAction::Stats => {
+            let stats = cache.stats()?;
+            println!(
+                "{} cached responses, {}",
+                stats.entries,
+                format_size(stats.bytes)
+            );
+            if stats.expired > 0 {
+                println!("{} expired; `flom cache prune` removes them", stats.expired);
+            }
+        }
+        CacheAction::Clear => println!("Removed {} cached responses", cache.clear()?),
+        CacheAction::Prune => println!("Removed {} expired responses", cache.prune()?),
+    }
+    Ok(())
+}
+
+/// `bytes` in the largest unit that keeps the number at least 1.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+fn handle_history_command(action: &HistoryAction) -> FlomResult<()> {
+    match action {
+        HistoryAction::Pick => {
+            let mut entries = history::load()?;
+            if entries.is_empty() {
+                println!("No conversions recorded yet");
+                return Ok(());
+            }
+            entries.reverse();
+
+            let items: Vec<String> = entries
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "{}  {}",
+                        entry.summary(),
+                        entry.result.target_url.as_deref().unwrap_or("")
+                    )
+                })
+                .collect();
+            let selection = prompt::fuzzy_select("Search history", &items)?;
+
+            if let Some(index) = selection {
+                print_result(
+                    &entries[index].result,
+                    output::Layout::Simple,
+                    OutputFormat::Human,
+                );
+            }
+            Ok(())
+        }
+        HistoryAction::List { label, limit } => {
+            let entries = history::load()?;
+            let matching: Vec<_> = history::filter_by_label(&entries, label.as_deref()).collect();
+            print_history_entries(&matching, *limit);
+            Ok(())
+        }
+        HistoryAction::Search { words, limit } => {
+            let entries = history::load()?;
+            let matching: Vec<_> = history::search(&entries, &words.join(" ")).collect();
+            if matching.is_empty() {
+                println!("No conversions match {}", words.join(" "));
+            }
+            print_history_entries(&matching, *limit);
+            Ok(())
+        }
+        HistoryAction::Rerun { .. } => unreachable!("rerun is dispatched once a converter exists"),
+    }
+}
+
+/// Prints history entries oldest first, keeping only the last `limit`.
+fn print_history_entries(entries: &[&history::HistoryEntry], limit: Option<usize>) {
+    let skip = limit.map_or(0, |limit| entries.len().saturating_sub(limit));
+    for entry in &entries[skip..] {
+        let label = entry
+            .label
+            .as_deref()
+            .map(|label| format!(" [{label}]"))
+            .unwrap_or_default();
+        println!(
+            "{} {} {}{}",
+            style(format!("#{}", entry.id)).dim(),
+            entry.recorded_at.format("%Y-%m-%d %H:%M"),
+            entry.summary(),
+            style(label).cyan()
+        );
+        if let Some(url) = &entry.result.target_url {
+            println!("  {} {url}", style("URL:").dim());
+        }
+    }
+}
+
+/// Converts the source link of history entry `id` to `to`, or to the
+/// entry's own target, and records the new result under the entry's label.
+async fn rerun_history(
+    converter: &MusicConverter,
+    id: usize,
+    to: Option<&str>,
+    record: bool,
+    layout: output::Layout<'_>,
+    format: OutputFormat,
+) -> FlomResult<()> {
+    let entries = history::load()?;
+    let entry = history::find(&entries, id)?;
+    let target = to
+        .or(entry.result.target_platform.as_deref())
+        .unwrap_or("songlink");
+    let results = process_url(converter, &entry.result.source_url, Some(target), None).await?;
+    print_results(&results, layout, format);
+    if record {
+        history::append(&results, entry.label.as_deref())?;
+    }
+    Ok(())
+}
+
+/// Converts the feed once, or every `every` minutes, printing only entries with
+/// music links that were not part of an earlier digest.
+async fn run_feed(
+    converter: &MusicConverter,
+    feed_url: &str,
+    target: &str,
+    format: DigestFormat,
+    every: Option<u64>,
+    order: SortOrder,
+) -> FlomResult<()> {
+    let mut seen = HashSet::new();
+    let scheduler = Scheduler::default();
+    loop {
+        let xml = input::read_source(feed_url).await?;
+        let mut digest = Vec::new();
+        for entry in feed::parse_feed(&xml)? {
+            let urls: Vec<_> = entry
+                .urls
+                .iter()
+                .filter(|url| seen.insert(url.to_string()))
+                .collect();
+            if urls.is_empty() {
+                continue;
+            }
+            let mut digest_entry = feed::DigestEntry::new(&entry);
+            for url in urls {
+                let outcome = scheduled_process_url(
+                    &scheduler,
+                    Priority::Background,
+                    converter,
+                    url,
+                    Some(target),
+                    None,
+                )
+                .await
+                .result
+                .map(|mut results| {
+                    output::sort_results(&mut results, order);
+                    results
+                });
+                digest_entry.push(url, outcome);
+            }
+            digest.push(digest_entry);
+        }
+
+        if !digest.is_empty() || every.is_none() {
+            match format {
+                DigestFormat::Markdown => print!("{}", feed::render_markdown(feed_url, &digest)),
+                DigestFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&digest).map_err(|err| FlomError::Parse(
+                        format!("failed to serialize digest: {err}")
+                    ))?
+                ),
+            }
+        }
+
+        let Some(minutes) = every else {
+            return Ok(());
+        };
+        tokio::time::sleep(Duration::from_secs(minutes.max(1) * 60)).await;
+    }
+}
+
+async fn handle_post_command(
+    url: &str,
+    template: Option<String>,
+    config: &mut flom_config::FlomConfigData,
+    flags: ConverterFlags,
+) -> FlomResult<()> {
+    let template = template
+        .or_else(|| config.post.template.clone())
+        .unwrap_or_else(|| post::DEFAULT_TEMPLATE.to_string());
+    let converter = build_converter(config, flags);
+    let response = converter.fetch_links(url).await?;
+    println!("{}", post::render(&template, &response)?);
+    Ok(())
+}
+
+/// Converts a podcast episode link to the same episode on `to`.
+async fn run_podcast(
+    config: &flom_config::FlomConfigData,
+    url: &str,
+    to: &str,
+    strict: bool,
+    layout: output::Layout<'_>,
+    format: OutputFormat,
+) -> FlomResult<()> {
+    let target = PodcastPlatform::from_key(to)
+        .ok_or_else(|| FlomError::InvalidInput(format!("unknown podcast target: {to}")))?;
+    let link = EpisodeLink::parse(url)
+        .ok_or_else(|| FlomError::UnsupportedInput(format!("not a podcast episode link: {url}")))?;
+    let uses_spotify = [link.platform, target].contains(&PodcastPlatform::Spotify);
+    let spotify = if uses_spotify {
+        Some(spotify_for_reading(config).await?)
+    } else {
+        None
+    };
+    let converter = PodcastConverter::new(
+        reqwest::Client::new(),
+        &resolve_user_country(config),
+        spotify,
+    );
+    let result = converter.convert(url, target).await?;
+    if (strict || resolve_strict(config))
+        && let Some(warning) = &result.warning
+    {
+        return Err(FlomError::UnsupportedInput(format!(
+            "strict mode: {warning}"
+        )));
+    }
+    print_header(format);
+    print_results(&[result], layout, format);
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct RadioLink {
+    platform: &'static str,
+    url: String,
+}
+
+/// Prints the radio or mix link of every platform that has one for the track.
+async fn run_similar(
+    converter: &MusicConverter,
+    url: &str,
+    to: Option<&str>,
+    format: OutputFormat,
+) -> FlomResult<()> {
+    let platforms = match to {
+        Some(target) => vec![
+            Platform::from_key(target)
+                .ok_or_else(|| FlomError::InvalidInput(format!("unknown target: {target}")))?,
+        ],
+        None => Platform::ALL.to_vec(),
+    };
+    let response = converter.fetch_links(url).await?;
+    let links: Vec<_> = platforms
+        .into_iter()
+        .filter_map(|platform| {
+            let link = response.links_by_platform.get(platform.key())?;
+            // Stations are seeded with songs; albums have none.
+            if !link.entity_unique_id.contains("_SONG::") {
+                return None;
+            }
+            let result =
+                MusicConverter::convert_from_response(&response, url, platform.key()).ok()?;
+            let radio = build_radio_url(platform, result.target_id.as_deref()?).ok()?;
+            Some(RadioLink {
+                platform: platform.key(),
+                url: radio,
+            })
+        })
+        .collect();
+    if links.is_empty() {
+        return Err(FlomError::UnsupportedInput(
+            "no radio or mix links available for this track".to_string(),
+        ));
+    }
+
+    if format == OutputFormat::Csv {
+        println!("platform,url");
+    }
+    for link in &links {
+        match format {
+            OutputFormat::Json => match serde_json::to_string(link) {
+                Ok(json) => println!("{json}"),
+                Err(err) => report_error(&err),
+            },
+            OutputFormat::Csv => println!("{},{}", link.platform, output::csv_field(&link.url)),
+            OutputFormat::Markdown => println!("- [{}]({})", link.platform, link.url),
+            OutputFormat::Human | OutputFormat::Gha => {
+                println!(
+                    "{} {}",
+                    style(format!("{}:", link.platform)).cyan(),
+                    link.url
+                )
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_track_watch(
+    url: &str,
+    target: Option<String>,
+    notify: bool,
+    config: &mut flom_config::FlomConfigData,
+    flags: ConverterFlags,
+) -> FlomResult<()> {
+    let target = target.ok_or_else(|| {
+        FlomError::InvalidInput("track-watch needs a platform; pass --to".to_string())
+    })?;
+    let key = MusicConverter::normalize_target(&target)
+        .ok_or_else(|| FlomError::InvalidInput(format!("unknown target: {target}")))?;
+    let converter = build_converter(config, flags);
+    let response = converter.fetch_links(url).await?;
+    if let Some(link) = response.links_by_platform.get(&key) {
+        println!("{} Already available: {}", style("✓").green(), link.url);
+        return Ok(());
+    }
+    let mut watches = trackwatch::load()?;
+    let id = watches.add(trackwatch::TrackWatch::new(url, &key, &response, notify));
+    trackwatch::save(&watches)?;
+    println!(
+        "{} Watching #{id}; run `flom watch-run` to check again",
+        style("✓").green()
+    );
+    Ok(())
+}
+
+async fn run_track_watches(converter: &MusicConverter) -> FlomResult<()> {
+    let mut watches = trackwatch::load()?;
+    let mut pending = Vec::with_capacity(watches.entries.len());
+    for mut watch in std::mem::take(&mut watches.entries) {
+        let response = match converter.fetch_links(&watch.url).await {
+            Ok(response) => response,
+            Err(err) => {
+                report_failure(&watch.url, &err);
+                pending.push(watch);
+                continue;
+            }
+        };
+        let Some(link) = response.links_by_platform.get(&watch.target) else {
+            watch.last_checked = Some(chrono::Utc::now());
+            pending.push(watch);
+            continue;
+        };
+        println!(
+            "{} {} is now on {}: {}",
+            style("Available:").green(),
+            watch.summary(),
+            watch.target,
+            link.url
+        );
+        if watch.notify
+            && let Err(err) = notify::desktop(
+                "flom: track available",
+                &format!("{} is now on {}", watch.summary(), watch.target),
+            )
+        {
+            report_warning(&err);
+        }
+    }
+    if !pending.is_empty() {
+        println!("{} still waiting", pending.len());
+    }
+    watches.entries = pending;
+    trackwatch::save(&watches)
+}
+
+async fn run_dupes(
+    converter: &MusicConverter,
+    input: &str,
+    thresholds: &MatchThresholds,
+    format: OutputFormat,
+) -> FlomResult<()> {
+    let mut tracks = dupes::parse_tracks(&input::read_source(input).await?);
+    if tracks.is_empty() {
+        return Err(FlomError::InvalidInput(
+            "no tracks found in the input".to_string(),
+        ));
+    }
+    // Only entries the list doesn't describe need a lookup.
+    for track in &mut tracks {
+        if track.title.is_some() && track.artist.is_some() {
+            continue;
+        }
+        let Some(url) = track.url.clone() else {
+            continue;
+        };
+        match converter.fetch_links(&url).await {
+            Ok(response) => {
+                track.complete_from(&response);
+                if let Some(isrc) = &track.isrc {
+                    converter.remember_isrc(&url, isrc);
+                }
+            }
+            Err(err) => report_failure(&url, &err),
+        }
+    }
+
+    let groups = dupes::find_duplicates(&tracks, thresholds);
+    if format == OutputFormat::Json {
+        for group in &groups {
+            match serde_json::to_string(group) {
+                Ok(json) => println!("{json}"),
+                Err(err) => report_error(&err),
+            }
+        }
+        return Ok(());
+    }
+    for group in &groups {
+        let reason = match group.kind {
+            dupes::MatchKind::Isrc => format!("same ISRC {}", group.key),
+            dupes::MatchKind::TitleArtist => "same title and artist".to_string(),
+            dupes::MatchKind::Fuzzy => "similar title and artist".to_string(),
+        };
+        println!("{} {reason}", style("Probable duplicates:").yellow());
+        for track in &group.tracks {
+            println!(
+                "  {} {}",
+                style(format!("line {}", track.line)).dim(),
+                track.summary()
+            );
+            if let Some(url) = &track.url {
+                println!("    {} {url}", style("URL:").dim());
+            }
+        }
+    }
+    println!(
+        "{} {} tracks, {} duplicate groups",
+        style("Summary:").bold(),
+        tracks.len(),
+        groups.len()
+    );
+    Ok(())
+}
+
+/// Converts both lists to one platform and reports the tracks added, removed
+/// or swapped for another version between them.
+async fn run_diff(
+    converter: &MusicConverter,
+    old: &str,
+    new: &str,
+    target: Option<String>,
+    format: OutputFormat,
+) -> FlomResult<()> {
+    // Tracks are only comparable as entities of a single platform.
+    let target = target
+        .as_deref()
+        .and_then(MusicConverter::normalize_target)
+        .ok_or_else(|| {
+            FlomError::InvalidInput("diff needs a single platform; pass --to".to_string())
+        })?;
+    let (old_content, new_content) =
+        tokio::try_join!(input::read_source(old), input::read_source(new))?;
+
+    let scheduler = Scheduler::default();
+    let mut failed = 0usize;
+    let mut lists = [Vec::new(), Vec::new()];
+    for (content, list) in [old_content, new_content].iter().zip(&mut lists) {
+        for (index, line) in content.lines().enumerate() {
+            let Some(url) = extract_music_urls(line).into_iter().next() else {
+                continue;
+            };
+            let outcome = scheduled_process_url(
+                &scheduler,
+                Priority::Batch,
+                converter,
+                &url,
+                Some(&target),
+                None,
+            )
+            .await;
+            match outcome.result {
+                Ok(results) => list.extend(
+                    results
+                        .iter()
+                        .map(|result| listdiff::ListEntry::new(index + 1, result)),
+                ),
+                Err(err) => {
+                    report_final_failure(&url, outcome.attempts, &err);
+                    failed += 1;
+                }
+            }
+        }
     }
+    let [old_entries, new_entries] = lists;
+    let diff = listdiff::diff_lists(old_entries, new_entries);
 
-    let mut config = match load_config() {
-        Ok(config) => config,
-        Err(err) => {
-            eprintln!("{} {err}", style("Error:").red());
-            std::process::exit(1);
+    if format == OutputFormat::Json {
+        match serde_json::to_string(&diff) {
+            Ok(json) => println!("{json}"),
+            Err(err) => report_error(&err),
         }
-    };
-
-    let mut urls = gather_inputs(&cli).unwrap_or_else(|err| {
-        eprintln!("{} {err}", style("Error:").red());
-        std::process::exit(1);
-    });
-
-    if urls.is_empty() {
-        eprintln!("{} no input URLs provided", style("Error:").red());
-        std::process::exit(1);
+    } else {
+        let link = |entry: &listdiff::ListEntry| {
+            ui::link(entry.target_url.as_deref().unwrap_or(&entry.url))
+        };
+        for entry in &diff.added {
+            println!(
+                "{} {}  {}",
+                style("+").green(),
+                entry.summary(),
+                link(entry)
+            );
+        }
+        for entry in &diff.removed {
+            println!("{} {}  {}", style("-").red(), entry.summary(), link(entry));
+        }
+        for change in &diff.changed {
+            println!(
+                "{} {}  {} -> {}",
+                style("~").yellow(),
+                change.new.summary(),
+                link(&change.old),
+                link(&change.new)
+            );
+        }
+        println!(
+            "{} {} added, {} removed, {} changed, {} unchanged",
+            style("Summary:").bold(),
+            diff.added.len(),
+            diff.removed.len(),
+            diff.changed.len(),
+            diff.unchanged
+        );
     }
-
-    if cli.shorten {
-        run_shorten(&urls).await;
-        return;
+    if failed > 0 {
+        print_note(
+            &format!("{failed} link(s) could not be converted and are left out of the diff"),
+            format,
+        );
     }
+    Ok(())
+}
 
-    let api_key = resolve_or_prompt_odesli_key(&mut config);
-    let converter = MusicConverter::new(api_key, &config);
-
-    let simple = cli.simple || resolve_simple_output(&config).unwrap_or(false);
-    let default_target = resolve_default_target(&config);
+/// Converts each track of a recognition-app export, searching by title and
+/// artist for tracks that come without a streaming link.
+async fn run_import(
+    converter: &MusicConverter,
+    input: &str,
+    target: Option<String>,
+    thresholds: &MatchThresholds,
+    layout: output::Layout<'_>,
+    format: OutputFormat,
+    order: SortOrder,
+) -> FlomResult<()> {
+    // Hundreds of tracks are too many to prompt for one by one.
+    let target = target
+        .ok_or_else(|| FlomError::InvalidInput("import needs a platform; pass --to".to_string()))?;
+    let tracks = import::parse_export(&input::read_source(input).await?)?;
+    if tracks.is_empty() {
+        return Err(FlomError::InvalidInput(
+            "no tracks found in the input".to_string(),
+        ));
+    }
 
+    let scheduler = Scheduler::default();
     let mut success = 0usize;
-    let mut failed = 0usize;
-
-    for url in urls.drain(..) {
-        match process_url(
-            &converter,
+    let mut failures = Vec::new();
+    print_header(format);
+    for track in &tracks {
+        let url = match import::music_url(track) {
+            Some(url) => Ok(url.to_string()),
+            None => match track.as_ref() {
+                Some(reference) => converter.search_link(&reference, thresholds).await,
+                None => Err(FlomError::InvalidInput(
+                    "missing title or artist".to_string(),
+                )),
+            },
+        };
+        let url = match url {
+            Ok(url) => url,
+            Err(err) => {
+                failures.push((track.summary(), 1, err));
+                continue;
+            }
+        };
+        let outcome = scheduled_process_url(
+            &scheduler,
+            Priority::Batch,
+            converter,
             &url,
-            cli.to.as_deref(),
-            default_target.as_deref(),
-            simple,
+            Some(&target),
+            None,
         )
-        .await
-        {
-            Ok(count) => success += count,
-            Err(err) => {
-                failed += 1;
-                eprintln!("{} {url}: {err}", style("Failed").red());
+        .await;
+        match outcome.result {
+            Ok(mut results) => {
+                output::sort_results(&mut results, order);
+                print_results(&results, layout, format);
+                success += results.len();
             }
+            Err(err) => failures.push((track.summary(), outcome.attempts, err)),
         }
     }
 
-    print_summary(success + failed, success, failed);
+    for (track, attempts, err) in &failures {
+        report_final_failure(track, *attempts, err);
+    }
+    let failed = failures.len();
+    print_summary(success + failed, success, failed, format);
+    Ok(())
 }
 
-fn gather_inputs(cli: &Cli) -> Result<Vec<String>, FlomError> {
-    let mut urls = cli.urls.clone();
+struct ArtistOptions<'a> {
+    limit: u32,
+    layout: output::Layout<'a>,
+    format: OutputFormat,
+    order: SortOrder,
+}
 
-    if let Some(path) = &cli.input {
-        let content = fs::read_to_string(path)
-            .map_err(|err| FlomError::InvalidInput(format!("failed to read input file: {err}")))?;
-        urls.extend(parse_lines(&content));
+async fn run_artist(
+    converter: &MusicConverter,
+    config: &flom_config::FlomConfigData,
+    url: &str,
+    target: Option<String>,
+    options: &ArtistOptions<'_>,
+) -> FlomResult<()> {
+    let link = ArtistLink::parse(url)
+        .ok_or_else(|| FlomError::UnsupportedInput(format!("not an artist link: {url}")))?;
+    let target = target
+        .ok_or_else(|| FlomError::InvalidInput("artist needs a platform; pass --to".to_string()))?;
+    let spotify = match link.platform {
+        Platform::Spotify => Some(spotify_for_reading(config).await?),
+        _ => None,
+    };
+    let (name, releases) = artist_releases(
+        converter.http_client(),
+        &link,
+        spotify.as_ref(),
+        &resolve_user_country(config),
+        options.limit,
+    )
+    .await?;
+    if releases.is_empty() {
+        return Err(FlomError::InvalidInput(
+            "no releases found for the artist".to_string(),
+        ));
+    }
+    if !name.is_empty() {
+        eprintln!("{} {name}", style("Artist:").cyan());
     }
 
-    if urls.is_empty() && !io::stdin().is_terminal() {
-        let mut buffer = String::new();
-        io::stdin()
-            .read_to_string(&mut buffer)
-            .map_err(|err| FlomError::InvalidInput(format!("failed to read stdin: {err}")))?;
-        urls.extend(parse_lines(&buffer));
+    let scheduler = Scheduler::default();
+    let mut success = 0usize;
+    let mut failures = Vec::new();
+    print_header(options.format);
+    for release in &releases {
+        let outcome = scheduled_process_url(
+            &scheduler,
+            Priority::Batch,
+            converter,
+            &release.url,
+            Some(&target),
+            None,
+        )
+        .await;
+        match outcome.result {
+            Ok(mut results) => {
+                output::sort_results(&mut results, options.order);
+                print_results(&results, options.layout, options.format);
+                success += results.len();
+            }
+            Err(err) => failures.push((release.title.as_str(), outcome.attempts, err)),
+        }
     }
 
-    Ok(urls)
+    for (release, attempts, err) in &failures {
+        report_final_failure(release, *attempts, err);
+    }
+    let failed = failures.len();
+    print_summary(success + failed, success, failed, options.format);
+    Ok(())
 }
 
-fn parse_lines(content: &str) -> Vec<String> {
-    content
-        .lines()
-        .map(str::trim)
-        .filter(|line| !line.is_empty())
-        .map(|line| line.to_string())
-        .collect()
+/// Converts the track found for a text query, via its Apple Music link.
+async fn run_search(
+    converter: &MusicConverter,
+    query: &str,
+    target: Option<&str>,
+    thresholds: &MatchThresholds,
+    layout: output::Layout<'_>,
+    format: OutputFormat,
+    order: SortOrder,
+) -> FlomResult<()> {
+    let url = converter.search_text(query, thresholds).await?;
+    let mut results = process_url(converter, &url, target, None).await?;
+    output::sort_results(&mut results, order);
+    print_header(format);
+    print_results(&results, layout, format);
+    Ok(())
 }
 
-fn resolve_or_prompt_odesli_key(config: &mut flom_config::FlomConfigData) -> Option<String> {
-    // Check environment variable first
-    if let Ok(value) = std::env::var("FLOM_ODESLI_KEY")
-        && !value.trim().is_empty() {
-            return Some(value);
-        }
+async fn run_playlist(
+    converter: &MusicConverter,
+    config: &flom_config::FlomConfigData,
+    url: &str,
+    target: Option<String>,
+    layout: output::Layout<'_>,
+    format: OutputFormat,
+    order: SortOrder,
+) -> FlomResult<()> {
+    let playlist = PlaylistUrl::parse(url).ok_or_else(|| {
+        FlomError::UnsupportedInput(format!(
+            "not a Spotify, Apple Music or YouTube playlist link: {url}"
+        ))
+    })?;
+    let target = target.ok_or_else(|| {
+        FlomError::InvalidInput("playlist needs a platform; pass --to".to_string())
+    })?;
+    let spotify = match playlist {
+        PlaylistUrl::Spotify(_) => Some(spotify_for_reading(config).await?),
+        _ => None,
+    };
+    let tracks = playlist_tracks(converter.http_client(), &playlist, spotify.as_ref()).await?;
+    if tracks.is_empty() {
+        return Err(FlomError::InvalidInput(
+            "no tracks found in the playlist".to_string(),
+        ));
+    }
 
-    // If config file exists, use its value (never prompt)
-    if config_exists().unwrap_or(false) {
-        return config.api.odesli_key.clone();
+    let scheduler = Scheduler::default();
+    let mut success = 0usize;
+    let mut failures = Vec::new();
+    print_header(format);
+    for track in &tracks {
+        let outcome = scheduled_process_url(
+            &scheduler,
+            Priority::Batch,
+            converter,
+            track,
+            Some(&target),
+            None,
+        )
+        .await;
+        match outcome.result {
+            Ok(mut results) => {
+                output::sort_results(&mut results, order);
+                print_results(&results, layout, format);
+                success += results.len();
+            }
+            Err(err) => failures.push((track, outcome.attempts, err)),
+        }
     }
 
-    // Config file doesn't exist - first time setup
-    let theme = ColorfulTheme::default();
-    println!(
-        "{} Let's configure your flom settings",
-        style("First-time setup:").bold().cyan()
-    );
+    for (track, attempts, err) in &failures {
+        report_final_failure(track, *attempts, err);
+    }
+    let failed = failures.len();
+    print_summary(success + failed, success, failed, format);
+    Ok(())
+}
 
-    let input: String = Input::with_theme(&theme)
-        .with_prompt("Odesli API key (optional, press Enter to skip)")
-        .allow_empty(true)
-        .interact_text()
-        .unwrap_or_default();
+struct LibraryOptions<'a> {
+    playlist: Option<&'a str>,
+    playlists_dir: Option<&'a Path>,
+    restart: bool,
+    layout: output::Layout<'a>,
+    format: OutputFormat,
+}
 
-    if !input.trim().is_empty() {
-        config.api.odesli_key = Some(input.clone());
+/// Converts library tracks found by title and artist search, recording each
+/// link so an interrupted run picks up where it stopped.
+async fn run_library(
+    converter: &MusicConverter,
+    input: &str,
+    target: Option<String>,
+    thresholds: &MatchThresholds,
+    options: LibraryOptions<'_>,
+) -> FlomResult<()> {
+    // Playlist files need exactly one link per track.
+    let target = target.ok_or_else(|| {
+        FlomError::InvalidInput("library needs a platform; pass --to".to_string())
+    })?;
+    let key = MusicConverter::normalize_target(&target)
+        .ok_or_else(|| FlomError::InvalidInput(format!("unknown target: {target}")))?;
+    let library = library::parse_library(&input::read_source(input).await?)?;
+    let (indices, playlists): (Vec<usize>, Vec<&library::Playlist>) = match options.playlist {
+        Some(name) => {
+            let playlist = library
+                .playlist(name)
+                .ok_or_else(|| FlomError::InvalidInput(format!("playlist not found: {name}")))?;
+            (playlist.tracks.clone(), vec![playlist])
+        }
+        None => (
+            (0..library.tracks.len()).collect(),
+            library.playlists.iter().collect(),
+        ),
+    };
+    if indices.is_empty() {
+        return Err(FlomError::InvalidInput(
+            "no tracks found in the input".to_string(),
+        ));
     }
 
-    // Always create config file on first run
-    if let Err(err) = save_config(config) {
-        eprintln!("{} {err}", style("Warning:").yellow());
-    } else {
-        println!(
-            "{} Config file created at ~/.flom/config.toml",
-            style("✓").green()
+    let mut progress = library::Progress::load(library::progress_path()?, &key);
+    if options.restart {
+        progress.clear();
+    }
+    let scheduler = Scheduler::default();
+    let mut links = HashMap::new();
+    let mut resumed = 0usize;
+    let mut failures = Vec::new();
+    let total = indices.len();
+    print_header(options.format);
+    for (position, &index) in indices.iter().enumerate() {
+        let track = &library.tracks[index];
+        if links.contains_key(&index) {
+            continue;
+        }
+        eprintln!(
+            "{} {}",
+            style(format!("[{}/{total}]", position + 1)).dim(),
+            track.summary()
         );
+        if let Some(url) = progress.get(track) {
+            links.insert(index, url.to_string());
+            resumed += 1;
+            continue;
+        }
+        let url = match track.as_ref() {
+            Some(reference) => converter.search_link(&reference, thresholds).await,
+            None => Err(FlomError::InvalidInput(
+                "missing title or artist".to_string(),
+            )),
+        };
+        let url = match url {
+            Ok(url) => url,
+            Err(err) => {
+                failures.push((track.summary(), 1, err));
+                continue;
+            }
+        };
+        let outcome = scheduled_process_url(
+            &scheduler,
+            Priority::Batch,
+            converter,
+            &url,
+            Some(&key),
+            None,
+        )
+        .await;
+        match outcome.result {
+            Ok(results) => {
+                print_results(&results, options.layout, options.format);
+                if let Some(link) = results.first().and_then(|r| r.target_url.clone()) {
+                    if let Err(err) = progress.record(track, &link) {
+                        report_warning(&err);
+                    }
+                    links.insert(index, link);
+                }
+            }
+            Err(err) => failures.push((track.summary(), outcome.attempts, err)),
+        }
     }
 
-    config.api.odesli_key.clone()
+    if let Some(dir) = options.playlists_dir {
+        for playlist in &playlists {
+            let path = library::write_playlist(dir, &library, playlist, &links)?;
+            eprintln!("{} Wrote {}", style("✓").green(), path.display());
+        }
+    }
+    for (track, attempts, err) in &failures {
+        report_final_failure(track, *attempts, err);
+    }
+    if resumed > 0 {
+        eprintln!("{resumed} tracks were converted by an earlier run");
+    }
+    let failed = failures.len();
+    print_summary(links.len() + failed, links.len(), failed, options.format);
+    Ok(())
 }
 
-fn handle_config_command(action: ConfigAction) -> FlomResult<()> {
+async fn handle_fav_command(
+    action: FavAction,
+    config: &mut flom_config::FlomConfigData,
+    flags: ConverterFlags,
+) -> FlomResult<()> {
     match action {
-        ConfigAction::Get { key } => {
-            let config = load_config()?;
-            let value = get_nested_config_value(&config, &key);
-            match value {
-                Some(v) => println!("{} = {}", key, v),
-                None => println!("{} = <null>", key),
+        FavAction::Add { url, tags } => {
+            let converter = build_converter(config, flags);
+            let response = converter.fetch_links(&url).await?;
+            let favorite = favorites::Favorite::from_response(&url, &response, tags);
+            let mut favs = favorites::load()?;
+            let id = favs.add(favorite);
+            favorites::save(&favs)?;
+            println!("{} Added favorite #{id}", style("✓").green());
+            Ok(())
+        }
+        FavAction::List { tag } => {
+            let favs = favorites::load()?;
+            for favorite in favs.filtered(tag.as_deref()) {
+                let title = favorite.title.as_deref().unwrap_or("Unknown title");
+                let artist = favorite.artist.as_deref().unwrap_or("Unknown artist");
+                let tags = if favorite.tags.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", favorite.tags.join(", "))
+                };
+                println!(
+                    "{} {title} / {artist}{}",
+                    style(format!("#{}", favorite.id)).dim(),
+                    style(tags).cyan()
+                );
+                println!("  {} {}", style("URL:").dim(), favorite.url);
             }
             Ok(())
         }
-        ConfigAction::Set { key, value } => {
-            set_config_value(&key, &value)?;
-            println!("{} Set {} = {}", style("✓").green(), key, value);
+        FavAction::Remove { id } => {
+            let mut favs = favorites::load()?;
+            if favs.remove(id).is_none() {
+                return Err(FlomError::InvalidInput(format!("no favorite with id {id}")));
+            }
+            favorites::save(&favs)?;
+            println!("{} Removed favorite #{id}", style("✓").green());
             Ok(())
         }
-        ConfigAction::List => {
-            let config = load_config()?;
-            println!("Current configuration:");
-            println!("\n[api]");
-            println!(
-                "odesli_key = {}",
-                config.api.odesli_key.as_deref().unwrap_or("<null>")
-            );
-            println!("\n[default]");
-            println!(
-                "target = {}",
-                config.default.target.as_deref().unwrap_or("<null>")
-            );
-            println!(
-                "user_country = {}",
-                config.default.user_country.as_deref().unwrap_or("<null>")
-            );
-            println!("\n[output]");
-            println!("simple = {}", config.output.simple.unwrap_or(false));
+        FavAction::Copy { id, to } => {
+            let target = to
+                .map(|to| {
+                    MusicConverter::normalize_target(&to)
+                        .ok_or_else(|| FlomError::InvalidInput(format!("unknown target: {to}")))
+                })
+                .transpose()?;
+            let favs = favorites::load()?;
+            let link = favs.get(id)?.link(target.as_deref())?;
+            if !clipboard::write_text(link) {
+                return Err(FlomError::UnsupportedInput(
+                    "no clipboard tool found (pbcopy, wl-copy, xclip, xsel or clip)".to_string(),
+                ));
+            }
+            println!("{} Copied {link}", style("✓").green());
             Ok(())
         }
-        ConfigAction::Edit => {
-            open_in_editor()?;
+        FavAction::Export { to, format, tag } => {
+            let target = to
+                .map(|to| {
+                    MusicConverter::normalize_target(&to)
+                        .ok_or_else(|| FlomError::InvalidInput(format!("unknown target: {to}")))
+                })
+                .transpose()?;
+            let favs = favorites::load()?;
+            let entries = favs.filtered(tag.as_deref());
+            match format {
+                ExportFormat::Csv => {
+                    print!("{}", favorites::export_csv(entries, target.as_deref()))
+                }
+                ExportFormat::Text => {
+                    for favorite in entries {
+                        let url = target
+                            .as_deref()
+                            .and_then(|target| favorite.links.get(target))
+                            .unwrap_or(&favorite.url);
+                        println!("{url}");
+                    }
+                }
+            }
             Ok(())
         }
     }
@@ -239,82 +2979,194 @@ fn get_nested_config_value(config: &flom_config::FlomConfigData, key_path: &str)
         ["api", "odesli_key"] => config.api.odesli_key.clone(),
         ["default", "target"] => config.default.target.clone(),
         ["default", "user_country"] => config.default.user_country.clone(),
+        ["default", "locale"] => config.default.locale.clone(),
+        ["default", "clipboard_fallback"] => {
+            config.default.clipboard_fallback.map(|b| b.to_string())
+        }
         ["output", "simple"] => config.output.simple.map(|b| b.to_string()),
+        ["output", "romanize"] => config.output.romanize.map(|b| b.to_string()),
+        ["output", "format"] => config.output.format.clone(),
+        ["output", "template"] => config.output.template.clone(),
+        ["output", "hyperlinks"] => config.output.hyperlinks.map(|b| b.to_string()),
+        ["output", "icons"] => config.output.icons.map(|b| b.to_string()),
+        ["odesli", "song_if_single"] => config.odesli.song_if_single.map(|b| b.to_string()),
+        ["odesli", "entity_db"] => config.odesli.entity_db.map(|b| b.to_string()),
+        ["odesli", "cache_ttl_secs"] => config.odesli.cache_ttl_secs.map(|v| v.to_string()),
+        ["matching", "min_score"] => config.matching.min_score.map(|v| v.to_string()),
+        ["matching", "duration_tolerance_secs"] => config
+            .matching
+            .duration_tolerance_secs
+            .map(|v| v.to_string()),
+        ["matching", "strict"] => config.matching.strict.map(|v| v.to_string()),
+        ["history", "enabled"] => config.history.enabled.map(|b| b.to_string()),
+        ["log", "enabled"] => config.log.enabled.map(|b| b.to_string()),
+        ["log", "path"] => config.log.path.clone(),
+        ["log", "max_size"] => config.log.max_size.map(|v| v.to_string()),
+        ["log", "keep"] => config.log.keep.map(|v| v.to_string()),
+        ["post", "template"] => config.post.template.clone(),
+        ["spotify", "client_id"] => config.spotify.client_id.clone(),
+        ["spotify", "client_secret"] => config.spotify.client_secret.clone(),
+        ["spotify", "refresh_token"] => config.spotify.refresh_token.clone(),
+        ["signing", "key"] => config.signing.key.clone(),
         _ => None,
     }
 }
 
+/// Runs `process_url` through `scheduler`, so transient failures are retried
+/// and requests to one host are limited.
+async fn scheduled_process_url(
+    scheduler: &Scheduler,
+    priority: Priority,
+    converter: &MusicConverter,
+    url: &str,
+    explicit_target: Option<&str>,
+    default_target: Option<&str>,
+) -> Attempted<Vec<ConversionResult>> {
+    scheduler
+        .run(priority, scheduler::lookup_key(url), || {
+            process_url(converter, url, explicit_target, default_target)
+        })
+        .await
+}
+
 async fn process_url(
     converter: &MusicConverter,
     url: &str,
     explicit_target: Option<&str>,
     default_target: Option<&str>,
-    simple: bool,
-) -> Result<usize, FlomError> {
+) -> Result<Vec<ConversionResult>, FlomError> {
     let response = converter.fetch_links(url).await?;
     let target = explicit_target
         .map(|value| value.to_string())
         .or_else(|| default_target.map(|value| value.to_string()));
 
-    let target_key = if let Some(target) = target {
-        let normalized = target.trim().to_lowercase();
-        if normalized == "all" {
-            "all".to_string()
-        } else if normalized == "songlink" {
-            "songlink".to_string()
+    let targets = match target {
+        Some(target) => parse_targets(&target)?,
+        None => vec![prompt_target(&response)?],
+    };
+    if let [target_key] = targets.as_slice() {
+        return results_for_target(converter, &response, url, target_key);
+    }
+
+    // One lookup serves every target; a platform missing from it doesn't
+    // fail the others, except in strict mode.
+    let mut results = Vec::new();
+    for target_key in &targets {
+        match results_for_target(converter, &response, url, target_key) {
+            Ok(found) => results.extend(found),
+            Err(FlomError::UnsupportedInput(message)) if !converter.is_strict() => {
+                results.push(ConversionResult {
+                    source_url: url.to_string(),
+                    target_platform: Some(target_key.clone()),
+                    page_url: Some(response.page_url.clone()),
+                    warning: Some(message),
+                    ..Default::default()
+                });
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(results)
+}
+
+/// Splits a `--to` value such as `spotify,tidal` into normalized target keys,
+/// dropping duplicates. `all` and `songlink` are kept as they are.
+fn parse_targets(value: &str) -> FlomResult<Vec<String>> {
+    let mut targets: Vec<String> = Vec::new();
+    for target in value
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let normalized = target.to_lowercase();
+        let key = if normalized == "all" || normalized == "songlink" {
+            normalized
         } else {
-            MusicConverter::normalize_target(&target)
+            MusicConverter::normalize_target(target)
                 .ok_or_else(|| FlomError::InvalidInput(format!("unknown target: {target}")))?
+        };
+        if !targets.contains(&key) {
+            targets.push(key);
         }
-    } else {
-        prompt_target(&response)?
+    }
+    if targets.is_empty() {
+        return Err(FlomError::InvalidInput(format!("no target in: {value}")));
+    }
+    Ok(targets)
+}
+
+/// Checks that a target yields one record per link, as `--ordered` needs:
+/// not a list and not `all`.
+fn single_target(target: Option<&str>) -> FlomResult<()> {
+    let Some(target) = target else {
+        return Ok(());
     };
+    match parse_targets(target)?.as_slice() {
+        [key] if key != "all" => Ok(()),
+        _ => Err(FlomError::InvalidInput(format!(
+            "--ordered needs a single target, not: {target}"
+        ))),
+    }
+}
 
+fn results_for_target(
+    converter: &MusicConverter,
+    response: &flom_music::api::odesli::OdesliResponse,
+    url: &str,
+    target_key: &str,
+) -> FlomResult<Vec<ConversionResult>> {
     if target_key == "all" {
-        let mut count = 0;
-        let mut keys: Vec<_> = response.links_by_platform.keys().cloned().collect();
-        keys.sort();
-        for key in keys {
-            let result = MusicConverter::convert_from_response(&response, url, &key)?;
-            print_result(&result, simple);
-            count += 1;
-        }
-        return Ok(count);
+        // A platform may only know the album of a song (or the reverse); such
+        // links would be wrong answers, so `all` leaves them out.
+        let mut results = response
+            .links_by_platform
+            .keys()
+            .map(|key| converter.convert(response, url, key))
+            .collect::<FlomResult<Vec<_>>>()?;
+        results.retain(|result| !result.is_type_mismatch());
+        return Ok(results);
     }
 
     if target_key == "songlink" {
         let result = ConversionResult {
             source_url: url.to_string(),
             target_url: Some(response.page_url.clone()),
-            source_platform: None,
             target_platform: Some("songlink".to_string()),
-            source_info: None,
-            target_info: None,
-            warning: None,
+            page_url: Some(response.page_url.clone()),
+            media_type: MusicConverter::media_type(response),
+            ..Default::default()
         };
-        print_result(&result, simple);
-        return Ok(1);
+        return Ok(vec![result]);
     }
 
-    let result = MusicConverter::convert_from_response(&response, url, &target_key)?;
-    print_result(&result, simple);
-    Ok(1)
+    let result = converter.convert(response, url, target_key)?;
+    Ok(vec![result])
 }
 
 fn prompt_target(response: &flom_music::api::odesli::OdesliResponse) -> Result<String, FlomError> {
-    let mut options = MusicConverter::targets_from_response(response);
+    select_target(
+        "Select target platform",
+        MusicConverter::targets_from_response(response),
+    )
+}
+
+/// Asks for one target for a whole batch. Without a lookup to go by, every
+/// known platform is offered.
+fn prompt_batch_target() -> Result<String, FlomError> {
+    select_target(
+        "Select target platform for all links",
+        MusicConverter::known_targets(),
+    )
+}
+
+fn select_target(prompt: &str, mut options: Vec<TargetOption>) -> Result<String, FlomError> {
     options.sort_by(|a, b| a.label.cmp(&b.label));
 
     let mut labels: Vec<String> = options.iter().map(|opt| opt.label.clone()).collect();
     labels.push("All available".to_string());
     labels.push("Songlink page".to_string());
 
-    let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select target platform")
-        .items(&labels)
-        .default(0)
-        .interact()
-        .map_err(|err| FlomError::InvalidInput(format!("selection failed: {err}")))?;
+    let selection = prompt::select(prompt, &labels)?;
 
     if selection == labels.len() - 2 {
         return Ok("all".to_string());
@@ -326,68 +3178,307 @@ fn prompt_target(response: &flom_music::api::odesli::OdesliResponse) -> Result<S
     Ok(options[selection].key.clone())
 }
 
-fn print_result(result: &ConversionResult, simple: bool) {
-    if simple {
-        if let Some(url) = &result.target_url {
-            println!("{url}");
-        }
-        return;
-    }
-
-    let source_line = format_source_line(result);
-    println!("{} {source_line}", style("From:").cyan());
-    println!("  {} {}", style("URL:").dim(), result.source_url);
-
-    if let Some(target_url) = &result.target_url {
-        println!("{} {}", style("To:").green(), target_url);
-    } else {
-        println!("{} (no target url)", style("To:").red());
+fn print_results(results: &[ConversionResult], layout: output::Layout<'_>, format: OutputFormat) {
+    match output::render_results(results, layout, format) {
+        Ok(rendered) => print!("{rendered}"),
+        Err(err) => report_error(&err),
     }
+}
 
-    if let Some(warning) = &result.warning {
-        println!("{} {warning}", style("Warning:").yellow());
+/// Starts the output of a batch of results; only CSV has a header.
+fn print_header(format: OutputFormat) {
+    if format == OutputFormat::Csv {
+        print!("{}", output::CSV_HEADER);
     }
-
-    println!();
 }
 
-fn format_source_line(result: &ConversionResult) -> String {
-    let platform = result.source_platform.as_deref().unwrap_or("Unknown");
-    if let Some(info) = &result.source_info {
-        let title = info.title.as_deref().unwrap_or("Unknown title");
-        let artist = info.artist.as_deref().unwrap_or("Unknown artist");
-        return format!("{platform} - {title} / {artist}");
+fn print_result(result: &ConversionResult, layout: output::Layout<'_>, format: OutputFormat) {
+    match output::render_result(result, layout, format) {
+        Ok(rendered) => print!("{rendered}"),
+        Err(err) => report_error(&err),
     }
-    platform.to_string()
 }
 
-async fn run_shorten(urls: &[String]) {
-    let client = ShortenClient::new();
+async fn run_shorten(
+    inputs: impl Iterator<Item = FlomResult<input::InputLine>>,
+    verify: bool,
+    format: OutputFormat,
+) {
+    let mut client = ShortenClient::new().with_verify(verify);
+    if let Ok(dir) = flom_config::flom_dir() {
+        client = client.with_state_file(dir.join("shorten-state.json"));
+    }
     let mut success = 0usize;
     let mut failed = 0usize;
+    if format == OutputFormat::Csv {
+        println!("url,short_url");
+    }
 
-    for url in urls {
-        match client.shorten(url).await {
-            Ok(short) => {
-                println!("{} -> {}", url, short);
+    for input in inputs {
+        let input = match input {
+            Ok(input) => input,
+            Err(err) => {
+                report_error(&err);
+                break;
+            }
+        };
+        let url = input.text.as_str();
+        match client.shorten_link(url).await {
+            Ok(link) => {
+                match format {
+                    OutputFormat::Json => match serde_json::to_string(&link) {
+                        Ok(json) => println!("{json}"),
+                        Err(err) => report_error(&err),
+                    },
+                    OutputFormat::Csv => println!(
+                        "{},{}",
+                        output::csv_field(url),
+                        output::csv_field(&link.short_url)
+                    ),
+                    OutputFormat::Markdown => println!("- [{url}]({})", link.short_url),
+                    OutputFormat::Human | OutputFormat::Gha => {
+                        println!("{} -> {}", url, link.short_url)
+                    }
+                }
                 success += 1;
             }
             Err(err) => {
                 failed += 1;
-                eprintln!("{} {url}: {err}", style("Failed").red());
+                report_failure(url, &err);
             }
         }
     }
 
-    print_summary(success + failed, success, failed);
+    print_summary(success + failed, success, failed, format);
 }
 
-fn print_summary(total: usize, success: usize, failed: usize) {
-    println!(
+/// Prints the Odesli response body of each link as one JSON line.
+async fn run_raw(
+    converter: &MusicConverter,
+    inputs: impl Iterator<Item = FlomResult<input::InputLine>>,
+) {
+    let mut success = 0usize;
+    let mut failed = 0usize;
+    for input in inputs {
+        let input = match input {
+            Ok(input) => input,
+            Err(err) => {
+                report_error(&err);
+                break;
+            }
+        };
+        match converter.fetch_raw(&input.text).await {
+            Ok(body) => {
+                println!("{body}");
+                success += 1;
+            }
+            Err(err) => {
+                failed += 1;
+                report_failure(&input.text, &err);
+            }
+        }
+    }
+    print_summary(success + failed, success, failed, OutputFormat::Json);
+}
+
+fn print_summary(total: usize, success: usize, failed: usize, format: OutputFormat) {
+    let summary = format!(
         "{} Total: {} | Success: {} | Failed: {}",
         style("Summary:").bold(),
         total,
         success,
         failed
     );
+    print_note(&summary, format);
+}
+
+/// Prints a line about the run as a whole, to stderr for machine-readable formats.
+fn print_note(note: &str, format: OutputFormat) {
+    // Keep stdout parseable for machine-readable formats
+    if matches!(
+        format,
+        OutputFormat::Json | OutputFormat::Csv | OutputFormat::Markdown
+    ) {
+        eprintln!("{note}");
+    } else {
+        println!("{note}");
+    }
+}
+
+/// Removes results whose target URL is already in `seen`, and returns how many.
+fn drop_seen_targets(results: &mut Vec<ConversionResult>, seen: &mut HashSet<String>) -> usize {
+    let before = results.len();
+    results.retain(|result| {
+        result
+            .target_url
+            .as_ref()
+            .is_none_or(|url| seen.insert(url.clone()))
+    });
+    before - results.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        let mut cli = Cli::try_parse_from(args).unwrap();
+        cli.expand_shortcuts();
+        cli
+    }
+
+    #[test]
+    fn shortcut_subcommands_expand_to_flags() {
+        let cli = parse(&[
+            "flom",
+            "to",
+            "spotify",
+            "https://youtu.be/a",
+            "https://youtu.be/b",
+        ]);
+        assert!(cli.command.is_none());
+        assert_eq!(cli.to.as_deref(), Some("spotify"));
+        assert_eq!(cli.urls.len(), 2);
+
+        let cli = parse(&["flom", "--to", "tidal", "c", "https://youtu.be/a"]);
+        assert_eq!(cli.to.as_deref(), Some("tidal"));
+        assert_eq!(cli.urls, vec!["https://youtu.be/a"]);
+
+        let cli = parse(&["flom", "s", "--verify", "https://example.com/long"]);
+        assert!(cli.shorten && cli.verify);
+        assert_eq!(cli.urls, vec!["https://example.com/long"]);
+
+        let cli = parse(&["flom", "cfg", "list"]);
+        assert!(matches!(cli.command, Some(Commands::Config { .. })));
+    }
+
+    #[test]
+    fn target_lists_are_split_and_normalized() {
+        assert_eq!(
+            parse_targets("spotify, Apple-Music,,spotify").unwrap(),
+            vec!["spotify", "appleMusic"]
+        );
+        assert_eq!(parse_targets("SongLink").unwrap(), vec!["songlink"]);
+        assert!(matches!(
+            parse_targets("spotify,napster"),
+            Err(FlomError::InvalidInput(_))
+        ));
+        assert!(parse_targets(" , ").is_err());
+    }
+
+    #[test]
+    fn output_flags_override_the_config() {
+        let mut config = flom_config::FlomConfigData::default();
+        config.output.format = Some("JSON".to_string());
+        let cli = parse(&["flom", "https://youtu.be/a"]);
+        assert_eq!(cli.output_format(&config).unwrap(), OutputFormat::Json);
+        let cli = parse(&["flom", "--output", "gha", "https://youtu.be/a"]);
+        assert_eq!(cli.output_format(&config).unwrap(), OutputFormat::Gha);
+
+        config.output.format = Some("xml".to_string());
+        let cli = parse(&["flom", "--json", "https://youtu.be/a"]);
+        assert_eq!(cli.output_format(&config).unwrap(), OutputFormat::Json);
+        let cli = parse(&["flom", "https://youtu.be/a"]);
+        assert!(matches!(
+            cli.output_format(&config),
+            Err(FlomError::Config(_))
+        ));
+        assert!(Cli::try_parse_from(["flom", "--json", "--output", "human"]).is_err());
+    }
+
+    #[test]
+    fn isrc_counts_as_an_id_input() {
+        let cli = parse(&["flom", "--isrc", "USS1Z9900001", "--to", "spotify"]);
+        assert!(cli.has_id_input());
+        assert!(!parse(&["flom", "https://youtu.be/a"]).has_id_input());
+        assert!(
+            Cli::try_parse_from(["flom", "--isrc", "X", "--id", "1", "--platform", "spotify"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn repeated_targets_are_dropped() {
+        let result = |target: Option<&str>| ConversionResult {
+            target_url: target.map(str::to_string),
+            ..Default::default()
+        };
+        let mut seen = HashSet::new();
+        let mut first = vec![result(Some("https://a")), result(Some("https://b"))];
+        assert_eq!(drop_seen_targets(&mut first, &mut seen), 0);
+        let mut second = vec![result(Some("https://a")), result(None), result(None)];
+        assert_eq!(drop_seen_targets(&mut second, &mut seen), 1);
+        assert_eq!(second.len(), 2);
+    }
+
+    #[test]
+    fn concurrency_must_be_positive() {
+        let cli = parse(&["flom", "--concurrency", "16", "https://youtu.be/a"]);
+        assert_eq!(cli.concurrency.map(NonZeroUsize::get), Some(16));
+        assert!(Cli::try_parse_from(["flom", "--concurrency", "0", "https://youtu.be/a"]).is_err());
+    }
+
+    #[test]
+    fn ordered_rules_out_changing_the_record_count() {
+        assert!(parse(&["flom", "--ordered", "https://youtu.be/a"]).ordered);
+        for flag in ["--unique-targets", "--expand-tracks"] {
+            assert!(
+                Cli::try_parse_from(["flom", "--ordered", flag, "https://youtu.be/a"]).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn ordered_needs_a_single_target() {
+        assert!(single_target(None).is_ok());
+        assert!(single_target(Some("spotify")).is_ok());
+        assert!(single_target(Some("Spotify,spotify")).is_ok());
+        assert!(single_target(Some("spotify,tidal")).is_err());
+        assert!(single_target(Some("all")).is_err());
+    }
+
+    #[test]
+    fn strict_flag_reaches_the_converter_flags() {
+        assert!(
+            !parse(&["flom", "https://youtu.be/a"])
+                .converter_flags()
+                .strict
+        );
+        assert!(
+            parse(&["flom", "--strict", "https://youtu.be/a"])
+                .converter_flags()
+                .strict
+        );
+    }
+
+    #[test]
+    fn cache_flags_pick_the_cache_mode() {
+        let mode = |args: &[&str]| parse(args).converter_flags().cache;
+        assert_eq!(mode(&["flom", "https://youtu.be/a"]), CacheMode::Use);
+        assert_eq!(
+            mode(&["flom", "--refresh", "https://youtu.be/a"]),
+            CacheMode::Refresh
+        );
+        assert_eq!(
+            mode(&["flom", "--no-cache", "https://youtu.be/a"]),
+            CacheMode::Off
+        );
+        assert_eq!(
+            mode(&["flom", "--offline", "https://youtu.be/a"]),
+            CacheMode::Offline
+        );
+        assert!(
+            Cli::try_parse_from(["flom", "--no-cache", "--refresh", "https://youtu.be/a"]).is_err()
+        );
+        assert!(
+            Cli::try_parse_from(["flom", "--offline", "--refresh", "https://youtu.be/a"]).is_err()
+        );
+    }
+
+    #[test]
+    fn sizes_use_binary_units() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+    }
 }
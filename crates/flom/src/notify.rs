@@ -0,0 +1,49 @@
+use std::process::Command;
+
+use flom_core::{FlomError, FlomResult};
+
+/// Shows a desktop notification using the platform's own tooling.
+pub fn desktop(title: &str, body: &str) -> FlomResult<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(body),
+            applescript_string(title)
+        );
+        let mut command = Command::new("osascript");
+        command.args(["-e", &script]);
+        command
+    } else if cfg!(unix) {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name=flom", title, body]);
+        command
+    } else {
+        return Err(FlomError::UnsupportedInput(
+            "desktop notifications are not supported on this platform".to_string(),
+        ));
+    };
+    let status = command
+        .status()
+        .map_err(|err| FlomError::Config(format!("failed to run notifier: {err}")))?;
+    if !status.success() {
+        return Err(FlomError::Config(format!("notifier exited with {status}")));
+    }
+    Ok(())
+}
+
+fn applescript_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applescript_string_escapes_quotes() {
+        assert_eq!(
+            applescript_string(r#"say "hi" \o/"#),
+            r#""say \"hi\" \\o/""#
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use flom_core::{ConversionResult, FlomError, FlomResult};
+
+use crate::template;
+
+pub const DEFAULT_TEMPLATE: &str = "{artist}-{title}.json";
+
+/// Writes `result` as JSON to `dir` under a name rendered from `name_template`.
+/// Existing files are never overwritten; a `-2`, `-3`, ... suffix is added instead.
+pub fn write_result(
+    dir: &Path,
+    name_template: &str,
+    result: &ConversionResult,
+) -> FlomResult<PathBuf> {
+    let name = template::render(name_template, result, sanitize_component)?;
+    let path = unique_path(&dir.join(name));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| FlomError::Config(format!("failed to create output dir: {err}")))?;
+    }
+    let content = serde_json::to_string_pretty(result)
+        .map_err(|err| FlomError::Parse(format!("failed to serialize result: {err}")))?;
+    fs::write(&path, content)
+        .map_err(|err| FlomError::Config(format!("failed to write {}: {err}", path.display())))?;
+    Ok(path)
+}
+
+/// Makes a field value safe to use as (part of) a file name.
+pub fn sanitize_component(value: &str) -> String {
+    let cleaned: String = value
+        .chars()
+        .map(|ch| match ch {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            ch if ch.is_control() => '_',
+            ch => ch,
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_matches('.');
+    if cleaned.is_empty() {
+        "unknown".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+fn unique_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    (2..)
+        .map(|index| path.with_file_name(format!("{stem}-{index}{extension}")))
+        .find(|candidate| !candidate.exists())
+        .expect("unbounded range always yields a free name")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flom_core::MediaInfo;
+
+    #[test]
+    fn write_result_sanitizes_and_avoids_collisions() {
+        let dir = std::env::temp_dir().join("flom-test-outfile");
+        let _ = fs::remove_dir_all(&dir);
+        let result = ConversionResult {
+            source_url: "https://open.spotify.com/track/abc".to_string(),
+            source_info: Some(MediaInfo {
+                title: Some("AC/DC: Live?".to_string()),
+                artist: None,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let first = write_result(&dir, DEFAULT_TEMPLATE, &result).unwrap();
+        let second = write_result(&dir, DEFAULT_TEMPLATE, &result).unwrap();
+        assert_eq!(first, dir.join("unknown-AC_DC_ Live_.json"));
+        assert_eq!(second, dir.join("unknown-AC_DC_ Live_-2.json"));
+        assert!(
+            fs::read_to_string(&second)
+                .unwrap()
+                .contains("open.spotify.com")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,629 @@
+//! Rendering of conversion results for stdout, kept free of I/O so every
+//! format can be checked against snapshots.
+
+use flom_core::{ConversionResult, FlomError, FlomResult, MediaInfo, MediaType};
+use serde::Serialize;
+
+use crate::ui::{icons_enabled, link, style, terminal_width};
+use crate::{OutputFormat, SortOrder};
+use crate::{gha, template};
+
+/// Header row for [`OutputFormat::Csv`], printed once before the first result.
+pub const CSV_HEADER: &str = "source_url,target_url,source_platform,target_platform,title,artist,album,duration_ms,isrc,release_date,artwork_url\n";
+
+/// Width assumed for the platform table when stdout is not a terminal.
+const DEFAULT_WIDTH: usize = 80;
+
+/// How much of a result the human format shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout<'a> {
+    Full,
+    /// Only the target URL.
+    Simple,
+    /// One line rendered from a `--template` such as `{title} — {artist}: {target_url}`.
+    Template(&'a str),
+}
+
+impl<'a> Layout<'a> {
+    /// A template wins over simple mode.
+    pub fn new(simple: bool, template: Option<&'a str>) -> Self {
+        match template {
+            Some(template) => Layout::Template(template),
+            None if simple => Layout::Simple,
+            None => Layout::Full,
+        }
+    }
+}
+
+/// Renders `result` exactly as it is printed, including the trailing newline.
+pub fn render_result(
+    result: &ConversionResult,
+    layout: Layout<'_>,
+    format: OutputFormat,
+) -> FlomResult<String> {
+    match format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string(result)
+                .map_err(|err| FlomError::Parse(format!("failed to serialize result: {err}")))?;
+            return Ok(format!("{json}\n"));
+        }
+        OutputFormat::Gha => return Ok(format!("{}\n", gha::result_command(result, None))),
+        OutputFormat::Csv => return Ok(csv_row(result)),
+        OutputFormat::Markdown => return Ok(format!("{}\n", markdown_item(result))),
+        OutputFormat::Human => {}
+    }
+
+    match layout {
+        Layout::Simple => {
+            return Ok(result
+                .target_url
+                .as_ref()
+                .map(|url| format!("{url}\n"))
+                .unwrap_or_default());
+        }
+        Layout::Template(line) => {
+            return Ok(format!(
+                "{}\n",
+                template::render(line, result, str::to_string)?
+            ));
+        }
+        Layout::Full => {}
+    }
+
+    let mut out = source_block(result);
+    match &result.target_url {
+        Some(target_url) => out.push_str(&format!(
+            "{} {}{}\n",
+            style("To:").green(),
+            icon_prefix(result.target_platform.as_deref(), icons_enabled()),
+            link(target_url)
+        )),
+        None => out.push_str(&format!("{} (no target url)\n", style("To:").red())),
+    }
+    if let Some(warning) = &result.warning {
+        out.push_str(&format!("{} {warning}\n", style("Warning:").yellow()));
+    }
+    out.push('\n');
+    Ok(out)
+}
+
+/// Renders all results of one input. Several results (e.g. `--to all`) share
+/// one source: they become a single nested JSON object, and one table in
+/// Markdown and in the full human layout. Everything else renders each result
+/// on its own.
+pub fn render_results(
+    results: &[ConversionResult],
+    layout: Layout<'_>,
+    format: OutputFormat,
+) -> FlomResult<String> {
+    if results.len() > 1 {
+        match (format, layout) {
+            (OutputFormat::Json, _) => return json_group(results),
+            (OutputFormat::Markdown, _) => return Ok(markdown_table(results)),
+            (OutputFormat::Human, Layout::Full) => {
+                let width = terminal_width().unwrap_or(DEFAULT_WIDTH);
+                return Ok(platform_table(results, width, icons_enabled()));
+            }
+            _ => {}
+        }
+    }
+    results
+        .iter()
+        .map(|result| render_result(result, layout, format))
+        .collect()
+}
+
+/// Renders the results of one input link under `--ordered`: as
+/// [`render_results`] does, but a simple line stays even without a target URL
+/// so every input link keeps a record.
+pub fn render_ordered(
+    results: &[ConversionResult],
+    layout: Layout<'_>,
+    format: OutputFormat,
+) -> FlomResult<String> {
+    let rendered = render_results(results, layout, format)?;
+    Ok(if rendered.is_empty() {
+        "\n".to_string()
+    } else {
+        rendered
+    })
+}
+
+/// The JSON line standing in for a link that failed.
+#[derive(Serialize)]
+struct FailureLine<'a> {
+    source_url: &'a str,
+    error: String,
+}
+
+/// Renders the record `--ordered` prints in place of a link that failed, so
+/// the output keeps one record per input link: a blank line for one-line
+/// layouts, and a record without a target otherwise.
+pub fn render_failure(
+    url: &str,
+    err: &FlomError,
+    layout: Layout<'_>,
+    format: OutputFormat,
+) -> FlomResult<String> {
+    match (format, layout) {
+        (OutputFormat::Json, _) => {
+            let line = FailureLine {
+                source_url: url,
+                error: err.to_string(),
+            };
+            let json = serde_json::to_string(&line)
+                .map_err(|err| FlomError::Parse(format!("failed to serialize result: {err}")))?;
+            Ok(format!("{json}\n"))
+        }
+        (OutputFormat::Human, Layout::Simple | Layout::Template(_)) => Ok("\n".to_string()),
+        _ => render_result(
+            &ConversionResult {
+                source_url: url.to_string(),
+                ..Default::default()
+            },
+            layout,
+            format,
+        ),
+    }
+}
+
+/// The JSON line for several results of one input: the source once, and each
+/// target under `results`.
+#[derive(Serialize)]
+struct ResultGroup<'a> {
+    source_url: &'a str,
+    source_platform: Option<&'a str>,
+    source_id: Option<&'a str>,
+    source_info: Option<&'a MediaInfo>,
+    page_url: Option<&'a str>,
+    media_type: Option<MediaType>,
+    results: Vec<TargetEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct TargetEntry<'a> {
+    target_url: Option<&'a str>,
+    target_platform: Option<&'a str>,
+    target_id: Option<&'a str>,
+    target_info: Option<&'a MediaInfo>,
+    warning: Option<&'a str>,
+}
+
+fn json_group(results: &[ConversionResult]) -> FlomResult<String> {
+    let source = &results[0];
+    let group = ResultGroup {
+        source_url: &source.source_url,
+        source_platform: source.source_platform.as_deref(),
+        source_id: source.source_id.as_deref(),
+        source_info: source.source_info.as_ref(),
+        page_url: source.page_url.as_deref(),
+        media_type: source.media_type,
+        results: results
+            .iter()
+            .map(|result| TargetEntry {
+                target_url: result.target_url.as_deref(),
+                target_platform: result.target_platform.as_deref(),
+                target_id: result.target_id.as_deref(),
+                target_info: result.target_info.as_ref(),
+                warning: result.warning.as_deref(),
+            })
+            .collect(),
+    };
+    let json = serde_json::to_string(&group)
+        .map_err(|err| FlomError::Parse(format!("failed to serialize results: {err}")))?;
+    Ok(format!("{json}\n"))
+}
+
+/// Orders the results of one input. Sorting is stable, so repeated runs over
+/// the same links print (and serialize) identically.
+pub fn sort_results(results: &mut [ConversionResult], order: SortOrder) {
+    match order {
+        SortOrder::Platform => results.sort_by(|a, b| a.target_platform.cmp(&b.target_platform)),
+        SortOrder::Title => results.sort_by_cached_key(|result| {
+            let title = result
+                .target_info
+                .as_ref()
+                .and_then(|info| info.title.as_deref())
+                .map(str::to_lowercase);
+            (title.is_none(), title, result.target_platform.clone())
+        }),
+        SortOrder::None => {}
+    }
+}
+
+/// Title, artist and album come from the target entity, falling back to the source.
+fn csv_row(result: &ConversionResult) -> String {
+    let info = |field: fn(&flom_core::MediaInfo) -> Option<&String>| {
+        [&result.target_info, &result.source_info]
+            .into_iter()
+            .flatten()
+            .find_map(field)
+            .map_or("", String::as_str)
+    };
+    let duration = [&result.target_info, &result.source_info]
+        .into_iter()
+        .flatten()
+        .find_map(|info| info.duration_ms)
+        .map(|ms| ms.to_string())
+        .unwrap_or_default();
+    let row = [
+        result.source_url.as_str(),
+        result.target_url.as_deref().unwrap_or(""),
+        result.source_platform.as_deref().unwrap_or(""),
+        result.target_platform.as_deref().unwrap_or(""),
+        info(|info| info.title.as_ref()),
+        info(|info| info.artist.as_ref()),
+        info(|info| info.album.as_ref()),
+        duration.as_str(),
+        info(|info| info.isrc.as_ref()),
+        info(|info| info.release_date.as_ref()),
+        info(|info| info.artwork_url.as_ref()),
+    ]
+    .map(csv_field)
+    .join(",");
+    format!("{row}\n")
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// "Title – Artist" as far as known, else the source URL.
+fn markdown_name(result: &ConversionResult) -> String {
+    let info = result.source_info.as_ref();
+    let name = match (
+        info.and_then(|info| info.title.as_deref()),
+        info.and_then(|info| info.artist.as_deref()),
+    ) {
+        (Some(title), Some(artist)) => format!("{title} – {artist}"),
+        (Some(title), None) => title.to_string(),
+        _ => result.source_url.clone(),
+    };
+    markdown_escape(&name)
+}
+
+fn markdown_item(result: &ConversionResult) -> String {
+    let name = markdown_name(result);
+    let duration = result
+        .source_info
+        .as_ref()
+        .and_then(MediaInfo::duration_label)
+        .map(|duration| format!(" ({duration})"))
+        .unwrap_or_default();
+    match &result.target_url {
+        Some(url) => format!("- [{name}]({}){duration}", markdown_url(url)),
+        None => format!("- {name}: no target link{duration}"),
+    }
+}
+
+fn markdown_table(results: &[ConversionResult]) -> String {
+    let mut out = format!(
+        "**{}**\n\n| Platform | Link |\n| --- | --- |\n",
+        markdown_name(&results[0])
+    );
+    for result in results {
+        let platform = markdown_escape(result.target_platform.as_deref().unwrap_or("unknown"));
+        let link = match &result.target_url {
+            Some(url) => format!("[{}]({})", markdown_escape(url), markdown_url(url)),
+            None => "no target link".to_string(),
+        };
+        out.push_str(&format!("| {platform} | {link} |\n"));
+    }
+    out.push('\n');
+    out
+}
+
+/// Escapes what would end a link text or table cell.
+fn markdown_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '\\' | '[' | ']' | '|') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+fn markdown_url(url: &str) -> String {
+    url.replace(' ', "%20").replace(')', "%29")
+}
+
+/// Icon for a platform key, shown before it when `output.icons` is on.
+fn platform_icon(platform: &str) -> Option<&'static str> {
+    let icon = match platform.to_ascii_lowercase().as_str() {
+        "spotify" => "🎧",
+        "applemusic" | "itunes" => "🍎",
+        "youtube" => "▶️",
+        "youtubemusic" => "🎵",
+        "deezer" => "🎶",
+        "tidal" => "🌊",
+        "amazonmusic" | "amazonstore" => "📦",
+        "soundcloud" => "☁️",
+        "songlink" => "🔗",
+        _ => return None,
+    };
+    Some(icon)
+}
+
+fn icon_prefix(platform: Option<&str>, enabled: bool) -> String {
+    platform
+        .filter(|_| enabled)
+        .and_then(platform_icon)
+        .map(|icon| format!("{icon} "))
+        .unwrap_or_default()
+}
+
+fn source_block(result: &ConversionResult) -> String {
+    format!(
+        "{} {}\n  {} {}\n",
+        style("From:").cyan(),
+        format_source_line(result),
+        style("URL:").dim(),
+        link(&result.source_url)
+    )
+}
+
+/// The source once, then one aligned `platform  url` row per result. A URL
+/// that would overflow `width` moves to its own line instead of being cut,
+/// so it stays copyable.
+fn platform_table(results: &[ConversionResult], width: usize, icons: bool) -> String {
+    const INDENT: &str = "  ";
+    const GAP: &str = "  ";
+    let cells: Vec<(String, usize)> = results
+        .iter()
+        .map(|result| platform_cell(result.target_platform.as_deref(), icons))
+        .collect();
+    let column = cells
+        .iter()
+        .map(|(_, cell_width)| *cell_width)
+        .max()
+        .unwrap_or(0);
+
+    let mut out = source_block(&results[0]);
+    for (result, (cell, cell_width)) in results.iter().zip(&cells) {
+        let padding = " ".repeat(column - cell_width);
+        out.push_str(&format!("{INDENT}{}", style(cell).green()));
+        match &result.target_url {
+            Some(url) if INDENT.len() + column + GAP.len() + url.chars().count() <= width => {
+                out.push_str(&format!("{padding}{GAP}{}", link(url)));
+            }
+            Some(url) => out.push_str(&format!("\n{INDENT}{INDENT}{}", link(url))),
+            None => out.push_str(&format!("{padding}{GAP}{}", style("(no target url)").red())),
+        }
+        out.push('\n');
+        if let Some(warning) = &result.warning {
+            out.push_str(&format!(
+                "{INDENT}{INDENT}{} {warning}\n",
+                style("Warning:").yellow()
+            ));
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// A platform label with its icon, and the columns it takes up (icons are
+/// double width).
+fn platform_cell(platform: Option<&str>, icons: bool) -> (String, usize) {
+    let name = platform.unwrap_or("unknown");
+    let icon = icon_prefix(platform, icons);
+    let width = name.chars().count() + if icon.is_empty() { 0 } else { 3 };
+    (format!("{icon}{name}"), width)
+}
+
+fn format_source_line(result: &ConversionResult) -> String {
+    let platform = format!(
+        "{}{}",
+        icon_prefix(result.source_platform.as_deref(), icons_enabled()),
+        result.source_platform.as_deref().unwrap_or("Unknown")
+    );
+    // Songs are the norm; anything else is labelled.
+    let platform = match result.media_type {
+        Some(kind) if kind != MediaType::Song => format!("{platform} {}", kind.as_str()),
+        _ => platform,
+    };
+    if let Some(info) = &result.source_info {
+        let title = info.title.as_deref().unwrap_or("Unknown title");
+        let artist = info.artist.as_deref().unwrap_or("Unknown artist");
+        return match info.duration_label() {
+            Some(duration) => format!("{platform} - {title} / {artist} ({duration})"),
+            None => format!("{platform} - {title} / {artist}"),
+        };
+    }
+    platform.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn render_all(layout: Layout, format: OutputFormat) -> String {
+        fixtures::plain_styles();
+        fixtures::results()
+            .iter()
+            .map(|result| render_result(result, layout, format).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn sorts_by_platform_or_title() {
+        let result = |platform: &str, title: Option<&str>| ConversionResult {
+            target_platform: Some(platform.to_string()),
+            target_info: title.map(|title| flom_core::MediaInfo {
+                title: Some(title.to_string()),
+                artist: None,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let platforms = |results: &[ConversionResult]| {
+            results
+                .iter()
+                .map(|result| result.target_platform.clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+        let mut results = vec![
+            result("tidal", Some("beta")),
+            result("deezer", None),
+            result("spotify", Some("Alpha")),
+            result("appleMusic", Some("beta")),
+        ];
+
+        sort_results(&mut results, SortOrder::None);
+        assert_eq!(
+            platforms(&results),
+            ["tidal", "deezer", "spotify", "appleMusic"]
+        );
+        sort_results(&mut results, SortOrder::Platform);
+        assert_eq!(
+            platforms(&results),
+            ["appleMusic", "deezer", "spotify", "tidal"]
+        );
+        sort_results(&mut results, SortOrder::Title);
+        assert_eq!(
+            platforms(&results),
+            ["spotify", "appleMusic", "tidal", "deezer"]
+        );
+    }
+
+    #[test]
+    fn failures_keep_one_record_per_link() {
+        fixtures::plain_styles();
+        let err = FlomError::Network("timeout".to_string());
+        let url = "https://open.spotify.com/track/abc";
+        let render = |layout, format| render_failure(url, &err, layout, format).unwrap();
+
+        assert_eq!(render(Layout::Simple, OutputFormat::Human), "\n");
+        assert_eq!(
+            render(Layout::Template("{title}"), OutputFormat::Human),
+            "\n"
+        );
+        assert_eq!(
+            render(Layout::Simple, OutputFormat::Json),
+            "{\"source_url\":\"https://open.spotify.com/track/abc\",\"error\":\"network error: timeout\"}\n"
+        );
+        let row = render(Layout::Simple, OutputFormat::Csv);
+        assert!(row.starts_with("https://open.spotify.com/track/abc,,"));
+        assert_eq!(row.lines().count(), 1);
+    }
+
+    #[test]
+    fn ordered_output_has_one_record_per_input() {
+        fixtures::plain_styles();
+        let err = FlomError::Network("timeout".to_string());
+        // Each fixture stands for one input link, one of them without a
+        // target URL; the last input failed.
+        let inputs = fixtures::results();
+        for format in [OutputFormat::Human, OutputFormat::Json, OutputFormat::Csv] {
+            let mut out: String = inputs
+                .iter()
+                .map(|result| {
+                    render_ordered(std::slice::from_ref(result), Layout::Simple, format).unwrap()
+                })
+                .collect();
+            out.push_str(
+                &render_failure("https://youtu.be/x", &err, Layout::Simple, format).unwrap(),
+            );
+            assert_eq!(out.lines().count(), inputs.len() + 1, "{format:?}");
+        }
+    }
+
+    #[test]
+    fn human_snapshot() {
+        insta::assert_snapshot!(render_all(Layout::Full, OutputFormat::Human));
+    }
+
+    #[test]
+    fn simple_snapshot() {
+        insta::assert_snapshot!(render_all(Layout::Simple, OutputFormat::Human));
+    }
+
+    #[test]
+    fn template_snapshot() {
+        insta::assert_snapshot!(render_all(
+            Layout::Template("{title} — {artist}: {target_url}"),
+            OutputFormat::Human
+        ));
+    }
+
+    #[test]
+    fn platform_table_snapshot() {
+        fixtures::plain_styles();
+        let mut results = fixtures::results();
+        let source = results[0].clone();
+        for result in &mut results {
+            result.source_url = source.source_url.clone();
+            result.source_platform = source.source_platform.clone();
+            result.source_info = source.source_info.clone();
+        }
+        let wide = platform_table(&results, 80, false);
+        let narrow = platform_table(&results, 40, true);
+        insta::assert_snapshot!(format!("{wide}{narrow}"));
+    }
+
+    #[test]
+    fn json_groups_results_of_one_input() {
+        let results = fixtures::results();
+        let single = render_results(&results[..1], Layout::Full, OutputFormat::Json).unwrap();
+        assert_eq!(
+            single,
+            render_result(&results[0], Layout::Full, OutputFormat::Json).unwrap()
+        );
+
+        let grouped = render_results(&results[..2], Layout::Full, OutputFormat::Json).unwrap();
+        assert_eq!(grouped.lines().count(), 1);
+        let value: serde_json::Value = serde_json::from_str(&grouped).unwrap();
+        assert_eq!(value["source_url"], results[0].source_url.as_str());
+        assert_eq!(value["source_info"]["artist"], "Rick Astley");
+        assert_eq!(
+            value["page_url"],
+            "https://song.link/s/4cOdK2wGLETKBW3PvgPWqT"
+        );
+        assert_eq!(value["results"][0]["target_platform"], "appleMusic");
+        assert_eq!(value["results"][1]["target_url"], serde_json::Value::Null);
+        assert_eq!(value["results"][1]["warning"], "matched by title only");
+        assert!(value.get("target_url").is_none());
+    }
+
+    #[test]
+    fn icons_only_when_enabled_and_known() {
+        assert_eq!(icon_prefix(Some("spotify"), true), "🎧 ");
+        assert_eq!(icon_prefix(Some("appleMusic"), true), "🍎 ");
+        assert_eq!(icon_prefix(Some("spotify"), false), "");
+        assert_eq!(icon_prefix(Some("napster"), true), "");
+        assert_eq!(icon_prefix(None, true), "");
+    }
+
+    #[test]
+    fn markdown_snapshot() {
+        fixtures::plain_styles();
+        let results = fixtures::results();
+        let items = render_all(Layout::Full, OutputFormat::Markdown);
+        let table = render_results(&results[..2], Layout::Full, OutputFormat::Markdown).unwrap();
+        insta::assert_snapshot!(format!("{items}\n{table}"));
+    }
+
+    #[test]
+    fn json_snapshot() {
+        insta::assert_snapshot!(render_all(Layout::Full, OutputFormat::Json));
+    }
+
+    #[test]
+    fn gha_snapshot() {
+        insta::assert_snapshot!(render_all(Layout::Full, OutputFormat::Gha));
+    }
+
+    #[test]
+    fn csv_snapshot() {
+        insta::assert_snapshot!(format!(
+            "{CSV_HEADER}{}",
+            render_all(Layout::Full, OutputFormat::Csv)
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+use flom_core::FlomResult;
+use flom_music::MusicConverter;
+use flom_music::api::odesli::OdesliResponse;
+
+use crate::template;
+
+pub const DEFAULT_TEMPLATE: &str = "🎵 {title} – {artist}
+Spotify: {link:spotify}
+Apple Music: {link:appleMusic}
+YouTube: {link:youtube}";
+
+/// Renders a share message for `response`. Besides `title`, `artist`, `album` and
+/// `page_url`, `{link:<platform>}` inserts the link for that platform; lines whose
+/// platform has no link are left out.
+pub fn render(template: &str, response: &OdesliResponse) -> FlomResult<String> {
+    let entity = response
+        .entities_by_unique_id
+        .get(&response.entity_unique_id)
+        .or_else(|| response.entities_by_unique_id.values().next());
+
+    let mut lines = Vec::new();
+    for line in template.lines() {
+        let mut missing_link = false;
+        let rendered = template::render_with(line, |name| {
+            if let Some(platform) = name.strip_prefix("link:") {
+                let key = MusicConverter::normalize_target(platform)
+                    .unwrap_or_else(|| platform.to_string());
+                let link = response.links_by_platform.get(&key);
+                missing_link |= link.is_none();
+                return Some(link.map(|link| link.url.clone()).unwrap_or_default());
+            }
+            let value = match name {
+                "title" => entity.and_then(|entity| entity.title.clone()),
+                "artist" => entity.and_then(|entity| entity.artist_name.clone()),
+                "album" => entity.and_then(|entity| entity.album_name.clone()),
+                "page_url" => Some(response.page_url.clone()),
+                _ => return None,
+            };
+            Some(value.unwrap_or_default())
+        })?;
+        if !missing_link {
+            lines.push(rendered);
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use flom_music::api::odesli::{OdesliEntity, OdesliLink};
+
+    use super::*;
+
+    #[test]
+    fn render_skips_lines_for_unavailable_platforms() {
+        let mut response = OdesliResponse {
+            entity_unique_id: "SPOTIFY_SONG::abc".to_string(),
+            page_url: "https://song.link/s/abc".to_string(),
+            links_by_platform: HashMap::new(),
+            entities_by_unique_id: HashMap::new(),
+        };
+        response.entities_by_unique_id.insert(
+            "SPOTIFY_SONG::abc".to_string(),
+            OdesliEntity {
+                id: Some("abc".to_string()),
+                title: Some("Song".to_string()),
+                artist_name: Some("Artist".to_string()),
+                api_provider: Some("spotify".to_string()),
+                ..Default::default()
+            },
+        );
+        for (key, url) in [
+            ("spotify", "https://open.spotify.com/track/abc"),
+            ("appleMusic", "https://music.apple.com/x"),
+        ] {
+            response.links_by_platform.insert(
+                key.to_string(),
+                OdesliLink {
+                    entity_unique_id: "SPOTIFY_SONG::abc".to_string(),
+                    url: url.to_string(),
+                },
+            );
+        }
+
+        let message = render(DEFAULT_TEMPLATE, &response).unwrap();
+        assert_eq!(
+            message,
+            "🎵 Song – Artist\nSpotify: https://open.spotify.com/track/abc\nApple Music: https://music.apple.com/x"
+        );
+
+        let message = render("{title}: {link:apple-music} ({page_url})", &response).unwrap();
+        assert_eq!(
+            message,
+            "Song: https://music.apple.com/x (https://song.link/s/abc)"
+        );
+    }
+}
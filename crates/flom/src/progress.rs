@@ -0,0 +1,138 @@
+//! The progress line a large batch keeps on stderr, so long playlists don't
+//! look frozen.
+
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// Smaller batches finish before a progress line would tell anyone much.
+pub const MIN_INPUTS: usize = 20;
+
+const BAR_WIDTH: usize = 24;
+
+pub struct Progress {
+    total: usize,
+    done: usize,
+    failed: usize,
+    started: Instant,
+    drawn: bool,
+}
+
+impl Progress {
+    /// A progress line for a batch of `total` links, when the size is known,
+    /// reaches [`MIN_INPUTS`] and stderr is a terminal.
+    pub fn start(total: Option<usize>) -> Option<Self> {
+        let total = total.filter(|total| *total >= MIN_INPUTS)?;
+        if !io::stderr().is_terminal() {
+            return None;
+        }
+        let mut progress = Self {
+            total,
+            done: 0,
+            failed: 0,
+            started: Instant::now(),
+            drawn: false,
+        };
+        progress.draw();
+        Some(progress)
+    }
+
+    /// Counts `more` links found on the way, e.g. the tracks of an album.
+    pub fn grow(&mut self, more: usize) {
+        self.total += more;
+    }
+
+    /// Counts a finished link and redraws the line.
+    pub fn finish_one(&mut self, failed: bool) {
+        self.done += 1;
+        if failed {
+            self.failed += 1;
+        }
+        self.draw();
+    }
+
+    /// Erases the line, so other output doesn't run into it. The next
+    /// [`Progress::finish_one`] draws it again.
+    pub fn clear(&mut self) {
+        if self.drawn {
+            eprint!("\r\x1b[2K");
+            let _ = io::stderr().flush();
+            self.drawn = false;
+        }
+    }
+
+    fn draw(&mut self) {
+        eprint!("\r\x1b[2K{}", self.line(self.started.elapsed()));
+        let _ = io::stderr().flush();
+        self.drawn = true;
+    }
+
+    fn line(&self, elapsed: Duration) -> String {
+        let total = self.total.max(1);
+        let filled = (self.done * BAR_WIDTH / total).min(BAR_WIDTH);
+        let mut line = format!(
+            "[{}{}] {}/{}",
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            self.done,
+            self.total
+        );
+        if self.failed > 0 {
+            line.push_str(&format!("  {} failed", self.failed));
+        }
+        // The pace so far, applied to what is left.
+        if self.done > 0 && self.done < self.total {
+            let left = elapsed.mul_f64((self.total - self.done) as f64 / self.done as f64);
+            line.push_str(&format!("  ETA {}", clock(left)));
+        }
+        line
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// `m:ss`, or `h:mm:ss` past an hour.
+fn clock(duration: Duration) -> String {
+    let total = duration.as_secs();
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_shows_count_failures_and_eta() {
+        let mut progress = Progress {
+            total: 100,
+            done: 0,
+            failed: 0,
+            started: Instant::now(),
+            drawn: false,
+        };
+        assert_eq!(
+            progress.line(Duration::ZERO),
+            "[------------------------] 0/100"
+        );
+        progress.done = 25;
+        progress.failed = 2;
+        assert_eq!(
+            progress.line(Duration::from_secs(30)),
+            "[######------------------] 25/100  2 failed  ETA 1:30"
+        );
+        progress.done = 100;
+        assert_eq!(
+            progress.line(Duration::from_secs(4000)),
+            "[########################] 100/100  2 failed"
+        );
+        assert_eq!(clock(Duration::from_secs(3725)), "1:02:05");
+    }
+}
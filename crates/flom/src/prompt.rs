@@ -0,0 +1,103 @@
+//! Interactive prompts. Builds without the `interactive` feature get stubs
+//! that fail with a hint to pass the value on the command line instead.
+
+use flom_core::{FlomError, FlomResult};
+
+#[cfg(feature = "interactive")]
+mod imp {
+    use dialoguer::{
+        Confirm, FuzzySelect, Input, MultiSelect, Password, Select, theme::ColorfulTheme,
+    };
+
+    use super::*;
+
+    pub fn text(prompt: &str) -> FlomResult<String> {
+        Input::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .allow_empty(true)
+            .interact_text()
+            .map_err(|err| FlomError::InvalidInput(format!("input prompt failed: {err}")))
+    }
+
+    pub fn confirm(prompt: &str, default: bool) -> FlomResult<bool> {
+        Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .default(default)
+            .interact()
+            .map_err(|err| FlomError::InvalidInput(format!("confirmation failed: {err}")))
+    }
+
+    pub fn password(prompt: &str, confirm: bool) -> FlomResult<String> {
+        let theme = ColorfulTheme::default();
+        let mut password = Password::with_theme(&theme).with_prompt(prompt);
+        if confirm {
+            password = password.with_confirmation("Confirm passphrase", "Passphrases do not match");
+        }
+        password
+            .interact()
+            .map_err(|err| FlomError::InvalidInput(format!("passphrase prompt failed: {err}")))
+    }
+
+    pub fn select(prompt: &str, items: &[String]) -> FlomResult<usize> {
+        Select::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .items(items)
+            .default(0)
+            .interact()
+            .map_err(|err| FlomError::InvalidInput(format!("selection failed: {err}")))
+    }
+
+    pub fn multi_select(prompt: &str, items: &[String]) -> FlomResult<Vec<usize>> {
+        MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .items(items)
+            .interact()
+            .map_err(|err| FlomError::InvalidInput(format!("selection failed: {err}")))
+    }
+
+    pub fn fuzzy_select(prompt: &str, items: &[String]) -> FlomResult<Option<usize>> {
+        FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .items(items)
+            .default(0)
+            .interact_opt()
+            .map_err(|err| FlomError::InvalidInput(format!("selection failed: {err}")))
+    }
+}
+
+#[cfg(not(feature = "interactive"))]
+mod imp {
+    use super::*;
+
+    fn unavailable(prompt: &str) -> FlomError {
+        FlomError::InvalidInput(format!(
+            "{prompt}: interactive prompts are disabled in this build; pass the value as an option"
+        ))
+    }
+
+    pub fn text(prompt: &str) -> FlomResult<String> {
+        Err(unavailable(prompt))
+    }
+
+    pub fn confirm(prompt: &str, _default: bool) -> FlomResult<bool> {
+        Err(unavailable(prompt))
+    }
+
+    pub fn password(prompt: &str, _confirm: bool) -> FlomResult<String> {
+        Err(unavailable(prompt))
+    }
+
+    pub fn select(prompt: &str, _items: &[String]) -> FlomResult<usize> {
+        Err(unavailable(prompt))
+    }
+
+    pub fn multi_select(prompt: &str, _items: &[String]) -> FlomResult<Vec<usize>> {
+        Err(unavailable(prompt))
+    }
+
+    pub fn fuzzy_select(prompt: &str, _items: &[String]) -> FlomResult<Option<usize>> {
+        Err(unavailable(prompt))
+    }
+}
+
+pub use imp::{confirm, fuzzy_select, multi_select, password, select, text};
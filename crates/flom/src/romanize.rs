@@ -0,0 +1,47 @@
+use any_ascii::any_ascii;
+use flom_core::{ConversionResult, MediaInfo};
+
+/// Transliterates title, artist and album of both sides to ASCII.
+pub fn romanize_result(result: &mut ConversionResult) {
+    for info in [&mut result.source_info, &mut result.target_info]
+        .into_iter()
+        .flatten()
+    {
+        romanize_info(info);
+    }
+}
+
+fn romanize_info(info: &mut MediaInfo) {
+    for field in [&mut info.title, &mut info.artist, &mut info.album]
+        .into_iter()
+        .flatten()
+    {
+        if !field.is_ascii() {
+            *field = any_ascii(field).trim().to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn romanizes_cjk_and_keeps_ascii() {
+        let mut result = ConversionResult {
+            source_info: Some(MediaInfo {
+                title: Some("夜に駆ける".to_string()),
+                artist: Some("YOASOBI".to_string()),
+                album: Some("Café".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        romanize_result(&mut result);
+        let info = result.source_info.unwrap();
+        assert!(info.title.as_deref().unwrap().is_ascii());
+        assert!(!info.title.as_deref().unwrap().is_empty());
+        assert_eq!(info.artist.as_deref(), Some("YOASOBI"));
+        assert_eq!(info.album.as_deref(), Some("Cafe"));
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use flom_core::{FlomError, FlomResult};
+use tokio::sync::oneshot;
+
+/// Jobs allowed to run against one host at a time.
+pub const DEFAULT_HOST_LIMIT: usize = 4;
+
+static HOST_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_HOST_LIMIT);
+
+/// Sets the per-host limit of schedulers created from now on with `default()`.
+pub fn set_host_limit(limit: usize) {
+    HOST_LIMIT.store(limit.max(1), Ordering::Relaxed);
+}
+
+pub fn host_limit() -> usize {
+    HOST_LIMIT.load(Ordering::Relaxed)
+}
+
+/// Lower values run first; a user waiting on a reply beats a bulk batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Interactive,
+    Batch,
+    Background,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Attempts after the first one.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for each further one.
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay.saturating_mul(1 << attempt.min(16))
+    }
+}
+
+/// Outcome of a scheduled job and how many times it was tried.
+#[derive(Debug)]
+pub struct Attempted<T> {
+    pub result: FlomResult<T>,
+    pub attempts: u32,
+}
+
+/// Runs network jobs with per-host concurrency limits, serving waiting jobs
+/// by priority and re-queueing transient failures with exponential backoff.
+///
+/// Cloning is cheap and clones share the same queues, so one scheduler can
+/// serve a long batch and interactive requests side by side.
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    state: Arc<Mutex<State>>,
+    host_limit: usize,
+    retry: RetryPolicy,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    in_flight: HashMap<String, usize>,
+    /// Waiting jobs per host, ordered by priority and then arrival.
+    waiting: HashMap<String, BTreeMap<(Priority, u64), oneshot::Sender<()>>>,
+    next_seq: u64,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new(host_limit(), RetryPolicy::default())
+    }
+}
+
+impl Scheduler {
+    pub fn new(host_limit: usize, retry: RetryPolicy) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State::default())),
+            host_limit: host_limit.max(1),
+            retry,
+        }
+    }
+
+    /// Runs `job` once a slot for `host` is free, quietly retrying transient
+    /// errors; only the last error is returned.
+    ///
+    /// The slot is released while a failed job waits for its retry, so the
+    /// backoff never blocks other work on the same host.
+    pub async fn run<T, F, Fut>(&self, priority: Priority, host: &str, mut job: F) -> Attempted<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = FlomResult<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let permit = self.acquire(priority, host).await;
+            let result = job().await;
+            drop(permit);
+            match result {
+                Err(err) if attempt < self.retry.max_retries && is_retryable(&err) => {
+                    tokio::time::sleep(self.retry.delay(attempt)).await;
+                    attempt += 1;
+                }
+                result => {
+                    return Attempted {
+                        result,
+                        attempts: attempt + 1,
+                    };
+                }
+            }
+        }
+    }
+
+    async fn acquire(&self, priority: Priority, host: &str) -> Permit {
+        let receiver = {
+            let mut state = self.lock();
+            let running = state.in_flight.get(host).copied().unwrap_or(0);
+            let queued = state
+                .waiting
+                .get(host)
+                .is_some_and(|queue| !queue.is_empty());
+            if running < self.host_limit && !queued {
+                *state.in_flight.entry(host.to_string()).or_default() += 1;
+                None
+            } else {
+                let (sender, receiver) = oneshot::channel();
+                let seq = state.next_seq;
+                state.next_seq += 1;
+                state
+                    .waiting
+                    .entry(host.to_string())
+                    .or_default()
+                    .insert((priority, seq), sender);
+                Some(receiver)
+            }
+        };
+        if let Some(receiver) = receiver {
+            // The slot is handed over by `release`, already counted as in flight.
+            let mut waiting = Waiting {
+                receiver: Some(receiver),
+                scheduler: self.clone(),
+                host: host.to_string(),
+            };
+            if let Some(receiver) = waiting.receiver.as_mut() {
+                let _ = receiver.await;
+            }
+            waiting.receiver = None;
+        }
+        Permit {
+            scheduler: self.clone(),
+            host: host.to_string(),
+        }
+    }
+
+    fn release(&self, host: &str) {
+        let mut state = self.lock();
+        if let Some(queue) = state.waiting.get_mut(host) {
+            while let Some((_, sender)) = queue.pop_first() {
+                // A dropped receiver means the waiting job was cancelled.
+                if sender.send(()).is_ok() {
+                    return;
+                }
+            }
+        }
+        if let Some(running) = state.in_flight.get_mut(host) {
+            *running = running.saturating_sub(1);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+struct Permit {
+    scheduler: Scheduler,
+    host: String,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.scheduler.release(&self.host);
+    }
+}
+
+/// Gives back a slot that was handed to a job cancelled before it noticed.
+struct Waiting {
+    receiver: Option<oneshot::Receiver<()>>,
+    scheduler: Scheduler,
+    host: String,
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        if let Some(mut receiver) = self.receiver.take() {
+            receiver.close();
+            if receiver.try_recv().is_ok() {
+                self.scheduler.release(&self.host);
+            }
+        }
+    }
+}
+
+/// Network failures, rate limits and server errors are worth another try.
+pub fn is_retryable(err: &FlomError) -> bool {
+    match err {
+        FlomError::Network(_) => true,
+        FlomError::ApiStatus { status, .. } => *status == 429 || (500..600).contains(status),
+        _ => false,
+    }
+}
+
+/// Host every link lookup goes to.
+const ODESLI_HOST: &str = "api.song.link";
+
+/// Scheduling key for looking up `url`. Links from every site are resolved
+/// through Odesli, so they share its limit rather than one per site.
+pub fn lookup_key(_url: &str) -> &'static str {
+    ODESLI_HOST
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn retries_transient_errors_only() {
+        let scheduler = Scheduler::new(
+            1,
+            RetryPolicy {
+                max_retries: 2,
+                base_delay: Duration::from_millis(1),
+            },
+        );
+        let calls = AtomicUsize::new(0);
+        let outcome = runtime().block_on(scheduler.run(Priority::Batch, "a", || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(FlomError::Network("reset".to_string())),
+                _ => Ok("done"),
+            }
+        }));
+        assert_eq!(outcome.result.unwrap(), "done");
+        assert_eq!(outcome.attempts, 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        calls.store(0, Ordering::SeqCst);
+        let outcome: Attempted<()> =
+            runtime().block_on(scheduler.run(Priority::Batch, "a", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(FlomError::InvalidInput("bad".to_string()))
+            }));
+        assert!(outcome.result.is_err());
+        assert_eq!(outcome.attempts, 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        calls.store(0, Ordering::SeqCst);
+        let outcome: Attempted<()> =
+            runtime().block_on(scheduler.run(Priority::Batch, "a", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(FlomError::Network("down".to_string()))
+            }));
+        assert!(matches!(outcome.result, Err(FlomError::Network(_))));
+        assert_eq!(outcome.attempts, 3);
+    }
+
+    #[test]
+    fn waiting_jobs_run_by_priority() {
+        let scheduler = Scheduler::new(1, RetryPolicy::default());
+        let order = Arc::new(Mutex::new(Vec::new()));
+        runtime().block_on(async {
+            // Hold the only slot so the other jobs have to queue up.
+            let permit = scheduler.acquire(Priority::Batch, "a").await;
+            let mut handles = Vec::new();
+            for (name, priority) in [
+                ("background", Priority::Background),
+                ("batch", Priority::Batch),
+                ("interactive", Priority::Interactive),
+            ] {
+                let scheduler = scheduler.clone();
+                let order = order.clone();
+                handles.push(tokio::spawn(async move {
+                    scheduler
+                        .run(priority, "a", || async {
+                            order.lock().unwrap().push(name);
+                            Ok(())
+                        })
+                        .await
+                }));
+                tokio::task::yield_now().await;
+            }
+            drop(permit);
+            for handle in handles {
+                handle.await.unwrap().result.unwrap();
+            }
+        });
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["interactive", "batch", "background"]
+        );
+    }
+
+    #[test]
+    fn retryable_errors() {
+        let failed = |status| FlomError::ApiStatus {
+            status,
+            message: "odesli error".to_string(),
+        };
+        assert!(is_retryable(&failed(429)));
+        assert!(is_retryable(&failed(503)));
+        assert!(!is_retryable(&failed(404)));
+        // The status counts, not how the message is worded.
+        assert!(!is_retryable(&FlomError::Api("status=503".to_string())));
+    }
+
+    #[test]
+    fn lookups_share_one_limit_across_sites() {
+        let scheduler = Scheduler::new(2, RetryPolicy::default());
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let links = [
+            "https://open.spotify.com/track/a",
+            "https://music.apple.com/us/song/b/1",
+            "https://youtu.be/c",
+            "https://www.deezer.com/track/4",
+            "https://tidal.com/track/5",
+            "https://open.spotify.com/track/f",
+        ];
+        runtime().block_on(async {
+            let mut handles = Vec::new();
+            for link in links {
+                let (scheduler, running, peak) = (scheduler.clone(), running.clone(), peak.clone());
+                handles.push(tokio::spawn(async move {
+                    scheduler
+                        .run(Priority::Batch, lookup_key(link), || async {
+                            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                            peak.fetch_max(now, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(5)).await;
+                            running.fetch_sub(1, Ordering::SeqCst);
+                            Ok(())
+                        })
+                        .await
+                }));
+            }
+            for handle in handles {
+                handle.await.unwrap().result.unwrap();
+            }
+        });
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+}
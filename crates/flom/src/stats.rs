@@ -0,0 +1,205 @@
+//! `flom stats`: what the history says about how flom is used.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::history::HistoryEntry;
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct UsageStats {
+    pub conversions: usize,
+    /// Conversions that produced a link on the target platform.
+    pub converted: usize,
+    pub success_rate: f64,
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+    pub sources: Vec<Count>,
+    pub targets: Vec<Count>,
+    pub busiest_days: Vec<DayCount>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct Count {
+    pub name: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct DayCount {
+    pub day: NaiveDate,
+    pub count: usize,
+}
+
+/// Aggregates `entries`, keeping the `top` busiest days.
+pub fn usage_stats<'a>(
+    entries: impl IntoIterator<Item = &'a HistoryEntry>,
+    top: usize,
+) -> UsageStats {
+    let mut stats = UsageStats {
+        conversions: 0,
+        converted: 0,
+        success_rate: 0.0,
+        first: None,
+        last: None,
+        sources: Vec::new(),
+        targets: Vec::new(),
+        busiest_days: Vec::new(),
+    };
+    let mut sources = HashMap::new();
+    let mut targets = HashMap::new();
+    let mut days = HashMap::new();
+    for entry in entries {
+        let result = &entry.result;
+        stats.conversions += 1;
+        if result.target_url.is_some() {
+            stats.converted += 1;
+        }
+        let at = entry.recorded_at;
+        stats.first = Some(stats.first.map_or(at, |first| first.min(at)));
+        stats.last = Some(stats.last.map_or(at, |last| last.max(at)));
+        let platform = |name: &Option<String>| name.clone().unwrap_or_else(|| "unknown".into());
+        *sources
+            .entry(platform(&result.source_platform))
+            .or_insert(0) += 1;
+        *targets
+            .entry(platform(&result.target_platform))
+            .or_insert(0) += 1;
+        *days.entry(entry.recorded_at.date_naive()).or_insert(0) += 1;
+    }
+    if stats.conversions > 0 {
+        stats.success_rate = stats.converted as f64 / stats.conversions as f64;
+    }
+    stats.sources = ranked(sources);
+    stats.targets = ranked(targets);
+    let mut days: Vec<DayCount> = days
+        .into_iter()
+        .map(|(day, count)| DayCount { day, count })
+        .collect();
+    // Most conversions first; ties go to the more recent day.
+    days.sort_by(|a, b| b.count.cmp(&a.count).then(b.day.cmp(&a.day)));
+    days.truncate(top);
+    stats.busiest_days = days;
+    stats
+}
+
+/// Counts by name, largest first and alphabetical among equals.
+fn ranked(counts: HashMap<String, usize>) -> Vec<Count> {
+    let mut ranked: Vec<Count> = counts
+        .into_iter()
+        .map(|(name, count)| Count { name, count })
+        .collect();
+    ranked.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    ranked
+}
+
+pub fn render(stats: &UsageStats) -> String {
+    if stats.conversions == 0 {
+        return "No conversions recorded yet\n".to_string();
+    }
+    let mut out = format!(
+        "{} conversions, {} with a target link ({:.1}%)\n",
+        stats.conversions,
+        stats.converted,
+        stats.success_rate * 100.0
+    );
+    if let (Some(first), Some(last)) = (stats.first, stats.last) {
+        out.push_str(&format!(
+            "From {} to {}\n",
+            first.format("%Y-%m-%d"),
+            last.format("%Y-%m-%d")
+        ));
+    }
+    let width = stats
+        .sources
+        .iter()
+        .chain(&stats.targets)
+        .map(|count| count.name.len())
+        .max()
+        .unwrap_or(0);
+    for (title, counts) in [("Sources", &stats.sources), ("Targets", &stats.targets)] {
+        out.push_str(&format!("\n{title}:\n"));
+        for count in counts {
+            out.push_str(&format!(
+                "  {:width$}  {:>5}  {}\n",
+                count.name,
+                count.count,
+                share(count.count, stats.conversions)
+            ));
+        }
+    }
+    out.push_str("\nBusiest days:\n");
+    for day in &stats.busiest_days {
+        out.push_str(&format!("  {}  {:>5}\n", day.day, day.count));
+    }
+    out
+}
+
+fn share(count: usize, total: usize) -> String {
+    format!("{:.1}%", count as f64 * 100.0 / total as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flom_core::ConversionResult;
+
+    fn entry(recorded_at: &str, source: &str, target: &str, converted: bool) -> HistoryEntry {
+        HistoryEntry {
+            id: 0,
+            recorded_at: recorded_at.parse().unwrap(),
+            label: None,
+            result: ConversionResult {
+                source_platform: Some(source.to_string()),
+                target_platform: Some(target.to_string()),
+                target_url: converted.then(|| "https://example.com".to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn counts_platforms_days_and_success() {
+        let entries = [
+            entry("2024-05-01T10:00:00Z", "spotify", "appleMusic", true),
+            entry("2024-05-01T11:00:00Z", "spotify", "tidal", false),
+            entry("2024-05-02T09:00:00Z", "youtube", "appleMusic", true),
+            entry("2024-05-03T09:00:00Z", "deezer", "appleMusic", true),
+        ];
+        let stats = usage_stats(&entries, 2);
+        assert_eq!((stats.conversions, stats.converted), (4, 3));
+        assert_eq!(stats.success_rate, 0.75);
+        assert_eq!(
+            stats.sources[0],
+            Count {
+                name: "spotify".to_string(),
+                count: 2
+            }
+        );
+        assert_eq!(
+            stats
+                .sources
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            ["spotify", "deezer", "youtube"]
+        );
+        assert_eq!(stats.targets[0].count, 3);
+        assert_eq!(
+            stats.busiest_days,
+            [
+                DayCount {
+                    day: "2024-05-01".parse().unwrap(),
+                    count: 2
+                },
+                DayCount {
+                    day: "2024-05-03".parse().unwrap(),
+                    count: 1
+                },
+            ]
+        );
+        assert_eq!(stats.first, Some("2024-05-01T10:00:00Z".parse().unwrap()));
+        assert_eq!(stats.last, Some("2024-05-03T09:00:00Z".parse().unwrap()));
+    }
+}
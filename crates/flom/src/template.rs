@@ -0,0 +1,150 @@
+use std::borrow::Cow;
+
+use flom_core::{ConversionResult, FlomError, FlomResult, MediaType};
+
+/// Expands `{field}` placeholders with values from `result`. Each value is passed
+/// through `escape` (missing values as an empty string); `{{` and `}}` produce
+/// literal braces.
+pub fn render(
+    template: &str,
+    result: &ConversionResult,
+    escape: impl Fn(&str) -> String,
+) -> FlomResult<String> {
+    render_with(template, |name| {
+        field(result, name).map(|value| escape(value.as_deref().unwrap_or("")))
+    })
+}
+
+/// Checks that `template` only uses known fields, before any result exists.
+pub fn validate(template: &str) -> FlomResult<()> {
+    render(template, &ConversionResult::default(), str::to_string).map(|_| ())
+}
+
+/// Expands placeholders using `lookup`, which returns `None` for unknown names.
+pub fn render_with(
+    template: &str,
+    mut lookup: impl FnMut(&str) -> Option<String>,
+) -> FlomResult<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                let value = lookup(name.trim()).ok_or_else(|| {
+                    FlomError::InvalidInput(format!("unknown template field: {{{name}}}"))
+                })?;
+                out.push_str(&value);
+            }
+            _ => out.push(ch),
+        }
+    }
+    Ok(out)
+}
+
+/// Returns `None` for unknown field names and `Some(None)` for known but empty ones.
+fn field<'a>(result: &'a ConversionResult, name: &str) -> Option<Option<Cow<'a, str>>> {
+    let source = result.source_info.as_ref();
+    if name == "duration" {
+        return Some(
+            source
+                .and_then(|info| info.duration_label())
+                .map(Cow::Owned),
+        );
+    }
+    if name == "year" {
+        return Some(
+            source
+                .and_then(|info| info.release_year)
+                .map(|year| Cow::Owned(year.to_string())),
+        );
+    }
+    let value = match name {
+        "title" => source.and_then(|info| info.title.as_deref()),
+        "artist" => source.and_then(|info| info.artist.as_deref()),
+        "album" => source.and_then(|info| info.album.as_deref()),
+        "type" => result.media_type.map(MediaType::as_str),
+        "isrc" => source.and_then(|info| info.isrc.as_deref()),
+        "release_date" => source.and_then(|info| info.release_date.as_deref()),
+        "label" => source.and_then(|info| info.label.as_deref()),
+        "artwork_url" => source.and_then(|info| info.artwork_url.as_deref()),
+        "source_url" => Some(result.source_url.as_str()),
+        "target_url" => result.target_url.as_deref(),
+        "source_platform" => result.source_platform.as_deref(),
+        "target_platform" => result.target_platform.as_deref(),
+        "source_id" => result.source_id.as_deref(),
+        "target_id" => result.target_id.as_deref(),
+        "page_url" => result.page_url.as_deref(),
+        _ => return None,
+    };
+    Some(value.map(Cow::Borrowed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flom_core::MediaInfo;
+
+    fn result() -> ConversionResult {
+        ConversionResult {
+            source_url: "https://open.spotify.com/track/abc".to_string(),
+            target_platform: Some("appleMusic".to_string()),
+            source_info: Some(MediaInfo {
+                title: Some("Song".to_string()),
+                artist: Some("Artist".to_string()),
+                duration_ms: Some(3_725_000),
+                isrc: Some("USRC17607839".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn render_fields_and_escaped_braces() {
+        let out = render(
+            "{{{artist}}} {title} [{album}] -> {target_platform}",
+            &result(),
+            str::to_string,
+        )
+        .unwrap();
+        assert_eq!(out, "{Artist} Song [] -> appleMusic");
+    }
+
+    #[test]
+    fn render_track_details() {
+        let out = render(
+            "{duration} {isrc} [{release_date}]",
+            &result(),
+            str::to_string,
+        )
+        .unwrap();
+        assert_eq!(out, "1:02:05 USRC17607839 []");
+    }
+
+    #[test]
+    fn render_release_details() {
+        let mut result = result();
+        let info = result.source_info.as_mut().unwrap();
+        info.release_year = Some(2001);
+        info.label = Some("Virgin".to_string());
+        let out = render("{title} ({year}, {label})", &result, str::to_string).unwrap();
+        assert_eq!(out, "Song (2001, Virgin)");
+    }
+
+    #[test]
+    fn render_rejects_unknown_field() {
+        let err = render("{nope}", &result(), str::to_string).unwrap_err();
+        assert!(matches!(err, FlomError::InvalidInput(msg) if msg.contains("{nope}")));
+        assert!(validate("{title} {target_url} {page_url}").is_ok());
+        assert!(validate("{title} {nope}").is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use flom_config::flom_dir;
+use flom_core::{FlomError, FlomResult};
+use flom_music::api::odesli::OdesliResponse;
+use serde::{Deserialize, Serialize};
+
+/// A track waiting to show up on a platform, e.g. a release that is delayed in some regions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackWatch {
+    pub id: u64,
+    pub url: String,
+    /// Odesli platform key the track is expected on.
+    pub target: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    /// Send a desktop notification once the track is available.
+    #[serde(default)]
+    pub notify: bool,
+    pub added_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_checked: Option<DateTime<Utc>>,
+}
+
+impl TrackWatch {
+    pub fn new(url: &str, target: &str, response: &OdesliResponse, notify: bool) -> Self {
+        let entity = response
+            .entities_by_unique_id
+            .get(&response.entity_unique_id);
+        Self {
+            id: 0,
+            url: url.to_string(),
+            target: target.to_string(),
+            title: entity.and_then(|entity| entity.title.clone()),
+            artist: entity.and_then(|entity| entity.artist_name.clone()),
+            notify,
+            added_at: Utc::now(),
+            last_checked: None,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let title = self.title.as_deref().unwrap_or("Unknown title");
+        let artist = self.artist.as_deref().unwrap_or("Unknown artist");
+        format!("{title} / {artist}")
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TrackWatches {
+    #[serde(default)]
+    pub entries: Vec<TrackWatch>,
+}
+
+impl TrackWatches {
+    pub fn add(&mut self, mut watch: TrackWatch) -> u64 {
+        let id = self.entries.iter().map(|w| w.id).max().unwrap_or(0) + 1;
+        watch.id = id;
+        self.entries.push(watch);
+        id
+    }
+}
+
+pub fn watches_path() -> FlomResult<PathBuf> {
+    Ok(flom_dir()?.join("track-watches.json"))
+}
+
+pub fn load() -> FlomResult<TrackWatches> {
+    let path = watches_path()?;
+    if !path.exists() {
+        return Ok(TrackWatches::default());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|err| FlomError::Config(format!("failed to read track watches: {err}")))?;
+    serde_json::from_str(&content)
+        .map_err(|err| FlomError::Parse(format!("failed to parse track watches: {err}")))
+}
+
+pub fn save(watches: &TrackWatches) -> FlomResult<()> {
+    let path = watches_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| {
+            FlomError::Config(format!("failed to create track watches dir: {err}"))
+        })?;
+    }
+    let content = serde_json::to_string_pretty(watches)
+        .map_err(|err| FlomError::Parse(format!("failed to serialize track watches: {err}")))?;
+    fs::write(&path, content)
+        .map_err(|err| FlomError::Config(format!("failed to write track watches: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watch(url: &str) -> TrackWatch {
+        TrackWatch {
+            id: 0,
+            url: url.to_string(),
+            target: "spotify".to_string(),
+            title: None,
+            artist: Some("Artist".to_string()),
+            notify: false,
+            added_at: Utc::now(),
+            last_checked: None,
+        }
+    }
+
+    #[test]
+    fn add_assigns_ids_and_summary_falls_back() {
+        let mut watches = TrackWatches::default();
+        assert_eq!(watches.add(watch("https://music.apple.com/a")), 1);
+        assert_eq!(watches.add(watch("https://music.apple.com/b")), 2);
+        assert_eq!(watches.entries[0].summary(), "Unknown title / Artist");
+    }
+}
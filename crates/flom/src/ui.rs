@@ -0,0 +1,173 @@
+//! Terminal styling. Without the `color` feature `style` is a no-op wrapper
+//! with the same method names, so call sites don't need their own `cfg`.
+
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static HYPERLINKS: AtomicBool = AtomicBool::new(false);
+static ICONS: AtomicBool = AtomicBool::new(false);
+
+#[cfg(feature = "color")]
+pub use console::style;
+
+#[cfg(not(feature = "color"))]
+pub use plain::style;
+
+/// Turns OSC 8 hyperlinks in [`link`] on or off for the rest of the process.
+pub fn set_hyperlinks(enabled: bool) {
+    HYPERLINKS.store(enabled, Ordering::Relaxed);
+}
+
+/// Turns platform icons in human output on or off for the rest of the process.
+pub fn set_icons(enabled: bool) {
+    ICONS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn icons_enabled() -> bool {
+    ICONS.load(Ordering::Relaxed)
+}
+
+/// Whether stdout is a terminal known to render OSC 8 hyperlinks.
+pub fn hyperlinks_supported() -> bool {
+    io::stdout().is_terminal() && terminal_supports_hyperlinks(|name| env::var(name).ok())
+}
+
+/// Columns of the terminal on stdout, `COLUMNS` first; `None` when stdout is
+/// not a terminal and `COLUMNS` is unset.
+pub fn terminal_width() -> Option<usize> {
+    if let Some(columns) = env::var("COLUMNS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+    {
+        return Some(columns);
+    }
+    #[cfg(feature = "color")]
+    return console::Term::stdout()
+        .size_checked()
+        .map(|(_, columns)| usize::from(columns));
+    #[cfg(not(feature = "color"))]
+    None
+}
+
+/// `url`, made clickable when hyperlinks are on.
+pub fn link(url: &str) -> String {
+    if HYPERLINKS.load(Ordering::Relaxed) {
+        hyperlink(url)
+    } else {
+        url.to_string()
+    }
+}
+
+fn hyperlink(url: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{url}\x1b]8;;\x1b\\")
+}
+
+/// Detection from the environment, following the conventions of the
+/// `supports-hyperlinks` family of libraries (`FORCE_HYPERLINK` wins).
+fn terminal_supports_hyperlinks(var: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(force) = var("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+    if var("TERM").as_deref() == Some("dumb") || var("CI").is_some() {
+        return false;
+    }
+    if var("WT_SESSION").is_some() || var("KONSOLE_VERSION").is_some() {
+        return true;
+    }
+    if var("VTE_VERSION")
+        .and_then(|version| version.parse::<u32>().ok())
+        .is_some_and(|version| version >= 5000)
+    {
+        return true;
+    }
+    if var("TERM").is_some_and(|term| term.contains("kitty") || term.contains("alacritty")) {
+        return true;
+    }
+    var("TERM_PROGRAM").is_some_and(|program| {
+        matches!(
+            program.as_str(),
+            "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper"
+        )
+    })
+}
+
+#[cfg(not(feature = "color"))]
+mod plain {
+    use std::fmt;
+
+    pub struct Plain<D>(D);
+
+    pub fn style<D>(value: D) -> Plain<D> {
+        Plain(value)
+    }
+
+    impl<D> Plain<D> {
+        pub fn red(self) -> Self {
+            self
+        }
+
+        pub fn green(self) -> Self {
+            self
+        }
+
+        pub fn yellow(self) -> Self {
+            self
+        }
+
+        pub fn cyan(self) -> Self {
+            self
+        }
+
+        pub fn bold(self) -> Self {
+            self
+        }
+
+        pub fn dim(self) -> Self {
+            self
+        }
+    }
+
+    impl<D: fmt::Display> fmt::Display for Plain<D> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.fmt(f)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(vars: &[(&str, &str)]) -> bool {
+        terminal_supports_hyperlinks(|name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn hyperlink_wraps_url_in_osc8() {
+        assert_eq!(
+            hyperlink("https://x.test/a"),
+            "\x1b]8;;https://x.test/a\x1b\\https://x.test/a\x1b]8;;\x1b\\"
+        );
+        assert_eq!(link("https://x.test/a"), "https://x.test/a");
+    }
+
+    #[test]
+    fn detects_capable_terminals() {
+        assert!(detect(&[("TERM_PROGRAM", "WezTerm")]));
+        assert!(detect(&[("TERM", "xterm-kitty")]));
+        assert!(detect(&[("VTE_VERSION", "6003")]));
+        assert!(!detect(&[("VTE_VERSION", "4200")]));
+        assert!(!detect(&[("TERM_PROGRAM", "Apple_Terminal")]));
+        assert!(!detect(&[("TERM_PROGRAM", "iTerm.app"), ("CI", "true")]));
+        assert!(detect(&[("FORCE_HYPERLINK", "1"), ("TERM", "dumb")]));
+        assert!(!detect(&[
+            ("FORCE_HYPERLINK", "0"),
+            ("TERM_PROGRAM", "iTerm.app")
+        ]));
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct VersionInfo {
+    pub version: &'static str,
+    pub crates: BTreeMap<&'static str, &'static str>,
+    pub features: Vec<&'static str>,
+    pub target: &'static str,
+    /// Set only for builds with `SOURCE_DATE_EPOCH`.
+    pub build_date: Option<&'static str>,
+}
+
+pub fn version_info() -> VersionInfo {
+    VersionInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        crates: BTreeMap::from([
+            ("flom", env!("CARGO_PKG_VERSION")),
+            ("flom-config", flom_config::VERSION),
+            ("flom-core", flom_core::VERSION),
+            ("flom-music", flom_music::VERSION),
+            ("flom-shorten", flom_shorten::VERSION),
+        ]),
+        features: enabled_features(),
+        target: env!("FLOM_TARGET"),
+        build_date: option_env!("FLOM_BUILD_DATE"),
+    }
+}
+
+fn enabled_features() -> Vec<&'static str> {
+    [
+        ("interactive", cfg!(feature = "interactive")),
+        ("color", cfg!(feature = "color")),
+        ("encryption", cfg!(feature = "encryption")),
+        ("watch", cfg!(feature = "watch")),
+        ("signing", cfg!(feature = "signing")),
+        ("rustls", cfg!(feature = "rustls")),
+        ("native-tls", cfg!(feature = "native-tls")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::version_info;
+
+    #[test]
+    fn version_info_lists_workspace_crates() {
+        let info = version_info();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(info.crates.contains_key("flom-music"));
+        assert!(!info.target.is_empty());
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert!(json["crates"]["flom-core"].is_string());
+        assert_eq!(json["build_date"].is_string(), info.build_date.is_some());
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use flom_core::{ConversionResult, FlomError, FlomResult};
+use flom_music::MusicConverter;
+use flom_music::extract::extract_music_urls;
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::SortOrder;
+use crate::scheduler::{Priority, Scheduler};
+use crate::ui::style;
+
+/// Time given to whoever drops a file to finish writing it.
+const SETTLE_DELAY: Duration = Duration::from_millis(300);
+const CONVERTED_SUFFIX: &str = ".converted.md";
+
+/// Watches `dir` and converts every `.txt`/`.md` file created or changed in it,
+/// writing the links to a sibling `<name>.converted.md`. Runs until interrupted.
+pub async fn watch_dir(
+    dir: &Path,
+    converter: &MusicConverter,
+    target: &str,
+    order: SortOrder,
+) -> FlomResult<()> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })
+    .map_err(|err| FlomError::Config(format!("failed to start watcher: {err}")))?;
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|err| FlomError::Config(format!("failed to watch {}: {err}", dir.display())))?;
+
+    println!(
+        "{} Watching {} (Ctrl-C to stop)",
+        style("●").green(),
+        dir.display()
+    );
+
+    let scheduler = Scheduler::default();
+    let mut processed: HashMap<PathBuf, SystemTime> = HashMap::new();
+    while let Some(event) = rx.recv().await {
+        let event = match event {
+            Ok(event) => event,
+            Err(err) => {
+                crate::report_warning(&err);
+                continue;
+            }
+        };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            continue;
+        }
+        for path in event.paths.into_iter().filter(|path| is_link_file(path)) {
+            tokio::time::sleep(SETTLE_DELAY).await;
+            // One drop usually fires several events; convert each version once.
+            let Ok(modified) = fs::metadata(&path).and_then(|meta| meta.modified()) else {
+                continue;
+            };
+            if processed.get(&path) == Some(&modified) {
+                continue;
+            }
+            processed.insert(path.clone(), modified);
+            match convert_file(&path, &scheduler, converter, target, order).await {
+                Ok((output, count)) => println!(
+                    "{} {} ({count} links)",
+                    style("✓").green(),
+                    output.display()
+                ),
+                Err(err) => crate::report_failure(&path.display().to_string(), &err),
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn convert_file(
+    path: &Path,
+    scheduler: &Scheduler,
+    converter: &MusicConverter,
+    target: &str,
+    order: SortOrder,
+) -> FlomResult<(PathBuf, usize)> {
+    let content = fs::read_to_string(path).map_err(|err| {
+        FlomError::InvalidInput(format!("failed to read {}: {err}", path.display()))
+    })?;
+    let urls = extract_music_urls(&content);
+    let mut entries = Vec::with_capacity(urls.len());
+    for url in urls {
+        // Someone just dropped the file and is waiting for the result.
+        let outcome = crate::scheduled_process_url(
+            scheduler,
+            Priority::Interactive,
+            converter,
+            &url,
+            Some(target),
+            None,
+        )
+        .await
+        .result
+        .map(|mut results| {
+            crate::output::sort_results(&mut results, order);
+            results
+        });
+        entries.push((url, outcome));
+    }
+
+    let output = converted_path(path);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    fs::write(&output, converted_markdown(&name, &entries))
+        .map_err(|err| FlomError::Config(format!("failed to write {}: {err}", output.display())))?;
+    Ok((output, entries.len()))
+}
+
+fn is_link_file(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    (name.ends_with(".txt") || name.ends_with(".md")) && !name.ends_with(CONVERTED_SUFFIX)
+}
+
+fn converted_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!("{stem}{CONVERTED_SUFFIX}"))
+}
+
+fn converted_markdown(
+    name: &str,
+    entries: &[(String, FlomResult<Vec<ConversionResult>>)],
+) -> String {
+    let mut out = format!("# Converted links from {name}\n\n");
+    if entries.is_empty() {
+        out.push_str("No music links found.\n");
+    }
+    for (url, outcome) in entries {
+        match outcome {
+            Ok(results) => {
+                for result in results {
+                    let label = result
+                        .source_info
+                        .as_ref()
+                        .and_then(|info| match (&info.title, &info.artist) {
+                            (Some(title), Some(artist)) => Some(format!("{title} – {artist}")),
+                            (Some(title), None) => Some(title.clone()),
+                            _ => None,
+                        })
+                        .unwrap_or_else(|| url.clone());
+                    match &result.target_url {
+                        Some(target_url) => {
+                            out.push_str(&format!("- [{label}]({target_url}) (from {url})\n"))
+                        }
+                        None => out.push_str(&format!("- {label}: no target link ({url})\n")),
+                    }
+                }
+            }
+            Err(err) => out.push_str(&format!("- {url}: failed ({err})\n")),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flom_core::MediaInfo;
+
+    #[test]
+    fn link_files_and_converted_paths() {
+        assert!(is_link_file(Path::new("/drop/links.txt")));
+        assert!(is_link_file(Path::new("/drop/notes.md")));
+        assert!(!is_link_file(Path::new("/drop/notes.converted.md")));
+        assert!(!is_link_file(Path::new("/drop/cover.png")));
+        assert_eq!(
+            converted_path(Path::new("/drop/links.txt")),
+            PathBuf::from("/drop/links.converted.md")
+        );
+    }
+
+    #[test]
+    fn converted_markdown_lists_results_and_failures() {
+        let result = ConversionResult {
+            source_url: "https://open.spotify.com/track/abc".to_string(),
+            target_url: Some("https://music.apple.com/x".to_string()),
+            source_info: Some(MediaInfo {
+                title: Some("Song".to_string()),
+                artist: Some("Artist".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let entries = vec![
+            (result.source_url.clone(), Ok(vec![result])),
+            (
+                "https://youtu.be/xyz".to_string(),
+                Err(FlomError::Api("status=404".to_string())),
+            ),
+        ];
+        assert_eq!(
+            converted_markdown("links.txt", &entries),
+            "# Converted links from links.txt\n\n\
+             - [Song – Artist](https://music.apple.com/x) (from https://open.spotify.com/track/abc)\n\
+             - https://youtu.be/xyz: failed (api error: status=404)\n"
+        );
+    }
+}